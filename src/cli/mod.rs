@@ -1,2 +0,0 @@
-pub mod errors;
-pub mod helpers;
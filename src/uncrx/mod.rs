@@ -1,3 +0,0 @@
-pub mod constants;
-pub mod helpers;
-pub mod types;
@@ -0,0 +1,32 @@
+use uncrx::{analysis::analyze_crx_with_options, licenses::summarize_licenses};
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str) {
+    let data = read_crx_file(filename);
+
+    let report =
+        analyze_crx_with_options(&data, &get_parse_options()).expect("Failed to analyze crx");
+
+    if report.findings.is_empty() {
+        println!("No issues found");
+    }
+
+    for finding in &report.findings {
+        println!(
+            "[{}] {}: {}",
+            finding.severity, finding.category, finding.message
+        );
+    }
+
+    if report.licenses.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Licenses:");
+
+    for (license, paths) in summarize_licenses(&report.licenses) {
+        println!("  {}: {}", license, paths.join(", "));
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    env, fs,
+    io::{self, BufRead},
+    path::{Component, Path, PathBuf},
+    process,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use uncrx::{
+    batch::{run_batch, BatchJob, BatchOptions, BatchState, Extraction, BATCH_STATE_FILE_NAME},
+    cache::ExtractionCache,
+    id::is_valid_extension_id,
+    permissions::{FileModes, Ownership},
+    wrapper::get_path_wrapper,
+};
+
+use super::extract::ExtractArgs;
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{
+        exit_with_error, get_env, get_env_parsed, get_parse_options, is_crx_input, parse_duration,
+    },
+    style::format_throughput,
+};
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// CRX files, or directories searched recursively for CRX files
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Directory the zip payloads are written to, mirroring the input directories, defaults to
+    /// UNCRX_OUTPUT_DIR or ./out
+    #[arg(short, long)]
+    output_dir: Option<String>,
+    /// Number of CRX files read concurrently
+    #[arg(long)]
+    read_jobs: Option<usize>,
+    /// Number of CRX files parsed concurrently, defaults to UNCRX_JOBS or the number of CPUs
+    #[arg(long)]
+    parse_jobs: Option<usize>,
+    /// Number of zip payloads written concurrently
+    #[arg(long)]
+    extract_jobs: Option<usize>,
+    /// Number of CRX files waiting between two stages
+    #[arg(long)]
+    queue_size: Option<usize>,
+    /// Remove zip comments and extra fields from the written zips
+    #[arg(long)]
+    strip_zip_metadata: bool,
+    /// Password used to decrypt encrypted zip entries
+    #[arg(long)]
+    password: Option<String>,
+    /// File recording completed CRX files, defaults to <OUTPUT_DIR>/.uncrx-batch-state
+    #[arg(long)]
+    state: Option<String>,
+    /// Skip the CRX files the state file records as completed
+    #[arg(long)]
+    resume: bool,
+    /// Reuse the zips already extracted from identical CRX files, cached in this directory
+    #[arg(long)]
+    cache: Option<String>,
+    /// Give up on a CRX file taking longer than this to parse, e.g. 30s, 500ms or 2m
+    #[arg(long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+    /// Mode of the written files and directories, e.g. 0644/0755 or 0640
+    #[arg(long, value_name = "MODES")]
+    chmod: Option<FileModes>,
+    /// Owner of the written files and directories, as USER, USER:GROUP or :GROUP
+    #[arg(long, value_name = "OWNER")]
+    chown: Option<Ownership>,
+}
+
+pub fn run(args: BatchArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let output_dir = current_dir.join(get_batch_output_dir(args.output_dir));
+
+    let mut jobs = vec![];
+
+    for input in &args.inputs {
+        let input = current_dir.join(input);
+
+        if input.is_dir() {
+            collect_jobs(&input, &input, &output_dir, &mut jobs);
+        } else if input.is_file() {
+            let file_name = input.file_name().expect("Failed to get file name");
+
+            jobs.push(BatchJob {
+                output: get_batch_output(&output_dir, Path::new(file_name)),
+                input,
+            });
+        } else {
+            exit_with_error(UncrxCliError::NotFound(input.to_str().unwrap().to_string()));
+        }
+    }
+
+    let state_file = match args.state {
+        Some(path) => current_dir.join(path),
+        None => output_dir.join(BATCH_STATE_FILE_NAME),
+    };
+
+    let mut state = BatchState::open(&state_file, args.resume).expect("Failed to open state file");
+
+    let total = jobs.len();
+    jobs.retain(|job| !state.is_completed(job));
+
+    if jobs.len() < total {
+        println!(
+            "Resuming, {} of {} CRX files already extracted",
+            total - jobs.len(),
+            total
+        );
+    }
+
+    let defaults = BatchOptions::default();
+
+    let options = BatchOptions {
+        read_jobs: args.read_jobs.unwrap_or(defaults.read_jobs),
+        parse_jobs: args
+            .parse_jobs
+            .or_else(|| get_env_parsed("UNCRX_JOBS"))
+            .unwrap_or(defaults.parse_jobs),
+        extract_jobs: args.extract_jobs.unwrap_or(defaults.extract_jobs),
+        queue_size: args.queue_size.unwrap_or(defaults.queue_size),
+        strip_zip_metadata: args.strip_zip_metadata,
+        password: args.password,
+        parse: get_parse_options(),
+        cache: args
+            .cache
+            .map(|path| ExtractionCache::new(current_dir.join(path))),
+        timeout: args.timeout,
+        file_modes: args.chmod,
+        ownership: args.chown,
+    };
+
+    run_jobs(jobs, &options, Some(&mut state), vec![]);
+}
+
+// Reads one CRX path or extension id per line, as printed by find or ls
+pub fn run_stdin_list(args: ExtractArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let output_dir = current_dir.join(get_batch_output_dir(args.output_dir));
+
+    let mut jobs = vec![];
+    let mut rejected = vec![];
+
+    for line in io::stdin().lock().lines() {
+        let line = line.expect("Failed to read stdin");
+        let line = line.trim().trim_start_matches("./");
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if is_valid_extension_id(line) {
+            rejected.push((
+                line.to_string(),
+                "Downloading extensions by id is not supported".to_string(),
+            ));
+            continue;
+        }
+
+        let path = Path::new(line);
+
+        // Relative paths are mirrored in the output directory, others only keep their name
+        let relative = match path.is_relative()
+            && path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            true => path,
+            false => Path::new(path.file_name().unwrap_or(path.as_os_str())),
+        };
+
+        jobs.push(BatchJob {
+            input: current_dir.join(path),
+            output: get_batch_output(&output_dir, relative),
+        });
+    }
+
+    let defaults = BatchOptions::default();
+
+    let options = BatchOptions {
+        parse_jobs: get_env_parsed("UNCRX_JOBS").unwrap_or(defaults.parse_jobs),
+        strip_zip_metadata: args.strip_zip_metadata,
+        password: args.password,
+        file_modes: args.chmod,
+        ownership: args.chown,
+        ..defaults
+    };
+
+    run_jobs(jobs, &options, None, rejected);
+}
+
+// extension.crx.gz is written to extension.zip, like extension.crx
+fn get_batch_output(output_dir: &Path, relative: &Path) -> PathBuf {
+    let relative = match get_path_wrapper(relative) {
+        Some(_) => relative.with_extension(""),
+        None => relative.to_path_buf(),
+    };
+
+    output_dir.join(relative).with_extension("zip")
+}
+
+fn get_batch_output_dir(output_dir: Option<String>) -> String {
+    output_dir
+        .or_else(|| get_env("UNCRX_OUTPUT_DIR"))
+        .unwrap_or_else(|| "out".to_string())
+}
+
+fn run_jobs(
+    jobs: Vec<BatchJob>,
+    options: &BatchOptions,
+    mut state: Option<&mut BatchState>,
+    rejected: Vec<(String, String)>,
+) {
+    let start = Instant::now();
+    let mut extracted = 0;
+    let mut failed = rejected.len();
+    let mut read = 0;
+
+    for (input, error) in &rejected {
+        eprintln!("{}: {}", input, error);
+    }
+
+    run_batch(jobs, options, |outcome| match outcome.result {
+        Ok(extraction) => {
+            extracted += 1;
+            read += fs::metadata(&outcome.job.input).map_or(0, |metadata| metadata.len());
+
+            if let Some(state) = state.as_mut() {
+                state
+                    .record(&outcome.job)
+                    .expect("Failed to write state file");
+            }
+
+            println!(
+                "{} -> {}{}",
+                outcome.job.input.display(),
+                outcome.job.output.display(),
+                match extraction {
+                    Extraction::Cached => " (cached)",
+                    Extraction::Written => "",
+                }
+            );
+        }
+        Err(error) => {
+            failed += 1;
+            eprintln!("{}: {}", outcome.job.input.display(), error);
+        }
+    });
+
+    let duration = start.elapsed();
+
+    println!(
+        "{} extracted, {} failed in {} ms ({})",
+        extracted,
+        failed,
+        duration.as_millis(),
+        format_throughput(read, duration)
+    );
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn collect_jobs(root: &Path, directory: &Path, output_dir: &Path, jobs: &mut Vec<BatchJob>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)
+        .expect("Failed to read directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_jobs(root, &path, output_dir, jobs);
+        } else if is_crx_input(&path) {
+            let relative = path
+                .strip_prefix(root)
+                .expect("Failed to get relative path");
+
+            jobs.push(BatchJob {
+                output: get_batch_output(output_dir, relative),
+                input: path,
+            });
+        }
+    }
+}
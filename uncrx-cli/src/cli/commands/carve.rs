@@ -0,0 +1,54 @@
+use std::{env, fs};
+
+use uncrx::carve::find_crx_files_with_options;
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, get_parse_options},
+    interrupt::track_partial_output,
+};
+
+pub fn run(filename: &str, output_dir: Option<String>) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let input_file = current_dir.join(filename);
+
+    if !input_file.is_file() {
+        exit_with_error(UncrxCliError::NotFound(
+            input_file.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let data = fs::read(&input_file).expect("Failed to read file");
+
+    let output_dir = match output_dir {
+        Some(path) => current_dir.join(path),
+        None => current_dir.join("carved"),
+    };
+
+    let carved = find_crx_files_with_options(&data, &get_parse_options());
+
+    if carved.is_empty() {
+        println!("No CRX files found");
+        return;
+    }
+
+    fs::create_dir_all(&output_dir).expect("Failed to create directory");
+
+    for crx in &carved {
+        let output_file = output_dir.join(format!("{:010x}.crx", crx.offset));
+
+        let partial = track_partial_output(&output_file);
+        fs::write(&output_file, &data[crx.offset..crx.offset + crx.length])
+            .expect("Failed to write file");
+        drop(partial);
+
+        println!(
+            "CRX{} at byte {:#x} ({} bytes) -> {}",
+            crx.version,
+            crx.offset,
+            crx.length,
+            output_file.display()
+        );
+    }
+}
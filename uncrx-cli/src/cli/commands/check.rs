@@ -0,0 +1,67 @@
+use std::{env, process};
+
+use clap::Args;
+use uncrx::{
+    hash::HashAlgorithm,
+    helpers::parse_crx_with_options,
+    provenance::{find_tree_changes, get_entry_hashes, read_extraction_manifest},
+};
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, get_parse_options, read_crx_file},
+};
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Directory written by an extraction
+    directory: String,
+    /// Compare with the files of this CRX file instead of .uncrx-manifest.json
+    #[arg(long, value_name = "CRX")]
+    crx: Option<String>,
+    /// Digest compared with --crx, sha256, sha512 or blake3
+    #[arg(long, value_name = "ALGORITHM", default_value_t, requires = "crx")]
+    hash: HashAlgorithm,
+}
+
+pub fn run(args: CheckArgs) {
+    let directory = env::current_dir()
+        .expect("Failed to get current directory")
+        .join(&args.directory);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let (files, hash_algorithm) = match args.crx {
+        Some(filename) => {
+            let extension = parse_crx_with_options(&read_crx_file(&filename), &get_parse_options())
+                .expect("Failed to parse crx");
+            let files = get_entry_hashes(&extension.zip, args.hash).expect("Failed to hash files");
+
+            (files, args.hash)
+        }
+        None => {
+            let manifest =
+                read_extraction_manifest(&directory).expect("Failed to read .uncrx-manifest.json");
+
+            (manifest.files, manifest.hash_algorithm)
+        }
+    };
+
+    let changes =
+        find_tree_changes(&directory, &files, hash_algorithm).expect("Failed to check directory");
+
+    if changes.is_empty() {
+        println!("No changes since extraction");
+        return;
+    }
+
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    process::exit(1);
+}
@@ -0,0 +1,33 @@
+use uncrx::{compat::get_compatibility_report, helpers::parse_crx_with_options};
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str) {
+    let data = read_crx_file(filename);
+
+    let extension =
+        parse_crx_with_options(&data, &get_parse_options()).expect("Failed to parse crx");
+
+    let report = get_compatibility_report(&extension).expect("Failed to build report");
+
+    for requirement in &report.requirements {
+        println!(
+            "Chrome {:>3}  {} ({})",
+            requirement.minimum_version, requirement.feature, requirement.source
+        );
+    }
+
+    let declared = report
+        .declared_minimum_version
+        .map_or("not declared".to_string(), |version| version.to_string());
+    let required = report
+        .required_minimum_version()
+        .map_or("any".to_string(), |version| version.to_string());
+
+    println!("Declared minimum Chrome version: {}", declared);
+    println!("Required minimum Chrome version: {}", required);
+
+    if !report.is_declared_version_sufficient() {
+        println!("minimum_chrome_version should be at least {}", required);
+    }
+}
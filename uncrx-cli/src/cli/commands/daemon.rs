@@ -0,0 +1,249 @@
+use std::{
+    borrow::Cow,
+    env,
+    fs::{self, File},
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender, SyncSender},
+    thread,
+};
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uncrx::{
+    archive::{decrypt_zip, ensure_not_encrypted, strip_zip_metadata},
+    helpers::get_zip_payload_with_options,
+    id::get_extension_id,
+    manifest::get_manifest,
+    validation::validate_manifest,
+};
+
+use crate::cli::{
+    helpers::get_parse_options,
+    pool::{get_worker_count, spawn_workers},
+};
+
+// Every worker keeps the buffer CRX files are read into between jobs, unless an unusually
+// large file grew it past this
+const MAX_RETAINED_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Path of the Unix socket to listen on
+    #[arg(long)]
+    socket: String,
+    /// Number of jobs processed concurrently, defaults to the number of CPUs
+    #[arg(long)]
+    workers: Option<usize>,
+    /// Number of jobs waiting for a worker before clients are made to wait
+    #[arg(long, default_value_t = 64)]
+    queue_size: usize,
+}
+
+// One JSON object per line, answered by one JSON object per line. Relative paths are
+// resolved against the directory the daemon was started from.
+#[derive(Deserialize)]
+struct Request {
+    id: Option<Value>,
+    #[serde(flatten)]
+    job: Job,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "job", rename_all = "snake_case")]
+enum Job {
+    Extract {
+        path: String,
+        output_dir: String,
+        #[serde(default)]
+        strip_zip_metadata: bool,
+        password: Option<String>,
+    },
+    Id {
+        path: String,
+    },
+    Manifest {
+        path: String,
+    },
+    Validate {
+        path: String,
+    },
+}
+
+#[derive(Serialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+type QueuedJob = (Job, Sender<anyhow::Result<Value>>);
+
+pub fn run(args: DaemonArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let socket = current_dir.join(&args.socket);
+
+    // A socket nobody answers on is left over from a previous run
+    if socket.exists() && UnixStream::connect(&socket).is_err() {
+        fs::remove_file(&socket).expect("Failed to remove stale socket");
+    }
+
+    let listener = UnixListener::bind(&socket).expect("Failed to bind socket");
+
+    // The bounded queue blocks connections once every worker is busy and it is full
+    let queue = spawn_workers(
+        get_worker_count(args.workers),
+        args.queue_size,
+        Vec::new,
+        move |buffer, (job, reply): QueuedJob| {
+            let _ = reply.send(run_job(job, &current_dir, buffer));
+
+            if buffer.capacity() > MAX_RETAINED_BUFFER_SIZE {
+                *buffer = Vec::new();
+            }
+        },
+    );
+
+    println!("Listening on {}", socket.display());
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let queue = queue.clone();
+
+        thread::spawn(move || {
+            if let Err(error) = handle_connection(stream, &queue) {
+                eprintln!("Failed to answer request: {}", error);
+            }
+        });
+    }
+}
+
+fn handle_connection(stream: UnixStream, queue: &SyncSender<QueuedJob>) -> anyhow::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (reply, result) = mpsc::channel();
+                queue.send((request.job, reply))?;
+
+                match result.recv()? {
+                    Ok(result) => Response {
+                        id: request.id,
+                        ok: true,
+                        result: Some(result),
+                        error: None,
+                    },
+                    Err(error) => Response {
+                        id: request.id,
+                        ok: false,
+                        result: None,
+                        error: Some(error.to_string()),
+                    },
+                }
+            }
+            Err(error) => Response {
+                id: None,
+                ok: false,
+                result: None,
+                error: Some(format!("Invalid request: {}", error)),
+            },
+        };
+
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+impl Job {
+    fn path(&self) -> &str {
+        match self {
+            Job::Extract { path, .. }
+            | Job::Id { path }
+            | Job::Manifest { path }
+            | Job::Validate { path } => path,
+        }
+    }
+}
+
+fn run_job(job: Job, current_dir: &Path, buffer: &mut Vec<u8>) -> anyhow::Result<Value> {
+    let data = read_into(buffer, current_dir, job.path())?;
+    let options = get_parse_options();
+
+    match job {
+        Job::Extract {
+            output_dir,
+            strip_zip_metadata: strip,
+            password,
+            ..
+        } => {
+            let payload = get_zip_payload_with_options(data, &options)?;
+
+            // Written straight from the read buffer unless it has to be changed
+            let zip = match password {
+                Some(password) => Cow::Owned(decrypt_zip(payload, password.as_bytes())?),
+                None => {
+                    ensure_not_encrypted(payload)?;
+                    Cow::Borrowed(payload)
+                }
+            };
+
+            let zip = match strip {
+                true => Cow::Owned(strip_zip_metadata(&zip)?),
+                false => zip,
+            };
+
+            let output_dir: PathBuf = current_dir.join(output_dir);
+            fs::create_dir_all(&output_dir)?;
+
+            let output_file = output_dir.join("extension.zip");
+            fs::write(&output_file, &zip)?;
+
+            Ok(json!({ "output": output_file }))
+        }
+        Job::Id { .. } => Ok(json!({ "id": get_extension_id(data)? })),
+        Job::Manifest { .. } => get_manifest(get_zip_payload_with_options(data, &options)?),
+        Job::Validate { .. } => {
+            let manifest = get_manifest(get_zip_payload_with_options(data, &options)?)?;
+
+            let errors: Vec<_> = validate_manifest(&manifest)
+                .iter()
+                .map(|error| json!({ "path": error.path, "message": error.message }))
+                .collect();
+
+            Ok(json!({ "valid": errors.is_empty(), "errors": errors }))
+        }
+    }
+}
+
+// Reuses the capacity left by previous jobs
+fn read_into<'a>(
+    buffer: &'a mut Vec<u8>,
+    current_dir: &Path,
+    path: &str,
+) -> anyhow::Result<&'a [u8]> {
+    buffer.clear();
+
+    File::open(current_dir.join(path))
+        .and_then(|mut file| file.read_to_end(buffer))
+        .map_err(|error| anyhow::anyhow!("Failed to read {}: {}", path, error))?;
+
+    Ok(buffer)
+}
@@ -0,0 +1,97 @@
+use std::{env, fs};
+
+use clap::Args;
+use uncrx::{
+    cache::HttpCache,
+    download::{is_url, DownloadOptions},
+    helpers::get_zip_start_offset_with_options,
+    id::is_valid_extension_id,
+    webstore::{download_url, get_host_platform, DEFAULT_CHROME_VERSION},
+};
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{
+        describe_crx_error, download_file, exit_with_error, get_parse_options, is_offline,
+        parse_size,
+    },
+    interrupt::track_partial_output,
+    style::format_size,
+};
+
+#[derive(Args)]
+pub struct DownloadArgs {
+    /// Chrome Web Store extension id, or URL of a CRX file
+    source: String,
+    /// Path of the CRX file to write, defaults to <ID>.crx or the file name in the URL
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Chrome version reported to the Web Store, which serves the newest package supporting it
+    #[arg(long, value_name = "VERSION", default_value = DEFAULT_CHROME_VERSION)]
+    chrome_version: String,
+    /// Refuse to download more than this, e.g. 500K, defaults to 256M
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_download_size: Option<u64>,
+    /// Limit the download to this many bytes per second, e.g. 500K
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    rate_limit: Option<u64>,
+    /// Keep downloads in this directory, and only download them again once their ETag or
+    /// Last-Modified date changed
+    #[arg(long, value_name = "DIR")]
+    http_cache: Option<String>,
+}
+
+pub fn run(args: DownloadArgs) {
+    let (url, default_output) = match (is_url(&args.source), is_valid_extension_id(&args.source)) {
+        (true, _) => (args.source.clone(), get_url_file_name(&args.source)),
+        (false, true) => {
+            let (os, arch) = get_host_platform();
+            let url = download_url(&args.source, &args.chrome_version, os, arch)
+                .expect("Failed to build the download URL");
+
+            (url, format!("{}.crx", args.source))
+        }
+        (false, false) => exit_with_error(UncrxCliError::InvalidExtensionId(args.source)),
+    };
+
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let defaults = DownloadOptions::default();
+    let options = DownloadOptions {
+        max_size: args.max_download_size.unwrap_or(defaults.max_size),
+        rate_limit: args.rate_limit,
+        offline: is_offline(),
+        cache: args
+            .http_cache
+            .map(|path| HttpCache::new(current_dir.join(path))),
+    };
+
+    let data = download_file(&url, &options);
+
+    // Checked before anything is written, servers answer unknown ids with an error page
+    if let Err(error) = get_zip_start_offset_with_options(&data, &get_parse_options()) {
+        exit_with_error(UncrxCliError::InvalidCrx(describe_crx_error(&error)));
+    }
+
+    let output = current_dir.join(args.output.unwrap_or(default_output));
+
+    let partial = track_partial_output(&output);
+    fs::write(&output, &data).expect("Failed to write file");
+    drop(partial);
+
+    println!(
+        "Downloaded {} ({}) to {}",
+        args.source,
+        format_size(data.len() as u64),
+        output.display()
+    );
+}
+
+fn get_url_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+
+    match path.rsplit('/').next() {
+        Some(name) if name.to_ascii_lowercase().ends_with(".crx") => name.to_string(),
+        _ => "extension.crx".to_string(),
+    }
+}
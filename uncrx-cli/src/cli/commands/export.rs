@@ -0,0 +1,63 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Write},
+};
+
+use clap::Args;
+use uncrx::export::{get_corpus_records, write_csv, CorpusRecord, ExportFormat};
+
+use crate::cli::{errors::UncrxCliError, helpers::exit_with_error};
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Corpus directory laid out as <ID>/<VERSION>
+    directory: String,
+    /// Output file, printed to stdout when omitted
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Output format: csv or parquet, guessed from the output file extension by default
+    #[arg(long)]
+    format: Option<ExportFormat>,
+}
+
+pub fn run(args: ExportArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    let directory = current_dir.join(&args.directory);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let output = args.output.map(|output| current_dir.join(output));
+
+    let format = args.format.unwrap_or_else(|| match &output {
+        Some(path) => ExportFormat::from_path(path),
+        None => ExportFormat::Csv,
+    });
+
+    let records = get_corpus_records(&directory).expect("Failed to read corpus");
+
+    let writer: Box<dyn Write + Send> = match output {
+        Some(path) => Box::new(File::create(path).expect("Failed to create file")),
+        None => Box::new(io::stdout()),
+    };
+
+    match format {
+        ExportFormat::Csv => write_csv(&records, writer),
+        ExportFormat::Parquet => write_parquet(&records, writer),
+    }
+    .expect("Failed to export corpus");
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(records: &[CorpusRecord], writer: impl Write + Send) -> anyhow::Result<()> {
+    uncrx::export::write_parquet(records, writer)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_records: &[CorpusRecord], _writer: impl Write + Send) -> anyhow::Result<()> {
+    exit_with_error(UncrxCliError::FeatureDisabled("parquet".to_string()));
+}
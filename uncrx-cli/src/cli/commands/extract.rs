@@ -0,0 +1,791 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use serde_json::{json, Value};
+use uncrx::{
+    analysis::{analyze_crx_with_options, Severity},
+    archive::{
+        decrypt_zip, find_nested_crx_files, get_encrypted_entries, recover_zip, strip_zip_metadata,
+        unpack_zip_with_options, UnpackOptions,
+    },
+    cache::HttpCache,
+    corpus::{merge_into_corpus, CorpusMerge},
+    download::{is_url, DownloadOptions},
+    errors::UncrxError,
+    hash::HashAlgorithm,
+    helpers::parse_crx_with_options,
+    id::get_extension_id,
+    manifest::{parse_manifest, MANIFEST_FILE_NAME},
+    opened::OpenedCrx,
+    output::{OutputFormat, DEFAULT_OUTPUT_FORMAT},
+    permissions::{apply_permissions, FileModes, Ownership},
+    provenance::{
+        get_entry_hashes, write_extraction_manifest, ExtractionManifest,
+        EXTRACTION_MANIFEST_FILE_NAME,
+    },
+    staging::{create_staging_dir, run_scanners},
+    stream::copy_zip_payload_with_options,
+    swhid::{get_tree_swhids, TreeSwhids},
+    wrapper::get_path_wrapper,
+};
+use zip::ZipArchive;
+
+use crate::cli::{
+    commands::batch::run_stdin_list,
+    errors::UncrxCliError,
+    formats::get_output_formats,
+    helpers::{
+        describe_crx_error, download_file, exit_with_error, get_env, get_parse_options,
+        has_sidecar_hash, is_offline, open_crx_file, parse_size, read_crx_file, read_input_file,
+        read_zip_file, verify_sha256,
+    },
+    interrupt::track_partial_output,
+    style::{format_size, format_throughput, paint, print_summary, DIMMED, SUCCESS},
+};
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    /// Directory the output is written to, defaults to UNCRX_OUTPUT_DIR or ./out
+    #[arg(short, long)]
+    pub output_dir: Option<String>,
+    /// Remove zip comments and extra fields from the written zip
+    #[arg(long)]
+    pub strip_zip_metadata: bool,
+    /// Password used to decrypt encrypted zip entries
+    #[arg(long)]
+    pub password: Option<String>,
+    /// Treat the input as a zip of CRX files and extract each of them
+    #[arg(long)]
+    pub recursive_archives: bool,
+    /// Fail unless the input file has this SHA-256 hash
+    #[arg(long, value_name = "HASH", conflicts_with = "stdin_list")]
+    pub expect_sha256: Option<String>,
+    /// Unpack into <DIR>/<ID>/<VERSION>, leaving versions already in DIR untouched
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output_dir", "recursive_archives"])]
+    pub into: Option<String>,
+    /// Treat the input as a plain zip instead of a CRX file
+    #[arg(long, conflicts_with_all = ["into", "recursive_archives"])]
+    pub force_zip: bool,
+    /// Salvage the entries of a truncated zip payload from their local headers
+    #[arg(long)]
+    pub repair: bool,
+    /// Read the CRX files to extract from stdin, one path per line
+    #[arg(long)]
+    pub stdin_list: bool,
+    /// Mode of the written files and directories, e.g. 0644/0755 or 0640
+    #[arg(long, value_name = "MODES")]
+    pub chmod: Option<FileModes>,
+    /// Owner of the written files and directories, as USER, USER:GROUP or :GROUP
+    #[arg(long, value_name = "OWNER")]
+    pub chown: Option<Ownership>,
+    /// Unpack into a quarantine directory first, and only write the output if the analysis
+    /// and scanners find no issues
+    #[arg(long, conflicts_with_all = ["recursive_archives", "stdin_list"])]
+    pub staging: bool,
+    /// Scanner command run on the quarantine directory, passed as its last argument. A
+    /// non-zero exit status rejects the extension
+    #[arg(long, value_name = "COMMAND", requires = "staging")]
+    pub scanner: Vec<String>,
+    /// Record the source hash, extension id and version and the hash of every written file
+    /// in .uncrx-manifest.json
+    #[arg(long, conflicts_with_all = ["recursive_archives", "stdin_list"])]
+    pub write_manifest: bool,
+    /// Print the Software Heritage identifier (SWHID) of the extracted tree, and with --json
+    /// those of every file
+    #[arg(long, conflicts_with_all = ["recursive_archives", "stdin_list"])]
+    pub swhid: bool,
+    /// Digest used in .uncrx-manifest.json, sha256, sha512 or blake3
+    #[arg(
+        long,
+        value_name = "ALGORITHM",
+        default_value_t,
+        requires = "write_manifest"
+    )]
+    pub hash: HashAlgorithm,
+    /// Refuse to download more than this from an HTTP(S) URL, e.g. 500K, defaults to 256M
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_download_size: Option<u64>,
+    /// Limit downloads to this many bytes per second, e.g. 500K
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub rate_limit: Option<u64>,
+    /// Keep downloads in this directory, and only download them again once their ETag or
+    /// Last-Modified date changed
+    #[arg(long, value_name = "DIR")]
+    pub http_cache: Option<String>,
+    /// Size of the write buffer of every unpacked file, e.g. 1M, defaults to 256K
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub chunk_size: Option<u64>,
+    /// Evict unpacked files from the page cache once written, on Linux, to keep large
+    /// extractions from crowding out other processes
+    #[arg(long)]
+    pub drop_page_cache: bool,
+    /// Print the extraction summary, timing included, as JSON
+    #[arg(long, conflicts_with_all = ["recursive_archives", "stdin_list"])]
+    pub json: bool,
+    /// Write the payload as a zip, an unpacked directory or a tar archive, defaults to
+    /// UNCRX_FORMAT or zip
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        conflicts_with_all = ["into", "recursive_archives", "stdin_list"]
+    )]
+    pub format: Option<String>,
+}
+
+// What runs when no subcommand is given, and for the extract subcommand
+#[derive(Args)]
+pub struct ExtractCommandArgs {
+    #[arg(required_unless_present = "stdin_list", conflicts_with = "stdin_list")]
+    pub filename: Option<String>,
+    #[command(flatten)]
+    pub extract: ExtractArgs,
+}
+
+pub fn dispatch(args: ExtractCommandArgs) {
+    let filename = args.filename.unwrap_or_default();
+    let mut extract = args.extract;
+
+    extract.output_dir = extract.output_dir.or_else(|| get_env("UNCRX_OUTPUT_DIR"));
+
+    // --format conflicts with the other modes, the variable only applies where it would
+    if !extract.stdin_list && !extract.recursive_archives && extract.into.is_none() {
+        extract.format = extract.format.or_else(|| get_env("UNCRX_FORMAT"));
+    }
+
+    match (extract.stdin_list, extract.recursive_archives) {
+        (true, _) => run_stdin_list(extract),
+        (false, true) => run_archive(&filename, extract),
+        (false, false) => run(&filename, extract),
+    }
+}
+
+pub fn run(filename: &str, args: ExtractArgs) {
+    let start = Instant::now();
+
+    let formats = get_output_formats(get_unpack_options(&args));
+    let format_name = args.format.as_deref().unwrap_or(DEFAULT_OUTPUT_FORMAT);
+    let format = formats.get(format_name).unwrap_or_else(|| {
+        exit_with_error(UncrxCliError::UnknownFormat(
+            format_name.to_string(),
+            formats.names().iter().map(ToString::to_string).collect(),
+        ))
+    });
+
+    if can_stream(filename, &args) {
+        run_streamed(filename, args, format, start);
+        return;
+    }
+
+    let data = match (is_url(filename), args.force_zip) {
+        (true, _) => download_file(filename, &get_download_options(&args)),
+        (false, true) => read_input_file(filename),
+        (false, false) => read_crx_file(filename),
+    };
+
+    if let Some(expected) = &args.expect_sha256 {
+        verify_sha256(&data, expected);
+    }
+
+    let payload = match args.force_zip {
+        true => data.clone(),
+        false => get_payload(&data),
+    };
+
+    let payload = match args.repair {
+        true => repair_payload(&payload),
+        false => payload,
+    };
+
+    let zip = get_zip(payload, args.strip_zip_metadata, args.password.as_deref());
+    let unpack_options = get_unpack_options(&args);
+
+    if let Some(into) = args.into {
+        let root = env::current_dir()
+            .expect("Failed to get current directory")
+            .join(into);
+
+        if args.staging {
+            review_staged(
+                &data,
+                &zip,
+                !args.force_zip,
+                &args.scanner,
+                &unpack_options,
+                &root,
+            );
+        }
+
+        let (status, output) = match merge_into_corpus(&root, &data, &zip, &unpack_options)
+            .expect("Failed to merge into corpus")
+        {
+            CorpusMerge::Added(path) => {
+                if args.write_manifest {
+                    let files = get_entry_hashes(&zip, args.hash).expect("Failed to hash files");
+                    write_manifest(&path, &data, &zip, files, args.hash);
+                }
+
+                set_permissions(&path, args.chmod, args.chown);
+                ("Extracted", path)
+            }
+            CorpusMerge::Present(path) => ("Present", path),
+        };
+
+        print_extraction_summary(
+            status,
+            &get_summary(&data, &zip, args.swhid),
+            &output,
+            start.elapsed(),
+            args.json,
+        );
+        return;
+    }
+
+    let output_dir = get_output_dir(args.output_dir);
+
+    if args.staging {
+        review_staged(
+            &data,
+            &zip,
+            !args.force_zip,
+            &args.scanner,
+            &unpack_options,
+            &output_dir,
+        );
+    }
+
+    let output_file = format.output_path(&output_dir);
+
+    let partial = track_partial_output(&output_file);
+    format
+        .write(&zip, &output_file)
+        .expect("Failed to write output");
+    drop(partial);
+    set_permissions(&output_file, args.chmod, args.chown);
+
+    if args.write_manifest {
+        write_output_manifest(&output_dir, &output_file, &data, &zip, args.hash);
+
+        set_permissions(
+            &get_manifest_dir(&output_dir, &output_file).join(EXTRACTION_MANIFEST_FILE_NAME),
+            args.chmod,
+            args.chown,
+        );
+    }
+
+    print_extraction_summary(
+        "Extracted",
+        &get_summary(&data, &zip, args.swhid),
+        &output_file,
+        start.elapsed(),
+        args.json,
+    );
+}
+
+// Writing the zip payload as it is only needs the CRX header in memory, anything looking
+// at or changing the payload before it's written goes through the buffered path
+fn can_stream(filename: &str, args: &ExtractArgs) -> bool {
+    !is_url(filename)
+        && !args.force_zip
+        && !args.repair
+        && !args.strip_zip_metadata
+        && args.password.is_none()
+        && args.expect_sha256.is_none()
+        && args.into.is_none()
+        && !args.staging
+        && !args.write_manifest
+        && !args.swhid
+        && args.format.as_deref().unwrap_or(DEFAULT_OUTPUT_FORMAT) == DEFAULT_OUTPUT_FORMAT
+        && !has_sidecar_hash(filename)
+        && get_path_wrapper(Path::new(filename)).is_none()
+}
+
+// Only called for the zip format, which writes the payload as it is
+fn run_streamed(filename: &str, args: ExtractArgs, format: &dyn OutputFormat, start: Instant) {
+    let input = open_crx_file(filename);
+    let input_size = input.metadata().expect("Failed to read metadata").len();
+
+    let output_file = format.output_path(&get_output_dir(args.output_dir));
+    let partial = track_partial_output(&output_file);
+
+    let output = File::create(&output_file).expect("Failed to create file");
+    let header = copy_zip_payload_with_options(
+        BufReader::new(input),
+        BufWriter::new(output),
+        &get_parse_options(),
+    );
+
+    // The zip is only checked once written, like the buffered path the output is not kept
+    // when it is rejected
+    let summary = header
+        .and_then(|header| get_streamed_summary(&header, &output_file, input_size))
+        .unwrap_or_else(|error| {
+            let _ = fs::remove_file(&output_file);
+
+            match error.downcast::<UncrxError>() {
+                Ok(UncrxError::EncryptedEntries(entries)) => {
+                    exit_with_error(UncrxCliError::EncryptedEntries(entries))
+                }
+                Ok(error) => {
+                    exit_with_error(UncrxCliError::InvalidCrx(describe_crx_error(&error.into())))
+                }
+                Err(error) => exit_with_error(UncrxCliError::InvalidCrx(format!(
+                    "{}. The zip payload may be truncated, try --repair",
+                    error
+                ))),
+            }
+        });
+
+    drop(partial);
+    set_permissions(&output_file, args.chmod, args.chown);
+
+    print_extraction_summary(
+        "Extracted",
+        &summary,
+        &output_file,
+        start.elapsed(),
+        args.json,
+    );
+}
+
+// Only the central directory and the manifest are read back from the written zip
+fn get_streamed_summary(
+    header: &[u8],
+    zip_file: &Path,
+    input_size: u64,
+) -> anyhow::Result<ExtractionSummary> {
+    let file = File::open(zip_file)?;
+    let zipped_size = file.metadata()?.len();
+    let mut archive = ZipArchive::new(BufReader::new(file))?;
+
+    let mut encrypted = vec![];
+    let mut size = 0;
+
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index)?;
+
+        if entry.encrypted() {
+            encrypted.push(entry.name().to_string());
+        }
+
+        size += entry.size();
+    }
+
+    if !encrypted.is_empty() {
+        return Err(UncrxError::EncryptedEntries(encrypted).into());
+    }
+
+    let manifest = archive
+        .by_name(MANIFEST_FILE_NAME)
+        .ok()
+        .and_then(|mut entry| {
+            let mut content = String::new();
+            entry.read_to_string(&mut content).ok()?;
+            parse_manifest(&content).ok()
+        });
+
+    Ok(ExtractionSummary {
+        manifest,
+        id: get_extension_id(header).ok(),
+        files: archive.len(),
+        size,
+        zipped_size,
+        input_size,
+        swhids: None,
+    })
+}
+
+// Exits, keeping the staged files for inspection, unless the extension passes the review
+fn review_staged(
+    data: &[u8],
+    zip: &[u8],
+    is_crx: bool,
+    scanners: &[String],
+    unpack_options: &UnpackOptions,
+    parent: &Path,
+) {
+    let staging = create_staging_dir(parent).expect("Failed to create the staging directory");
+    let _partial = track_partial_output(staging.path());
+
+    unpack_zip_with_options(zip, staging.path(), unpack_options)
+        .expect("Failed to unpack into the staging directory");
+
+    let mut issues = vec![];
+
+    if is_crx {
+        match analyze_crx_with_options(data, &get_parse_options()) {
+            Ok(report) => issues.extend(
+                report
+                    .findings
+                    .iter()
+                    .filter(|finding| finding.severity >= Severity::High)
+                    .map(|finding| {
+                        format!(
+                            "[{}] {}: {}",
+                            finding.severity, finding.category, finding.message
+                        )
+                    }),
+            ),
+            Err(error) => issues.push(format!("Failed to analyze crx: {}", error)),
+        }
+    }
+
+    for failure in run_scanners(staging.path(), scanners).expect("Failed to run scanners") {
+        let status = failure
+            .status
+            .map_or("a signal".to_string(), |status| status.to_string());
+
+        issues.push(format!("{} exited with {}", failure.scanner, status));
+
+        if !failure.output.is_empty() {
+            issues.push(failure.output);
+        }
+    }
+
+    for issue in &issues {
+        eprintln!("{}", issue);
+    }
+
+    if issues.is_empty() || confirm("Extract it anyway?") {
+        return;
+    }
+
+    eprintln!(
+        "Rejected, the staged files are in {}",
+        staging.keep().display()
+    );
+    process::exit(1);
+}
+
+// Only asks on an interactive terminal, anything but yes is a no
+fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush().expect("Failed to flush stderr");
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .expect("Failed to read stdin");
+
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+fn get_unpack_options(args: &ExtractArgs) -> UnpackOptions {
+    let defaults = UnpackOptions::default();
+
+    UnpackOptions {
+        chunk_size: args
+            .chunk_size
+            .map_or(defaults.chunk_size, |size| size as usize),
+        drop_page_cache: args.drop_page_cache,
+    }
+}
+
+fn get_download_options(args: &ExtractArgs) -> DownloadOptions {
+    let defaults = DownloadOptions::default();
+
+    DownloadOptions {
+        max_size: args.max_download_size.unwrap_or(defaults.max_size),
+        rate_limit: args.rate_limit,
+        offline: is_offline(),
+        cache: args.http_cache.as_ref().map(|path| {
+            HttpCache::new(
+                env::current_dir()
+                    .expect("Failed to get current directory")
+                    .join(path),
+            )
+        }),
+    }
+}
+
+struct ExtractionSummary {
+    manifest: Option<Value>,
+    id: Option<String>,
+    files: usize,
+    size: u64,
+    zipped_size: u64,
+    input_size: u64,
+    swhids: Option<TreeSwhids>,
+}
+
+impl ExtractionSummary {
+    fn new(data: &[u8], zip: &[u8]) -> Self {
+        let crx = OpenedCrx::from_zip(data, zip).ok();
+        let entries = crx
+            .as_ref()
+            .and_then(|crx| crx.entries().ok())
+            .unwrap_or_default();
+
+        ExtractionSummary {
+            manifest: crx.as_ref().and_then(|crx| crx.manifest().ok()),
+            id: get_extension_id(data).ok(),
+            files: entries.len(),
+            size: entries.iter().map(|entry| entry.size).sum(),
+            zipped_size: zip.len() as u64,
+            input_size: data.len() as u64,
+            swhids: None,
+        }
+    }
+}
+
+fn get_summary(data: &[u8], zip: &[u8], swhid: bool) -> ExtractionSummary {
+    ExtractionSummary {
+        swhids: swhid.then(|| get_tree_swhids(zip).expect("Failed to compute SWHIDs")),
+        ..ExtractionSummary::new(data, zip)
+    }
+}
+
+fn print_extraction_summary(
+    status: &str,
+    summary: &ExtractionSummary,
+    output: &Path,
+    duration: Duration,
+    json: bool,
+) {
+    let field = |key: &str| {
+        summary
+            .manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get(key))
+            .and_then(|value| value.as_str())
+            .unwrap_or("-")
+            .to_string()
+    };
+
+    // Throughput covers the whole run, from reading the input to writing the output
+    if json {
+        let seconds = duration.as_secs_f64();
+
+        let mut json = json!({
+            "status": status.to_lowercase(),
+            "name": summary.manifest.as_ref().and_then(|manifest| manifest.get("name")),
+            "version": summary.manifest.as_ref().and_then(|manifest| manifest.get("version")),
+            "id": summary.id,
+            "files": summary.files,
+            "size": summary.size,
+            "zipped_size": summary.zipped_size,
+            "input_size": summary.input_size,
+            "output": output,
+            "duration_ms": seconds * 1000.0,
+            "bytes_per_second": (seconds > 0.0).then(|| (summary.input_size as f64 / seconds) as u64),
+        });
+
+        if let Some(swhids) = &summary.swhids {
+            json["swhid"] = json!(swhids);
+        }
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).expect("Failed to serialize summary")
+        );
+        return;
+    }
+
+    let mut rows = vec![
+        (
+            status,
+            format!("{} {}", paint(SUCCESS, &field("name")), field("version")),
+        ),
+        ("ID", summary.id.clone().unwrap_or_else(|| "-".to_string())),
+        ("Files", summary.files.to_string()),
+        (
+            "Size",
+            format!(
+                "{} {}",
+                format_size(summary.size),
+                paint(
+                    DIMMED,
+                    &format!("({} zipped)", format_size(summary.zipped_size))
+                )
+            ),
+        ),
+        ("Output", output.display().to_string()),
+        (
+            "Duration",
+            format!(
+                "{} ms {}",
+                duration.as_millis(),
+                paint(
+                    DIMMED,
+                    &format!("({})", format_throughput(summary.input_size, duration))
+                )
+            ),
+        ),
+    ];
+
+    if let Some(swhids) = &summary.swhids {
+        rows.push(("SWHID", swhids.directory.clone()));
+    }
+
+    print_summary(&rows);
+}
+
+pub fn run_archive(filename: &str, args: ExtractArgs) {
+    let data = read_zip_file(filename);
+
+    if let Some(expected) = &args.expect_sha256 {
+        verify_sha256(&data, expected);
+    }
+
+    let crx_files = find_nested_crx_files(&data).expect("Failed to read archive");
+
+    if crx_files.is_empty() {
+        println!("No CRX files found in {}", filename);
+        return;
+    }
+
+    let output_dir = get_output_dir(args.output_dir);
+
+    for crx in crx_files {
+        let payload = get_payload(&crx.data);
+
+        let payload = match args.repair {
+            true => repair_payload(&payload),
+            false => payload,
+        };
+
+        let zip = get_zip(payload, args.strip_zip_metadata, args.password.as_deref());
+
+        let output_file = output_dir.join(Path::new(&crx.path).with_extension("zip"));
+
+        if let Some(parent) = output_file.parent() {
+            fs::create_dir_all(parent).expect("Failed to create directory");
+        }
+
+        let partial = track_partial_output(&output_file);
+        fs::write(&output_file, zip).expect("Failed to write file");
+        drop(partial);
+
+        set_permissions(&output_file, args.chmod, args.chown);
+
+        println!("{} -> {}", crx.path, output_file.display());
+    }
+}
+
+fn get_payload(data: &[u8]) -> Vec<u8> {
+    match parse_crx_with_options(data, &get_parse_options()) {
+        Ok(extension) => extension.zip,
+        Err(error) => exit_with_error(UncrxCliError::InvalidCrx(describe_crx_error(&error))),
+    }
+}
+
+fn repair_payload(payload: &[u8]) -> Vec<u8> {
+    let recovery = recover_zip(payload).expect("Failed to recover zip entries");
+
+    eprintln!("Recovered {} entries", recovery.recovered.len());
+
+    for name in &recovery.lost {
+        eprintln!("Lost {}", name);
+    }
+
+    recovery.zip
+}
+
+fn get_zip(payload: Vec<u8>, strip_zip_metadata_fields: bool, password: Option<&str>) -> Vec<u8> {
+    let encrypted = get_encrypted_entries(&payload).unwrap_or_else(|error| {
+        exit_with_error(UncrxCliError::InvalidCrx(format!(
+            "{}. The zip payload may be truncated, try --repair",
+            error
+        )))
+    });
+
+    let zip = match (encrypted.is_empty(), password) {
+        (true, _) => payload,
+        (false, None) => exit_with_error(UncrxCliError::EncryptedEntries(encrypted)),
+        (false, Some(password)) => match decrypt_zip(&payload, password.as_bytes()) {
+            Ok(zip) => zip,
+            Err(error) => match error.downcast_ref::<UncrxError>() {
+                Some(UncrxError::InvalidPassword) => {
+                    exit_with_error(UncrxCliError::InvalidPassword)
+                }
+                _ => panic!("Failed to decrypt zip: {}", error),
+            },
+        },
+    };
+
+    match strip_zip_metadata_fields {
+        true => strip_zip_metadata(&zip).expect("Failed to strip zip metadata"),
+        false => zip,
+    }
+}
+
+fn write_manifest(
+    directory: &Path,
+    data: &[u8],
+    zip: &[u8],
+    files: BTreeMap<String, String>,
+    hash_algorithm: HashAlgorithm,
+) {
+    let manifest = ExtractionManifest::new(data, zip, files, hash_algorithm);
+
+    write_extraction_manifest(directory, &manifest).expect("Failed to write extraction manifest");
+}
+
+// An unpacked directory holds its manifest like a corpus entry does, a file gets it next to
+// itself
+fn write_output_manifest(
+    output_dir: &Path,
+    output: &Path,
+    data: &[u8],
+    zip: &[u8],
+    hash_algorithm: HashAlgorithm,
+) {
+    let files = match output.is_dir() {
+        true => get_entry_hashes(zip, hash_algorithm).expect("Failed to hash files"),
+        false => BTreeMap::from([(
+            output
+                .file_name()
+                .expect("Outputs have a file name")
+                .to_string_lossy()
+                .into_owned(),
+            hash_algorithm.hash(&fs::read(output).expect("Failed to read output")),
+        )]),
+    };
+
+    write_manifest(
+        get_manifest_dir(output_dir, output),
+        data,
+        zip,
+        files,
+        hash_algorithm,
+    );
+}
+
+fn get_manifest_dir<'a>(output_dir: &'a Path, output: &'a Path) -> &'a Path {
+    match output.is_dir() {
+        true => output,
+        false => output_dir,
+    }
+}
+
+fn set_permissions(path: &Path, modes: Option<FileModes>, ownership: Option<Ownership>) {
+    apply_permissions(path, modes.as_ref(), ownership.as_ref())
+        .unwrap_or_else(|error| panic!("Failed to set permissions: {}", error));
+}
+
+fn get_output_dir(output_dir: Option<String>) -> PathBuf {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let output_dir = match output_dir {
+        Some(path) => current_dir.join(path),
+        None => current_dir.join("out"),
+    };
+
+    if !output_dir.exists() {
+        fs::create_dir_all(&output_dir).expect("Failed to create directory");
+    }
+
+    output_dir
+}
@@ -0,0 +1,59 @@
+use std::{env, process};
+
+use clap::Args;
+use uncrx::corpus::{find_installed_extensions, import_into_corpus, CorpusMerge};
+
+use crate::cli::{errors::UncrxCliError, helpers::exit_with_error};
+
+#[derive(Args)]
+pub struct ImportProfileArgs {
+    /// Extensions directory of a Chrome profile, e.g. ~/.config/google-chrome/Default/Extensions
+    directory: String,
+    /// Corpus directory the extensions are copied into as <ID>/<VERSION>
+    #[arg(short, long, default_value = "corpus")]
+    output_dir: String,
+}
+
+pub fn run(args: ImportProfileArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let directory = current_dir.join(&args.directory);
+    let root = current_dir.join(&args.output_dir);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let extensions = find_installed_extensions(&directory).expect("Failed to read profile");
+
+    let mut imported = 0;
+    let mut failed = 0;
+
+    for extension in &extensions {
+        match import_into_corpus(&root, extension) {
+            Ok(CorpusMerge::Added(path)) => {
+                imported += 1;
+                println!("{} -> {}", extension.path.display(), path.display());
+            }
+            Ok(CorpusMerge::Present(path)) => {
+                println!(
+                    "{} -> {} (present)",
+                    extension.path.display(),
+                    path.display()
+                );
+            }
+            Err(error) => {
+                failed += 1;
+                eprintln!("{}: {}", extension.path.display(), error);
+            }
+        }
+    }
+
+    println!("{} imported, {} failed", imported, failed);
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
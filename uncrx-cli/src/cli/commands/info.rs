@@ -0,0 +1,42 @@
+use serde_json::Value;
+use uncrx::opened::OpenedCrx;
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str) {
+    let data = read_crx_file(filename);
+    let crx =
+        OpenedCrx::open_with_options(&data, &get_parse_options()).expect("Failed to open crx");
+
+    let version = crx.crx_version().expect("Failed to read crx version");
+    let id = crx.extension_id().expect("Failed to compute extension id");
+
+    let manifest = crx.manifest().expect("Failed to read manifest");
+
+    let field = |key: &str| match manifest.get(key) {
+        Some(Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    };
+
+    println!("Name:             {}", field("name"));
+    println!("ID:               {}", id);
+    println!("Version:          {}", field("version"));
+    println!("Description:      {}", field("description"));
+    println!("Manifest version: {}", field("manifest_version"));
+    println!("CRX version:      {}", version);
+
+    if let Some(build_info) = crx.build_info().expect("Failed to read build info") {
+        let unknown = || "-".to_string();
+
+        println!(
+            "Build commit:     {}",
+            build_info.commit.unwrap_or_else(unknown)
+        );
+        println!("Build time:       {}", build_info.build_time);
+        println!(
+            "Builder:          {}",
+            build_info.builder.unwrap_or_else(unknown)
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use std::process;
+
+use clap::Args;
+use uncrx::webdriver::{test_install, InstallResult, DEFAULT_WEBDRIVER_URL};
+
+use crate::cli::helpers::read_crx_file;
+
+#[derive(Args)]
+pub struct InstallTestArgs {
+    filename: String,
+    /// URL of a running chromedriver
+    #[arg(long, default_value = DEFAULT_WEBDRIVER_URL)]
+    webdriver: String,
+}
+
+pub fn run(args: InstallTestArgs) {
+    let data = read_crx_file(&args.filename);
+
+    match test_install(&args.webdriver, &data).expect("Failed to reach chromedriver") {
+        InstallResult::Accepted => println!("Chrome accepted {}", args.filename),
+        InstallResult::Rejected(message) => {
+            eprintln!("Chrome rejected {}: {}", args.filename, message);
+            process::exit(1);
+        }
+    }
+}
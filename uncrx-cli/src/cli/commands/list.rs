@@ -0,0 +1,43 @@
+use std::{
+    io::{BufReader, Cursor},
+    path::Path,
+};
+
+use uncrx::{stream::read_zip_listing_with_options, wrapper::get_path_wrapper};
+
+use crate::cli::helpers::{get_parse_options, has_sidecar_hash, open_crx_file, read_crx_file};
+
+pub fn run(filename: &str) {
+    // The sidecar hash covers the whole file, which has to be read then. Compressed files
+    // can't be seeked into either
+    let in_memory = has_sidecar_hash(filename) || get_path_wrapper(Path::new(filename)).is_some();
+
+    let options = get_parse_options();
+
+    let listing = match in_memory {
+        true => read_zip_listing_with_options(Cursor::new(read_crx_file(filename)), &options),
+        false => read_zip_listing_with_options(BufReader::new(open_crx_file(filename)), &options),
+    }
+    .expect("Failed to read zip entries");
+
+    for entry in &listing.entries {
+        println!("{:>10}  {}  {}", entry.size, entry.modified, entry.name);
+
+        if !entry.comment.is_empty() {
+            println!("{:>10}  comment: {}", "", entry.comment);
+        }
+
+        if !entry.extra_field.is_empty() || !entry.local_extra_field.is_empty() {
+            println!(
+                "{:>10}  extra fields: {} bytes (central), {} bytes (local)",
+                "",
+                entry.extra_field.len(),
+                entry.local_extra_field.len()
+            );
+        }
+    }
+
+    if !listing.comment.is_empty() {
+        println!("Archive comment: {}", listing.comment);
+    }
+}
@@ -0,0 +1,34 @@
+use uncrx::{
+    helpers::get_zip_payload_with_options,
+    managed_schema::{get_managed_schema, get_policy_options},
+};
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str, raw: bool) {
+    let data = read_crx_file(filename);
+
+    let zip = get_zip_payload_with_options(&data, &get_parse_options())
+        .expect("Failed to read zip payload");
+
+    let Some(schema) = get_managed_schema(zip).expect("Failed to read managed schema") else {
+        println!("The extension doesn't declare storage.managed_schema");
+        return;
+    };
+
+    if raw {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema).expect("Failed to serialize schema")
+        );
+        return;
+    }
+
+    for option in get_policy_options(&schema) {
+        println!("{} ({})", option.name, option.kind);
+
+        for text in [option.title, option.description].into_iter().flatten() {
+            println!("    {}", text);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::process;
+
+use uncrx::{
+    helpers::get_zip_payload_with_options, manifest::get_manifest, validation::validate_manifest,
+};
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str, validate: bool) {
+    let data = read_crx_file(filename);
+
+    let manifest = get_zip_payload_with_options(&data, &get_parse_options())
+        .and_then(get_manifest)
+        .expect("Failed to read manifest");
+
+    if !validate {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&manifest).expect("Failed to serialize manifest")
+        );
+        return;
+    }
+
+    let errors = validate_manifest(&manifest);
+
+    if errors.is_empty() {
+        println!("manifest.json is valid");
+        return;
+    }
+
+    for error in &errors {
+        eprintln!("manifest.json{}", error);
+    }
+
+    process::exit(1);
+}
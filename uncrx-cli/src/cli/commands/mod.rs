@@ -0,0 +1,22 @@
+pub mod analyze;
+pub mod batch;
+pub mod carve;
+pub mod check;
+pub mod compat;
+#[cfg(unix)]
+pub mod daemon;
+pub mod download;
+pub mod export;
+pub mod extract;
+pub mod import_profile;
+pub mod info;
+pub mod install_test;
+pub mod list;
+pub mod managed_schema;
+pub mod manifest;
+pub mod pack;
+pub mod policy;
+pub mod serve;
+pub mod stats;
+pub mod update_manifest;
+pub mod verify;
@@ -0,0 +1,175 @@
+use std::{env, fs, path::PathBuf, process};
+
+use clap::Args;
+use uncrx::{
+    build_info::collect_build_info,
+    manifest::{VersionBump, MANIFEST_FILE_NAME},
+    pack::{
+        build_crx3, encode_private_key, generate_private_key, get_bumped_manifest,
+        read_private_key, verify_reproducible, zip_directory, PackOptions,
+    },
+};
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, get_parse_options, read_crx_file},
+    interrupt::track_partial_output,
+};
+
+#[derive(Args)]
+pub struct PackArgs {
+    directory: String,
+    /// Path of the CRX file to write, defaults to <DIRECTORY>.crx
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Private key used to sign the CRX file, a new one is generated if omitted
+    #[arg(short, long)]
+    key: Option<String>,
+    /// Bump the manifest version (major, minor or patch) before packing
+    #[arg(long)]
+    bump: Option<VersionBump>,
+    /// Only pack files matching this glob
+    #[arg(long)]
+    include: Vec<String>,
+    /// Never pack files matching this glob, in addition to .crxignore/.gitignore rules
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Check the zip payload is byte-identical across two builds, or to the given CRX file
+    #[arg(long, value_name = "CRX", require_equals = true)]
+    verify_reproducible: Option<Option<String>>,
+    /// Embed the git commit, build time and builder as uncrx-build-info.json
+    #[arg(long)]
+    build_info: bool,
+    /// Builder recorded in the build info, defaults to the current user
+    #[arg(long, requires = "build_info")]
+    builder: Option<String>,
+    /// Pack an extension installed in a Chrome profile, without the _metadata directory and
+    /// manifest key Chrome added
+    #[arg(long)]
+    installed: bool,
+    /// Load the packed extension in a headless Chromium and fail if it is rejected
+    #[cfg(feature = "smoke-test")]
+    #[arg(long)]
+    smoke_test: bool,
+    /// Browser used by --smoke-test, defaults to the first Chromium or Chrome found in PATH
+    #[cfg(feature = "smoke-test")]
+    #[arg(long, requires = "smoke_test")]
+    browser: Option<String>,
+}
+
+pub fn run(args: PackArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let directory = current_dir.join(&args.directory);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let sibling = |extension: &str| -> PathBuf { directory.with_extension(extension) };
+
+    // A generated key and a bumped manifest are only written once the directory is packed
+    // and checked, a failed pack leaves the directory as it was
+    let (key, new_key) = match args.key {
+        Some(path) => {
+            let pem = fs::read_to_string(current_dir.join(path)).expect("Failed to read key");
+            (read_private_key(&pem).expect("Failed to parse key"), None)
+        }
+        None => {
+            let key_file = sibling("pem");
+
+            if key_file.exists() {
+                exit_with_error(UncrxCliError::KeyExists(
+                    key_file.to_str().unwrap().to_string(),
+                ));
+            }
+
+            let key = generate_private_key().expect("Failed to generate key");
+            let pem = encode_private_key(&key).expect("Failed to encode key");
+
+            (key, Some((key_file, pem)))
+        }
+    };
+
+    let bumped = args
+        .bump
+        .map(|bump| get_bumped_manifest(&directory, bump).expect("Failed to bump version"));
+
+    let options = PackOptions {
+        include: args.include,
+        exclude: args.exclude,
+        build_info: args
+            .build_info
+            .then(|| collect_build_info(&directory, args.builder)),
+        installed: args.installed,
+        manifest: bumped.as_ref().map(|(_, manifest)| manifest.clone()),
+        parse: get_parse_options(),
+    };
+
+    let zip = match args.verify_reproducible {
+        Some(reference) => {
+            let reference = reference.map(|filename| read_crx_file(&filename));
+
+            match verify_reproducible(&directory, &options, reference.as_deref()) {
+                Ok(zip) => {
+                    println!("Zip payload is reproducible");
+                    zip
+                }
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(1);
+                }
+            }
+        }
+        None => zip_directory(&directory, &options).expect("Failed to pack directory"),
+    };
+
+    #[cfg(feature = "smoke-test")]
+    if args.smoke_test {
+        run_smoke_test(&zip, args.browser.map(|path| current_dir.join(path)));
+    }
+
+    let crx = build_crx3(&zip, &key).expect("Failed to sign crx");
+
+    if let Some((key_file, pem)) = new_key {
+        fs::write(&key_file, pem).expect("Failed to write key");
+        println!("Generated a new private key in {}", key_file.display());
+    }
+
+    if let Some((version, manifest)) = bumped {
+        fs::write(directory.join(MANIFEST_FILE_NAME), manifest).expect("Failed to write manifest");
+        println!("Bumped manifest version to {}", version);
+    }
+
+    let output_file = match args.output {
+        Some(path) => current_dir.join(path),
+        None => sibling("crx"),
+    };
+
+    let partial = track_partial_output(&output_file);
+    fs::write(&output_file, crx).expect("Failed to write file");
+    drop(partial);
+
+    println!("Packed {}", output_file.display());
+}
+
+#[cfg(feature = "smoke-test")]
+fn run_smoke_test(zip: &[u8], browser: Option<PathBuf>) {
+    use uncrx::smoke_test::{
+        find_browser, smoke_test, SmokeTestResult, DEFAULT_SMOKE_TEST_TIMEOUT,
+    };
+
+    let Some(browser) = browser.or_else(find_browser) else {
+        exit_with_error(UncrxCliError::NotFound("Chromium".to_string()));
+    };
+
+    match smoke_test(zip, &browser, DEFAULT_SMOKE_TEST_TIMEOUT).expect("Failed to run smoke test") {
+        SmokeTestResult::Loaded => println!("{} loaded the extension", browser.display()),
+        SmokeTestResult::Rejected(error) => {
+            eprintln!("{} rejected the extension: {}", browser.display(), error);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use clap::Args;
+use uncrx::{
+    helpers::is_crx_path,
+    id::{get_extension_id, is_valid_extension_id},
+    policy::{
+        generate_force_install_policy, ForceInstallEntry, PolicyFormat, WEB_STORE_UPDATE_URL,
+    },
+};
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, read_crx_file},
+};
+
+#[derive(Args)]
+pub struct PolicyArgs {
+    /// CRX files or extension IDs to force-install
+    #[arg(required = true)]
+    extensions: Vec<String>,
+    /// Update URL Chrome installs the extensions from
+    #[arg(long, default_value = WEB_STORE_UPDATE_URL)]
+    update_url: String,
+    /// Output format: json (Linux/ChromeOS), registry (Windows) or plist (macOS)
+    #[arg(long, default_value = "json")]
+    format: PolicyFormat,
+}
+
+pub fn run(args: PolicyArgs) {
+    let entries: Vec<ForceInstallEntry> = args
+        .extensions
+        .iter()
+        .map(|extension| {
+            let id = if is_crx_path(Path::new(extension)) {
+                get_extension_id(&read_crx_file(extension)).expect("Failed to compute extension id")
+            } else {
+                if !is_valid_extension_id(extension) {
+                    exit_with_error(UncrxCliError::InvalidExtensionId(extension.clone()));
+                }
+
+                extension.clone()
+            };
+
+            ForceInstallEntry {
+                id,
+                update_url: args.update_url.clone(),
+            }
+        })
+        .collect();
+
+    print!("{}", generate_force_install_policy(&entries, args.format));
+}
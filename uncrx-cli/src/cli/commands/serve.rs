@@ -0,0 +1,186 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::Args;
+use uncrx::{
+    helpers::is_crx_path,
+    update::{
+        answer_update_checks, parse_update_request, percent_decode,
+        scan_update_directory_with_options,
+    },
+};
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, get_parse_options},
+    pool::{get_worker_count, spawn_workers},
+};
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Directory containing the CRX files to serve
+    directory: String,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+    /// Public URL of the server used in codebase attributes, defaults to http://<ADDRESS>
+    #[arg(long)]
+    base_url: Option<String>,
+    /// Number of requests answered concurrently, defaults to the number of CPUs
+    #[arg(long)]
+    workers: Option<usize>,
+}
+
+pub fn run(args: ServeArgs) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let directory = current_dir.join(&args.directory);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let listener = TcpListener::bind(&args.address).expect("Failed to bind address");
+
+    let base_url = args
+        .base_url
+        .unwrap_or_else(|| format!("http://{}", args.address));
+
+    println!("Update URL: {}/update.xml", base_url.trim_end_matches('/'));
+
+    // Connections wait in the backlog of the listener while every worker is busy
+    let queue = spawn_workers(
+        get_worker_count(args.workers),
+        0,
+        || (),
+        move |_, stream: TcpStream| {
+            if let Err(error) = handle_connection(stream, &directory, &base_url) {
+                eprintln!("Failed to answer request: {}", error);
+            }
+        },
+    );
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+
+        queue.send(stream).expect("Workers stopped");
+    }
+}
+
+// Idle or slow clients are dropped instead of holding a worker forever
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+enum Body {
+    Bytes(Vec<u8>),
+    // Copied from the file to the socket, never read into memory as a whole
+    File(File, u64),
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    directory: &Path,
+    base_url: &str,
+) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/update.xml") => {
+            let entries: Vec<_> =
+                scan_update_directory_with_options(directory, base_url, &get_parse_options())?
+                    .into_iter()
+                    .map(|hosted| hosted.entry)
+                    .collect();
+
+            let xml = answer_update_checks(&parse_update_request(query), &entries);
+
+            ("200 OK", "application/xml", Body::Bytes(xml.into_bytes()))
+        }
+        ("GET", path) => match get_crx_path(directory, path) {
+            Some(path) => {
+                let file = File::open(path)?;
+                let length = file.metadata()?.len();
+
+                (
+                    "200 OK",
+                    "application/x-chrome-extension",
+                    Body::File(file, length),
+                )
+            }
+            None => (
+                "404 Not Found",
+                "text/plain",
+                Body::Bytes(b"Not found".to_vec()),
+            ),
+        },
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            Body::Bytes(b"Method not allowed".to_vec()),
+        ),
+    };
+
+    let length = match &body {
+        Body::Bytes(bytes) => bytes.len() as u64,
+        Body::File(_, length) => *length,
+    };
+
+    println!("{} {} {}", method, path, status);
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status, content_type, length
+    )?;
+
+    match body {
+        Body::Bytes(bytes) => stream.write_all(&bytes)?,
+        Body::File(file, length) => {
+            io::copy(&mut file.take(length), &mut stream)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn get_crx_path(directory: &Path, path: &str) -> Option<PathBuf> {
+    let file_name = percent_decode(path.trim_start_matches('/'));
+
+    // Only plain file names are served, anything resembling a path is rejected
+    if file_name.contains(['/', '\\'])
+        || file_name.starts_with('.')
+        || !is_crx_path(Path::new(&file_name))
+    {
+        return None;
+    }
+
+    Some(directory.join(file_name)).filter(|path| path.is_file())
+}
@@ -0,0 +1,87 @@
+use std::env;
+
+use clap::Args;
+use uncrx::stats::collect_corpus_stats_with_options;
+
+use crate::cli::{
+    errors::UncrxCliError,
+    helpers::{exit_with_error, get_parse_options},
+    style::{format_size, print_summary},
+};
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Corpus directory holding CRX files or unpacked extensions
+    directory: String,
+    /// Number of permissions listed, most requested first
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Print the statistics as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: StatsArgs) {
+    let directory = env::current_dir()
+        .expect("Failed to get current directory")
+        .join(&args.directory);
+
+    if !directory.is_dir() {
+        exit_with_error(UncrxCliError::NotFound(
+            directory.to_str().unwrap().to_string(),
+        ));
+    }
+
+    let mut stats = collect_corpus_stats_with_options(&directory, &get_parse_options())
+        .expect("Failed to collect statistics");
+    stats.permissions.truncate(args.top);
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("Failed to serialize statistics")
+        );
+        return;
+    }
+
+    let share = |count: usize| match stats.extensions {
+        0 => "0.0%".to_string(),
+        total => format!("{:.1}%", count as f64 * 100.0 / total as f64),
+    };
+
+    let mut rows = vec![("Extensions".to_string(), stats.extensions.to_string())];
+
+    for (version, count) in &stats.manifest_versions {
+        rows.push((
+            format!("Manifest v{}", version),
+            format!("{} ({})", count, share(*count)),
+        ));
+    }
+
+    rows.push(("Average size".to_string(), format_size(stats.average_size)));
+
+    for (algorithm, count) in &stats.signature_algorithms {
+        rows.push((
+            format!("Signed {}", algorithm),
+            format!("{} ({})", count, share(*count)),
+        ));
+    }
+
+    if stats.skipped > 0 {
+        rows.push(("Skipped".to_string(), stats.skipped.to_string()));
+    }
+
+    for permission in &stats.permissions {
+        rows.push((
+            format!("Permission {}", permission.name),
+            format!("{} ({})", permission.count, share(permission.count)),
+        ));
+    }
+
+    print_summary(
+        &rows
+            .iter()
+            .map(|(label, value)| (label.as_str(), value.clone()))
+            .collect::<Vec<_>>(),
+    );
+}
@@ -0,0 +1,22 @@
+use std::{env, fs};
+
+use uncrx::update::{generate_update_manifest, get_update_manifest_entry_with_options};
+
+use crate::cli::helpers::{get_parse_options, read_crx_file};
+
+pub fn run(filename: &str, codebase: &str, output: Option<String>) {
+    let data = read_crx_file(filename);
+
+    let entry = get_update_manifest_entry_with_options(&data, codebase, &get_parse_options())
+        .expect("Failed to read crx");
+
+    let xml = generate_update_manifest(&[entry]);
+
+    match output {
+        Some(path) => {
+            let current_dir = env::current_dir().expect("Failed to get current directory");
+            fs::write(current_dir.join(path), xml).expect("Failed to write file");
+        }
+        None => print!("{}", xml),
+    }
+}
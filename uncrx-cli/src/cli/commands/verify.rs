@@ -0,0 +1,49 @@
+use std::process;
+
+use uncrx::{
+    id::get_extension_id_from_public_key,
+    verify::{
+        is_webstore_publisher_key, verify_crx_with_options, VerificationResult, VERIFY_CHUNK_SIZE,
+    },
+};
+
+use crate::cli::{
+    helpers::{get_parse_options, open_crx_reader},
+    style::print_summary,
+};
+
+pub fn run(filename: &str, allow_unverified: bool) {
+    let reader = open_crx_reader(filename, VERIFY_CHUNK_SIZE);
+    let (result, proofs) =
+        verify_crx_with_options(reader, &get_parse_options()).expect("Failed to verify crx");
+
+    let labels: Vec<String> = proofs
+        .iter()
+        .map(|proof| proof.algorithm.to_string())
+        .collect();
+
+    let mut rows: Vec<(&str, String)> = labels
+        .iter()
+        .zip(&proofs)
+        .map(|(label, proof)| {
+            let signer = match is_webstore_publisher_key(&proof.public_key) {
+                true => "Chrome Web Store".to_string(),
+                false => get_extension_id_from_public_key(&proof.public_key),
+            };
+
+            (label.as_str(), format!("{} ({})", proof.status, signer))
+        })
+        .collect();
+    rows.push(("result", result.to_string()));
+
+    print_summary(&rows);
+
+    if result == VerificationResult::Unverified && allow_unverified {
+        return;
+    }
+
+    if !result.is_valid() {
+        eprintln!("{} is not validly signed", filename);
+        process::exit(1);
+    }
+}
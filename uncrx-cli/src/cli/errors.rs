@@ -0,0 +1,101 @@
+use clap::error::ErrorKind;
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone)]
+pub enum UncrxCliError {
+    UnsupportedFileType,
+    NotFound(String),
+    KeyExists(String),
+    InvalidExtensionId(String),
+    EncryptedEntries(Vec<String>),
+    InvalidPassword,
+    NotAnArchive(String),
+    ChecksumMismatch(String, String),
+    InvalidCrx(String),
+    DownloadFailed(String, String),
+    Offline(String),
+    FeatureDisabled(String),
+    UnknownFormat(String, Vec<String>),
+    InvalidEnvVar(String, String),
+}
+
+impl Error for UncrxCliError {}
+
+impl fmt::Display for UncrxCliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UncrxCliError::UnsupportedFileType => {
+                write!(f, "Unsupported file type. Only CRX files are supported")
+            }
+            UncrxCliError::NotFound(path) => write!(f, "{} not found", path),
+            UncrxCliError::KeyExists(path) => write!(
+                f,
+                "{} already exists. Pass it with --key to sign with it",
+                path
+            ),
+            UncrxCliError::InvalidExtensionId(id) => {
+                write!(f, "{} is neither a CRX file nor a valid extension id", id)
+            }
+            UncrxCliError::EncryptedEntries(paths) => write!(
+                f,
+                "The zip payload has encrypted entries: {}. Pass --password to decrypt them",
+                paths.join(", ")
+            ),
+            UncrxCliError::InvalidPassword => write!(f, "Invalid password"),
+            UncrxCliError::ChecksumMismatch(expected, found) => write!(
+                f,
+                "SHA-256 mismatch, expected {} but the file hashes to {}",
+                expected, found
+            ),
+            UncrxCliError::InvalidCrx(message) => write!(f, "{}", message),
+            UncrxCliError::DownloadFailed(url, message) => {
+                write!(f, "Failed to download {}: {}", url, message)
+            }
+            UncrxCliError::Offline(url) => write!(
+                f,
+                "Refusing to download {} in offline mode. Unset --offline and UNCRX_OFFLINE to allow it",
+                url
+            ),
+            UncrxCliError::FeatureDisabled(feature) => write!(
+                f,
+                "uncrx was built without {} support. Rebuild it with --features {}",
+                feature, feature
+            ),
+            UncrxCliError::NotAnArchive(path) => write!(
+                f,
+                "{} is not a zip archive. Only zip files are supported with --recursive-archives",
+                path
+            ),
+            UncrxCliError::UnknownFormat(format, known) => write!(
+                f,
+                "Unknown output format {}, expected one of {}",
+                format,
+                known.join(", ")
+            ),
+            UncrxCliError::InvalidEnvVar(name, value) => {
+                write!(f, "Invalid value {} for {}", value, name)
+            }
+        }
+    }
+}
+
+impl From<UncrxCliError> for ErrorKind {
+    fn from(error: UncrxCliError) -> Self {
+        match error {
+            UncrxCliError::UnsupportedFileType => ErrorKind::InvalidValue,
+            UncrxCliError::NotFound(_) => ErrorKind::Io,
+            UncrxCliError::KeyExists(_) => ErrorKind::ArgumentConflict,
+            UncrxCliError::InvalidExtensionId(_) => ErrorKind::InvalidValue,
+            UncrxCliError::EncryptedEntries(_) => ErrorKind::MissingRequiredArgument,
+            UncrxCliError::InvalidPassword => ErrorKind::InvalidValue,
+            UncrxCliError::NotAnArchive(_) => ErrorKind::InvalidValue,
+            UncrxCliError::ChecksumMismatch(_, _) => ErrorKind::ValueValidation,
+            UncrxCliError::InvalidCrx(_) => ErrorKind::InvalidValue,
+            UncrxCliError::DownloadFailed(_, _) => ErrorKind::Io,
+            UncrxCliError::Offline(_) => ErrorKind::ArgumentConflict,
+            UncrxCliError::FeatureDisabled(_) => ErrorKind::InvalidValue,
+            UncrxCliError::UnknownFormat(_, _) => ErrorKind::InvalidValue,
+            UncrxCliError::InvalidEnvVar(_, _) => ErrorKind::InvalidValue,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use uncrx::{archive::UnpackOptions, output::OutputFormats};
+
+// The formats --format resolves. Formats from other crates are registered here behind a
+// feature of their own, so they are only built on demand:
+//
+//     #[cfg(feature = "my-format")]
+//     formats.register(Box::new(my_format::MyFormat));
+pub fn get_output_formats(unpack_options: UnpackOptions) -> OutputFormats {
+    OutputFormats::builtin(unpack_options)
+}
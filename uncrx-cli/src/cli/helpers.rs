@@ -0,0 +1,254 @@
+use super::errors::UncrxCliError;
+use crate::Cli;
+use clap::CommandFactory;
+use std::{
+    borrow::Cow,
+    env,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+use uncrx::{
+    download::{download, DownloadOptions},
+    errors::UncrxError,
+    helpers::{get_sha256, is_crx_path, is_sha256_match, ParseOptions},
+    limits::HeaderLimits,
+    staging::set_temp_dir,
+    types::CrxField,
+    wrapper::{get_path_wrapper, get_unwrapping_reader, unwrap_crx},
+};
+
+static OFFLINE: OnceLock<bool> = OnceLock::new();
+static PARSE_OPTIONS: OnceLock<ParseOptions> = OnceLock::new();
+
+// Options set through UNCRX_* variables only apply when the matching flag is not passed.
+// Empty variables count as unset, so a container can clear one without removing it
+pub fn get_env(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+pub fn get_env_parsed<T: FromStr>(name: &str) -> Option<T> {
+    let value = get_env(name)?;
+
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => exit_with_error(UncrxCliError::InvalidEnvVar(name.to_string(), value)),
+    }
+}
+
+// UNCRX_OFFLINE lets air-gapped machines enforce it without passing --offline every time
+pub fn init_offline(offline: bool) {
+    let offline = offline || get_env("UNCRX_OFFLINE").is_some();
+
+    let _ = OFFLINE.set(offline);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.get().copied().unwrap_or(false)
+}
+
+// Sizes above 4G can't be declared by a CRX header anyway
+pub fn init_parse_options(
+    max_key_length: Option<u64>,
+    max_signature_length: Option<u64>,
+    max_header_size: Option<u64>,
+) {
+    let defaults = HeaderLimits::default();
+    let limit = |value: Option<u64>, default: u32| {
+        value.map_or(default, |value| u32::try_from(value).unwrap_or(u32::MAX))
+    };
+
+    let _ = PARSE_OPTIONS.set(ParseOptions {
+        header_limits: HeaderLimits {
+            max_public_key_length: limit(max_key_length, defaults.max_public_key_length),
+            max_signature_length: limit(max_signature_length, defaults.max_signature_length),
+            max_header_size: limit(max_header_size, defaults.max_header_size),
+        },
+    });
+}
+
+// Passed to every parser the commands call, with the header limits given on the command line
+pub fn get_parse_options() -> ParseOptions {
+    PARSE_OPTIONS.get().copied().unwrap_or_default()
+}
+
+// Relative to the current directory, like every other path given to the CLI
+pub fn init_temp_dir(tmpdir: Option<String>) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    set_temp_dir(tmpdir.map(|tmpdir| current_dir.join(tmpdir)));
+}
+
+// Appends the option raising the limit a header exceeded
+pub fn describe_crx_error(error: &anyhow::Error) -> String {
+    let option = match error.downcast_ref::<UncrxError>() {
+        Some(UncrxError::HeaderFieldTooLarge { field, .. }) => match field {
+            CrxField::SignatureLength => "--max-signature-length",
+            CrxField::HeaderSize => "--max-header-size",
+            _ => "--max-key-length",
+        },
+        _ => return error.to_string(),
+    };
+
+    format!(
+        "{}. Raise it with {} if the file is legitimate",
+        error, option
+    )
+}
+
+pub fn exit_with_error(error: UncrxCliError) -> ! {
+    let mut cmd = Cli::command();
+    cmd.error(error.clone().into(), error.to_string()).exit();
+}
+
+// CRX files compressed by archive mirrors are decompressed once the sidecar hash, which
+// covers the file as stored, is checked
+pub fn read_crx_file(filename: &str) -> Vec<u8> {
+    if !is_crx_input(Path::new(filename)) {
+        exit_with_error(UncrxCliError::UnsupportedFileType);
+    }
+
+    let data = read_input_file(filename);
+
+    match unwrap_crx(&data) {
+        Ok(Cow::Borrowed(_)) => data,
+        Ok(Cow::Owned(unwrapped)) => unwrapped,
+        Err(error) => exit_with_error(UncrxCliError::InvalidCrx(error.to_string())),
+    }
+}
+
+pub fn is_crx_input(path: &Path) -> bool {
+    is_crx_path(path) || get_path_wrapper(path).is_some()
+}
+
+pub fn read_zip_file(filename: &str) -> Vec<u8> {
+    if !filename.ends_with(".zip") {
+        exit_with_error(UncrxCliError::NotAnArchive(filename.to_string()));
+    }
+
+    read_input_file(filename)
+}
+
+pub fn download_file(url: &str, options: &DownloadOptions) -> Vec<u8> {
+    download(url, options).unwrap_or_else(|error| match error.downcast_ref::<UncrxError>() {
+        Some(UncrxError::Offline(_)) => exit_with_error(UncrxCliError::Offline(url.to_string())),
+        _ => exit_with_error(UncrxCliError::DownloadFailed(
+            url.to_string(),
+            error.to_string(),
+        )),
+    })
+}
+
+pub fn verify_sha256(data: &[u8], expected: &str) {
+    if !is_sha256_match(data, expected) {
+        exit_with_error(UncrxCliError::ChecksumMismatch(
+            expected.trim().to_string(),
+            get_sha256(data),
+        ));
+    }
+}
+
+// A <FILE>.sha256 sidecar, as written by sha256sum, is checked whenever it exists
+pub fn read_input_file(filename: &str) -> Vec<u8> {
+    let data = read_file(filename);
+
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    if let Ok(sidecar) = fs::read_to_string(current_dir.join(format!("{}.sha256", filename))) {
+        verify_sha256(&data, sidecar.split_whitespace().next().unwrap_or_default());
+    }
+
+    data
+}
+
+// Opened rather than read, for callers streaming the CRX file
+pub fn open_crx_file(filename: &str) -> File {
+    if !is_crx_input(Path::new(filename)) {
+        exit_with_error(UncrxCliError::UnsupportedFileType);
+    }
+
+    File::open(get_existing_path(filename)).expect("Failed to open file")
+}
+
+// Decompresses while reading when the name says the CRX file is compressed
+pub fn open_crx_reader(filename: &str, capacity: usize) -> Box<dyn Read> {
+    let file = BufReader::with_capacity(capacity, open_crx_file(filename));
+
+    match get_path_wrapper(Path::new(filename)) {
+        Some(wrapper) => get_unwrapping_reader(file, wrapper)
+            .unwrap_or_else(|error| exit_with_error(UncrxCliError::InvalidCrx(error.to_string()))),
+        None => Box::new(file),
+    }
+}
+
+pub fn has_sidecar_hash(filename: &str) -> bool {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    current_dir.join(format!("{}.sha256", filename)).is_file()
+}
+
+fn read_file(filename: &str) -> Vec<u8> {
+    fs::read(get_existing_path(filename)).expect("Failed to read file")
+}
+
+fn get_existing_path(filename: &str) -> PathBuf {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let crx_file_path = current_dir.join(filename);
+
+    if !crx_file_path.exists() {
+        exit_with_error(UncrxCliError::NotFound(
+            crx_file_path.to_str().unwrap().to_string(),
+        ));
+    }
+
+    crx_file_path
+}
+
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("{} is not a duration", value))?;
+
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        "h" => amount * 3600.0,
+        _ => return Err(format!("Unknown unit {}, expected ms, s, m or h", unit)),
+    };
+
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|error| format!("{} is not a duration: {}", value, error))
+}
+
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("{} is not a size", value))?;
+
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Unknown unit {}, expected K, M or G", unit)),
+    };
+
+    amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("{} is too large", value))
+}
@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// Outputs being written, removed if the process is interrupted before they are complete
+static PARTIAL_OUTPUTS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+pub struct PartialOutput(PathBuf);
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        let mut outputs = PARTIAL_OUTPUTS
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        if let Some(index) = outputs.iter().rposition(|path| path == &self.0) {
+            outputs.remove(index);
+        }
+    }
+}
+
+// Keep the returned guard alive until the file or directory is fully written
+pub fn track_partial_output(path: &Path) -> PartialOutput {
+    PARTIAL_OUTPUTS
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .push(path.to_path_buf());
+
+    PartialOutput(path.to_path_buf())
+}
+
+fn remove_partial_outputs() {
+    let outputs = PARTIAL_OUTPUTS
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    for path in outputs.iter() {
+        let _ = match path.is_dir() {
+            true => fs::remove_dir_all(path),
+            false => fs::remove_file(path),
+        };
+    }
+}
+
+// SIGINT and SIGTERM are blocked before any other thread starts and waited for on a thread
+// of their own, so the cleanup runs outside of a signal handler. Exits with 128 plus the
+// signal number, like shells report it
+#[cfg(unix)]
+pub fn init() {
+    use std::{mem::MaybeUninit, process, ptr, thread};
+
+    let signals = unsafe {
+        let mut signals = MaybeUninit::<libc::sigset_t>::uninit();
+        libc::sigemptyset(signals.as_mut_ptr());
+        libc::sigaddset(signals.as_mut_ptr(), libc::SIGINT);
+        libc::sigaddset(signals.as_mut_ptr(), libc::SIGTERM);
+
+        let signals = signals.assume_init();
+        libc::pthread_sigmask(libc::SIG_BLOCK, &signals, ptr::null_mut());
+
+        signals
+    };
+
+    thread::spawn(move || {
+        let mut signal = 0;
+
+        if unsafe { libc::sigwait(&signals, &mut signal) } == 0 {
+            remove_partial_outputs();
+            process::exit(128 + signal);
+        }
+    });
+}
+
+// The default console handler already ends the process with STATUS_CONTROL_C_EXIT
+#[cfg(not(unix))]
+pub fn init() {}
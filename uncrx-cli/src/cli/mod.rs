@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod errors;
+pub mod formats;
+pub mod helpers;
+pub mod interrupt;
+pub mod pool;
+pub mod style;
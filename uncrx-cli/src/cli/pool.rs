@@ -0,0 +1,53 @@
+use std::{
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+// Defaults to one worker per CPU
+pub fn get_worker_count(workers: Option<usize>) -> usize {
+    workers
+        .or_else(|| thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+        .max(1)
+}
+
+// Starts the workers of a long-running command, each with state of its own such as a scratch
+// buffer that outlives the jobs it handles. Sending blocks once every worker is busy and
+// the queue is full, the workers stop when every sender is dropped
+pub fn spawn_workers<T, S>(
+    count: usize,
+    queue_size: usize,
+    init: impl Fn() -> S + Send + Sync + 'static,
+    work: impl Fn(&mut S, T) + Send + Sync + 'static,
+) -> SyncSender<T>
+where
+    T: Send + 'static,
+{
+    let (queue, jobs) = mpsc::sync_channel::<T>(queue_size);
+    let jobs = Arc::new(Mutex::new(jobs));
+    let init = Arc::new(init);
+    let work = Arc::new(work);
+
+    for _ in 0..count {
+        let jobs = jobs.clone();
+        let init = init.clone();
+        let work = work.clone();
+
+        thread::spawn(move || {
+            let mut state = init();
+
+            loop {
+                let Ok(job) = jobs.lock().expect("Job queue is poisoned").recv() else {
+                    return;
+                };
+
+                work(&mut state, job);
+            }
+        });
+    }
+
+    queue
+}
@@ -0,0 +1,66 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+    time::Duration,
+};
+
+use anstyle::{AnsiColor, Color, Style};
+
+static COLOR: OnceLock<bool> = OnceLock::new();
+
+pub const LABEL: Style = Style::new().bold();
+pub const SUCCESS: Style = Style::new()
+    .bold()
+    .fg_color(Some(Color::Ansi(AnsiColor::Green)));
+pub const DIMMED: Style = Style::new().dimmed();
+
+// See https://no-color.org, an empty NO_COLOR doesn't count
+pub fn init(no_color: bool) {
+    let no_color = no_color || env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+    let _ = COLOR.set(!no_color && io::stdout().is_terminal());
+}
+
+pub fn paint(style: Style, text: &str) -> String {
+    match COLOR.get().copied().unwrap_or(false) {
+        true => format!("{}{}{}", style.render(), text, style.render_reset()),
+        false => text.to_string(),
+    }
+}
+
+pub fn print_summary(rows: &[(&str, String)]) {
+    let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+    for (label, value) in rows {
+        println!("{}  {}", paint(LABEL, &format!("{:<width$}", label)), value);
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+pub fn format_throughput(bytes: u64, duration: Duration) -> String {
+    match duration.is_zero() {
+        true => "-".to_string(),
+        false => format!(
+            "{}/s",
+            format_size((bytes as f64 / duration.as_secs_f64()) as u64)
+        ),
+    }
+}
@@ -0,0 +1,171 @@
+pub mod cli;
+use clap::{Parser, Subcommand};
+#[cfg(unix)]
+use cli::commands::daemon::DaemonArgs;
+use cli::commands::{
+    self, batch::BatchArgs, check::CheckArgs, download::DownloadArgs, export::ExportArgs,
+    extract::ExtractCommandArgs, import_profile::ImportProfileArgs, install_test::InstallTestArgs,
+    pack::PackArgs, policy::PolicyArgs, serve::ServeArgs, stats::StatsArgs,
+};
+use cli::helpers::parse_size;
+
+#[derive(Parser)]
+#[command(name = "uncrx-rs")]
+#[command(author = "Manuel Tumiati <tumiatimanuel@gmail.com>")]
+#[command(version)]
+#[command(about = "Easily convert a CRX Extension to a zip file", long_about = None)]
+#[command(next_line_help = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    /// Disable colored output, also disabled when NO_COLOR is set
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Fail instead of touching the network, also enabled when UNCRX_OFFLINE is set
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Refuse CRX2 public keys longer than this, defaults to 64K
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    max_key_length: Option<u64>,
+    /// Refuse CRX2 signatures longer than this, defaults to 64K
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    max_signature_length: Option<u64>,
+    /// Refuse CRX3 headers larger than this, defaults to 1M
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    max_header_size: Option<u64>,
+    /// Directory for staged files and smoke test profiles, TMPDIR is used when not given
+    #[arg(long, global = true, value_name = "DIR")]
+    tmpdir: Option<String>,
+    #[command(flatten)]
+    extract: ExtractCommandArgs,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Analyze a CRX file and report policy violations
+    Analyze { filename: String },
+    /// Extract many CRX files concurrently
+    Batch(BatchArgs),
+    /// Find and extract the CRX files embedded in an arbitrary binary file
+    Carve {
+        filename: String,
+        /// Directory the carved CRX files are written to, defaults to ./carved
+        #[arg(short, long)]
+        output_dir: Option<String>,
+    },
+    /// Verify an extracted directory has not been modified since its extraction
+    Check(CheckArgs),
+    /// Report the lowest Chrome version a CRX file needs
+    Compat { filename: String },
+    /// Answer JSON jobs sent over a Unix socket from a long-running process
+    #[cfg(unix)]
+    Daemon(DaemonArgs),
+    /// Download a CRX file from the Chrome Web Store or a URL without extracting it
+    Download(DownloadArgs),
+    /// Export the metadata of a corpus to CSV or Parquet
+    Export(ExportArgs),
+    /// Extract a CRX file, what runs when no command is given
+    Extract(ExtractCommandArgs),
+    /// Copy the extensions installed in a Chrome profile into a corpus
+    ImportProfile(ImportProfileArgs),
+    /// Print a summary of a CRX file
+    Info { filename: String },
+    /// Check Chrome accepts a CRX file by installing it through chromedriver
+    InstallTest(InstallTestArgs),
+    /// List the entries of a CRX file
+    List { filename: String },
+    /// Print the policy options declared by storage.managed_schema
+    ManagedSchema {
+        filename: String,
+        /// Print the schema as JSON
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Print the manifest of a CRX file
+    Manifest {
+        filename: String,
+        /// Validate the manifest against the Chrome manifest schema
+        #[arg(long)]
+        validate: bool,
+    },
+    /// Pack a directory into a signed CRX file
+    Pack(PackArgs),
+    /// Generate the enterprise policy force-installing extensions
+    Policy(PolicyArgs),
+    /// Serve a directory of CRX files as a self-hosted update service
+    Serve(ServeArgs),
+    /// Aggregate the manifests of a corpus into statistics
+    Stats(StatsArgs),
+    /// Generate the update.xml Chrome expects for self-hosted extensions
+    UpdateManifest {
+        filename: String,
+        /// URL the CRX file is served from
+        #[arg(long)]
+        codebase: String,
+        /// Path of the XML file to write, printed to stdout if omitted
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Check the signatures of a CRX3 file without loading it into memory
+    Verify {
+        filename: String,
+        /// Exit successfully when some ECDSA proofs use a curve that can't be checked
+        #[arg(long)]
+        allow_unverified: bool,
+    },
+}
+
+pub fn main() {
+    let cli = Cli::parse();
+
+    cli::interrupt::init();
+    cli::style::init(cli.no_color);
+    cli::helpers::init_offline(cli.offline);
+    cli::helpers::init_parse_options(
+        cli.max_key_length,
+        cli.max_signature_length,
+        cli.max_header_size,
+    );
+    cli::helpers::init_temp_dir(cli.tmpdir);
+
+    match cli.command {
+        Some(Commands::Analyze { filename }) => commands::analyze::run(&filename),
+        Some(Commands::Batch(args)) => commands::batch::run(args),
+        Some(Commands::Carve {
+            filename,
+            output_dir,
+        }) => commands::carve::run(&filename, output_dir),
+        Some(Commands::Check(args)) => commands::check::run(args),
+        Some(Commands::Compat { filename }) => commands::compat::run(&filename),
+        #[cfg(unix)]
+        Some(Commands::Daemon(args)) => commands::daemon::run(args),
+        Some(Commands::Download(args)) => commands::download::run(args),
+        Some(Commands::Export(args)) => commands::export::run(args),
+        Some(Commands::Extract(args)) => commands::extract::dispatch(args),
+        Some(Commands::ImportProfile(args)) => commands::import_profile::run(args),
+        Some(Commands::Info { filename }) => commands::info::run(&filename),
+        Some(Commands::InstallTest(args)) => commands::install_test::run(args),
+        Some(Commands::List { filename }) => commands::list::run(&filename),
+        Some(Commands::ManagedSchema { filename, raw }) => {
+            commands::managed_schema::run(&filename, raw)
+        }
+        Some(Commands::Manifest { filename, validate }) => {
+            commands::manifest::run(&filename, validate)
+        }
+        Some(Commands::Pack(args)) => commands::pack::run(args),
+        Some(Commands::Policy(args)) => commands::policy::run(args),
+        Some(Commands::Serve(args)) => commands::serve::run(args),
+        Some(Commands::Stats(args)) => commands::stats::run(args),
+        Some(Commands::UpdateManifest {
+            filename,
+            codebase,
+            output,
+        }) => commands::update_manifest::run(&filename, &codebase, output),
+        Some(Commands::Verify {
+            filename,
+            allow_unverified,
+        }) => commands::verify::run(&filename, allow_unverified),
+        None => commands::extract::dispatch(cli.extract),
+    }
+}
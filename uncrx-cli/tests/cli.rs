@@ -0,0 +1,144 @@
+use std::{path::Path, process::Command};
+
+const CRX: &str = "../uncrx/src/mock/test-extension.crx";
+// The mock CRX with an extra ECDSA proof on P-521, a curve the proofs can't be checked on
+const UNVERIFIED_CRX: &str = "tests/fixtures/unverified.crx";
+
+fn run(args: &[&str]) -> String {
+    run_with_env(args, &[])
+}
+
+// Runs the binary from the crate root and records its exit code and output, with the parts
+// that change between machines and runs replaced. UNCRX_* variables only come from vars
+fn run_with_env(args: &[&str], vars: &[(&str, &str)]) -> String {
+    let root = env!("CARGO_MANIFEST_DIR");
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_uncrx"));
+
+    for name in [
+        "UNCRX_OFFLINE",
+        "UNCRX_OUTPUT_DIR",
+        "UNCRX_FORMAT",
+        "UNCRX_JOBS",
+    ] {
+        command.env_remove(name);
+    }
+
+    let output = command
+        .args(args)
+        .current_dir(root)
+        .env("NO_COLOR", "1")
+        .envs(vars.iter().copied())
+        .output()
+        .expect("Failed to run uncrx");
+
+    let normalize = |text: &[u8]| -> String {
+        String::from_utf8_lossy(text)
+            .replace(root, "[ROOT]")
+            .lines()
+            .map(|line| match line.starts_with("Duration ") {
+                true => "Duration   [DURATION]".to_string(),
+                false => line.to_string(),
+            })
+            .map(|line| line + "\n")
+            .collect()
+    };
+
+    format!(
+        "exit code: {:?}\n--- stdout\n{}--- stderr\n{}",
+        output.status.code(),
+        normalize(&output.stdout),
+        normalize(&output.stderr)
+    )
+}
+
+#[test]
+fn info() {
+    insta::assert_snapshot!(run(&["info", CRX]));
+}
+
+#[test]
+fn list() {
+    insta::assert_snapshot!(run(&["list", CRX]));
+}
+
+#[test]
+fn manifest() {
+    insta::assert_snapshot!(run(&["manifest", CRX, "--validate"]));
+}
+
+#[test]
+fn verify() {
+    insta::assert_snapshot!(run(&["verify", CRX]));
+}
+
+#[test]
+fn verify_unverified() {
+    insta::assert_snapshot!(run(&["verify", UNVERIFIED_CRX]));
+}
+
+#[test]
+fn verify_allow_unverified() {
+    insta::assert_snapshot!(run(&["verify", UNVERIFIED_CRX, "--allow-unverified"]));
+}
+
+#[test]
+fn extract() {
+    let output_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("out/cli-extract");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    insta::assert_snapshot!(run(&[CRX, "-o", "out/cli-extract"]));
+    assert!(output_dir.join("extension.zip").is_file());
+}
+
+#[test]
+fn extract_command() {
+    insta::assert_snapshot!(run(&["extract", CRX, "-o", "out/cli-extract-command"]));
+}
+
+#[test]
+fn extract_from_env() {
+    let output_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("out/cli-extract-env");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    insta::assert_snapshot!(run_with_env(
+        &[CRX],
+        &[
+            ("UNCRX_OUTPUT_DIR", "out/cli-extract-env"),
+            ("UNCRX_FORMAT", "directory")
+        ]
+    ));
+    assert!(output_dir.join("extension/manifest.json").is_file());
+}
+
+#[test]
+fn extract_missing_file() {
+    insta::assert_snapshot!(run(&["missing.crx"]));
+}
+
+#[test]
+fn batch_overflowing_timeout() {
+    insta::assert_snapshot!(run(&[
+        "batch",
+        "../uncrx/src/mock",
+        "--timeout",
+        "99999999999999999999999h"
+    ]));
+}
+
+#[test]
+fn check() {
+    let output_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("out/cli-check");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    run(&[CRX, "--into", "out/cli-check", "--write-manifest"]);
+
+    let tree = "out/cli-check/hiebjbihmknnnjiaofclmmecnhigoooc/1.0.0";
+    std::fs::write(
+        output_dir.join("hiebjbihmknnnjiaofclmmecnhigoooc/1.0.0/index.html"),
+        "",
+    )
+    .expect("Failed to write file");
+
+    insta::assert_snapshot!(run(&["check", tree]));
+}
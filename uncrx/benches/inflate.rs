@@ -0,0 +1,122 @@
+// Times inflating a synthetic extension with the decompression backend picked by the
+// features, e.g. cargo bench -p uncrx --bench inflate --features test-support,zlib-ng
+use std::{
+    env, fs,
+    hint::black_box,
+    io::{self, Read},
+    time::{Duration, Instant},
+};
+
+use uncrx::{
+    archive::{open_archive, unpack_zip},
+    test_support::build_zip,
+};
+use zip::CompressionMethod;
+
+const FILES: usize = 256;
+const FILE_SIZE: usize = 256 * 1024;
+const ROUNDS: usize = 5;
+
+fn main() {
+    let backend = match (cfg!(feature = "zlib-ng"), cfg!(feature = "zlib")) {
+        (true, _) => "zlib-ng",
+        (false, true) => "zlib",
+        (false, false) => "zlib-rs",
+    };
+
+    // Repetitive like minified scripts, random enough not to collapse into nothing
+    let mut state = 0x2545f491u32;
+    let entries: Vec<(String, Vec<u8>)> = (0..FILES)
+        .map(|index| {
+            let content = (0..FILE_SIZE)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    b"function(){return this;}var "[(state % 28) as usize]
+                })
+                .collect();
+
+            (format!("js/{}.js", index), content)
+        })
+        .collect();
+
+    let zip = build_zip(&entries, CompressionMethod::Deflated).expect("Failed to build zip");
+    let size = (FILES * FILE_SIZE) as u64;
+
+    println!(
+        "{} backend, {} files, {} MiB inflated from {} MiB",
+        backend,
+        FILES,
+        size / 1024 / 1024,
+        zip.len() / 1024 / 1024
+    );
+
+    report(
+        "inflate",
+        size,
+        measure(|| {
+            let mut archive = open_archive(&zip).expect("Failed to open zip");
+
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index).expect("Failed to read entry");
+                io::copy(&mut file, &mut io::sink()).expect("Failed to inflate entry");
+            }
+        }),
+    );
+
+    let directory = env::temp_dir().join("uncrx-inflate-bench");
+
+    report(
+        "unpack",
+        size,
+        measure(|| {
+            let _ = fs::remove_dir_all(&directory);
+            black_box(unpack_zip(&zip, &directory).expect("Failed to unpack zip"));
+        }),
+    );
+
+    let _ = fs::remove_dir_all(&directory);
+
+    // Reading the stored bytes back checks the CRC without inflating, as a baseline
+    let stored = build_zip(&entries, CompressionMethod::Stored).expect("Failed to build zip");
+
+    report(
+        "crc32",
+        size,
+        measure(|| {
+            let mut archive = open_archive(&stored).expect("Failed to open zip");
+            let mut buffer = Vec::with_capacity(FILE_SIZE);
+
+            for index in 0..archive.len() {
+                buffer.clear();
+                archive
+                    .by_index(index)
+                    .expect("Failed to read entry")
+                    .read_to_end(&mut buffer)
+                    .expect("Failed to read entry");
+            }
+        }),
+    );
+}
+
+// Best of a few rounds, the first one warms the page cache and the allocator up
+fn measure(mut run: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(name: &str, bytes: u64, duration: Duration) {
+    println!(
+        "{:<8} {:>8.1} ms {:>8.1} MiB/s",
+        name,
+        duration.as_secs_f64() * 1000.0,
+        bytes as f64 / 1024.0 / 1024.0 / duration.as_secs_f64()
+    );
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use super::{
+    archive::check_zip_consistency,
+    csp::{evaluate_csp, get_content_security_policy},
+    helpers::{parse_crx_with_options, ParseOptions},
+    licenses::{find_licenses, DetectedLicense},
+    manifest::{get_manifest, get_manifest_version},
+    polyglot::find_polyglot_formats_with_options,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Low => write!(f, "low"),
+            Severity::Medium => write!(f, "medium"),
+            Severity::High => write!(f, "high"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub category: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisReport {
+    pub findings: Vec<Finding>,
+    // Licenses are reported for review rather than as findings, none of them being an issue
+    // in itself
+    pub licenses: Vec<DetectedLicense>,
+}
+
+pub fn analyze_crx(data: &[u8]) -> anyhow::Result<AnalysisReport> {
+    analyze_crx_with_options(data, &ParseOptions::default())
+}
+
+pub fn analyze_crx_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> anyhow::Result<AnalysisReport> {
+    let extension = parse_crx_with_options(data, options)?;
+    let manifest = get_manifest(&extension.zip)?;
+    let mut report = AnalysisReport::default();
+
+    if let Some(policy) = get_content_security_policy(&manifest)? {
+        for violation in evaluate_csp(&policy, get_manifest_version(&manifest)) {
+            report.findings.push(Finding {
+                severity: Severity::High,
+                category: "csp".to_string(),
+                message: violation.to_string(),
+            });
+        }
+    }
+
+    for inconsistency in check_zip_consistency(&extension.zip)? {
+        report.findings.push(Finding {
+            severity: Severity::High,
+            category: "zip".to_string(),
+            message: inconsistency.to_string(),
+        });
+    }
+
+    for polyglot in find_polyglot_formats_with_options(data, options)? {
+        report.findings.push(Finding {
+            severity: Severity::High,
+            category: "polyglot".to_string(),
+            message: polyglot.to_string(),
+        });
+    }
+
+    report.licenses = find_licenses(&extension.zip)?;
+
+    Ok(report)
+}
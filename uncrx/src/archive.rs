@@ -0,0 +1,639 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Cursor, Read, Write},
+    path::Path,
+};
+
+use zip::{result::ZipError, write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+use super::errors::UncrxError;
+use super::zip_records::{
+    find_end_of_central_directory, read_central_directory, read_local_header, rebuild_zip,
+    CentralDirectoryRecord, LocalFileHeader, ZipRecords, DATA_DESCRIPTOR_SIGNATURE,
+    FLAG_DATA_DESCRIPTOR, FLAG_ENCRYPTED, LOCAL_FILE_HEADER_SIGNATURE,
+};
+
+pub fn open_archive(zip: &[u8]) -> anyhow::Result<ZipArchive<Cursor<&[u8]>>> {
+    Ok(ZipArchive::new(Cursor::new(zip))?)
+}
+
+pub fn normalize_entry_name(name: &str) -> &str {
+    name.trim_start_matches("./").trim_start_matches('/')
+}
+
+pub fn read_entry(zip: &[u8], name: &str) -> anyhow::Result<Vec<u8>> {
+    read_entry_from_archive(&mut open_archive(zip)?, name)
+}
+
+pub fn read_entry_from_archive(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let name = normalize_entry_name(name);
+
+    let index = archive
+        .index_for_name(name)
+        .ok_or_else(|| anyhow::anyhow!("{} not found in the archive", name))?;
+
+    if archive.by_index_raw(index)?.encrypted() {
+        return Err(UncrxError::EncryptedEntries(vec![name.to_string()]).into());
+    }
+
+    let mut file = archive.by_index(index)?;
+
+    // Grows with the data actually read, the size an entry declares is never trusted
+    let mut content = vec![];
+    file.read_to_end(&mut content)?;
+
+    Ok(content)
+}
+
+pub fn read_entry_to_string(zip: &[u8], name: &str) -> anyhow::Result<String> {
+    let content = read_entry(zip, name)?;
+
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+pub fn get_encrypted_entries(zip: &[u8]) -> anyhow::Result<Vec<String>> {
+    let mut archive = open_archive(zip)?;
+    let mut encrypted = vec![];
+
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index)?;
+
+        if file.encrypted() {
+            encrypted.push(file.name().to_string());
+        }
+    }
+
+    Ok(encrypted)
+}
+
+pub fn ensure_not_encrypted(zip: &[u8]) -> anyhow::Result<()> {
+    let encrypted = get_encrypted_entries(zip)?;
+
+    if !encrypted.is_empty() {
+        return Err(UncrxError::EncryptedEntries(encrypted).into());
+    }
+
+    Ok(())
+}
+
+pub fn decrypt_zip(zip: &[u8], password: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut archive = open_archive(zip)?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for index in 0..archive.len() {
+        if !archive.by_index_raw(index)?.encrypted() {
+            writer.raw_copy_file(archive.by_index(index)?)?;
+            continue;
+        }
+
+        let mut file = match archive.by_index_decrypt(index, password) {
+            Ok(file) => file,
+            Err(ZipError::InvalidPassword) => return Err(UncrxError::InvalidPassword.into()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut options = SimpleFileOptions::DEFAULT.compression_method(file.compression());
+
+        if let Some(modified) = file.last_modified() {
+            options = options.last_modified_time(modified);
+        }
+
+        if let Some(mode) = file.unix_mode() {
+            options = options.unix_permissions(mode);
+        }
+
+        let mut content = vec![];
+        file.read_to_end(&mut content)
+            .map_err(|_| UncrxError::InvalidPassword)?;
+
+        writer.start_file(file.name(), options)?;
+        writer.write_all(&content)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+// Deflate can't expand data more than this, stored entries not at all
+const MAX_COMPRESSION_RATIO: u64 = 1032;
+
+// Large enough to keep the number of write calls low on network shares and Windows
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnpackOptions {
+    // Buffered before every write to an unpacked file
+    pub chunk_size: usize,
+    // Flushes every unpacked file and evicts it from the page cache, so unpacking a large
+    // corpus doesn't push everything else out of memory. Only has an effect on Linux
+    pub drop_page_cache: bool,
+}
+
+impl Default for UnpackOptions {
+    fn default() -> Self {
+        UnpackOptions {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            drop_page_cache: false,
+        }
+    }
+}
+
+pub fn unpack_zip(zip: &[u8], directory: &Path) -> anyhow::Result<usize> {
+    unpack_zip_with_options(zip, directory, &UnpackOptions::default())
+}
+
+// Entries resolving outside the directory are rejected rather than skipped, a zip
+// carrying them is not one to unpack half of
+pub fn unpack_zip_with_options(
+    zip: &[u8],
+    directory: &Path,
+    options: &UnpackOptions,
+) -> anyhow::Result<usize> {
+    ensure_not_encrypted(zip)?;
+
+    let mut archive = open_archive(zip)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow::anyhow!("{} points outside the archive", file.name()))?;
+        let path = directory.join(path);
+
+        if file.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Sized upfront so the filesystem can allocate the file in one go. The declared size
+        // comes from the archive, so it is only trusted as far as the compressed data could
+        // expand, and the bytes actually written are checked against it below
+        let output = File::create(&path)?;
+
+        if file.size() <= file.compressed_size().saturating_mul(MAX_COMPRESSION_RATIO) {
+            allocate(&output, file.size())?;
+        }
+
+        let mut writer = BufWriter::with_capacity(options.chunk_size.max(1), output);
+        let copied = io::copy(&mut file, &mut writer)
+            .map_err(anyhow::Error::from)
+            .and_then(|written| {
+                Ok((
+                    written,
+                    writer.into_inner().map_err(|error| error.into_error())?,
+                ))
+            });
+
+        // The zip reader only checks the CRC, an entry holding less than it declares, or
+        // failing to decompress halfway, would be left padded with zeros
+        let output = match copied {
+            Ok((written, output)) if written == file.size() => output,
+            Ok((written, output)) => {
+                drop(output);
+                fs::remove_file(&path)?;
+
+                return Err(anyhow::anyhow!(
+                    "{} holds {} bytes but declares {}",
+                    file.name(),
+                    written,
+                    file.size()
+                ));
+            }
+            Err(error) => {
+                fs::remove_file(&path)?;
+
+                return Err(error);
+            }
+        };
+
+        if options.drop_page_cache {
+            drop_page_cache(&output)?;
+        }
+    }
+
+    Ok(archive.len())
+}
+
+// fallocate rather than posix_fallocate, whose glibc fallback writes the whole file once
+// when the filesystem can't reserve blocks. Unlike set_len the blocks are really reserved,
+// callers check the size first
+#[cfg(target_os = "linux")]
+fn allocate(file: &File, size: u64) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    if size == 0 {
+        return Ok(());
+    }
+
+    match unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, size as libc::off_t) } {
+        0 => Ok(()),
+        _ => file.set_len(size),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allocate(file: &File, size: u64) -> io::Result<()> {
+    file.set_len(size)
+}
+
+// Dirty pages are skipped by the kernel, so the data is flushed before advising
+#[cfg(target_os = "linux")]
+fn drop_page_cache(file: &File) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    file.sync_data()?;
+
+    match unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) } {
+        0 => Ok(()),
+        error => Err(io::Error::from_raw_os_error(error)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_page_cache(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+// Zips of zips are followed this deep at most
+const MAX_ARCHIVE_NESTING: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedCrx {
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+pub fn find_nested_crx_files(zip: &[u8]) -> anyhow::Result<Vec<NestedCrx>> {
+    find_nested_crx_files_at_depth(zip, 0)
+}
+
+fn find_nested_crx_files_at_depth(zip: &[u8], depth: usize) -> anyhow::Result<Vec<NestedCrx>> {
+    if depth > MAX_ARCHIVE_NESTING {
+        return Err(anyhow::anyhow!(
+            "Archives are nested more than {} levels deep",
+            MAX_ARCHIVE_NESTING
+        ));
+    }
+
+    let mut archive = open_archive(zip)?;
+    let mut found = vec![];
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+
+        // Entries escaping the archive root could not be written back safely
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+        let path = path.to_string_lossy().replace('\\', "/");
+        let lowercase = path.to_lowercase();
+
+        if !lowercase.ends_with(".crx") && !lowercase.ends_with(".zip") {
+            continue;
+        }
+
+        let mut data = vec![];
+        file.read_to_end(&mut data)?;
+
+        if lowercase.ends_with(".crx") {
+            found.push(NestedCrx { path, data });
+            continue;
+        }
+
+        // Nested archives become directories named after them, without the .zip suffix
+        let directory = &path[..path.len() - ".zip".len()];
+
+        for nested in find_nested_crx_files_at_depth(&data, depth + 1)? {
+            found.push(NestedCrx {
+                path: format!("{}/{}", directory, nested.path),
+                data: nested.data,
+            });
+        }
+    }
+
+    Ok(found)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryMetadata {
+    pub name: String,
+    pub size: u64,
+    pub compressed_size: u64,
+    pub compression: u16,
+    pub crc32: u32,
+    pub modified: String,
+    pub encrypted: bool,
+    pub comment: String,
+    pub extra_field: Vec<u8>,
+    pub local_extra_field: Vec<u8>,
+}
+
+pub fn format_dos_time(date: u16, time: u16) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        1980 + (date >> 9),
+        (date >> 5) & 0x0f,
+        date & 0x1f,
+        time >> 11,
+        (time >> 5) & 0x3f,
+        (time & 0x1f) * 2
+    )
+}
+
+pub fn get_entries(zip: &[u8]) -> anyhow::Result<Vec<EntryMetadata>> {
+    get_entries_from_records(zip, &read_central_directory(zip)?)
+}
+
+pub fn get_entries_from_records(
+    zip: &[u8],
+    records: &ZipRecords,
+) -> anyhow::Result<Vec<EntryMetadata>> {
+    records
+        .entries
+        .iter()
+        .map(|record| {
+            let local = read_local_header(zip, records.local_header_offset(record))?;
+
+            Ok(get_entry_metadata(record, local))
+        })
+        .collect()
+}
+
+pub fn get_entry_metadata(
+    record: &CentralDirectoryRecord,
+    local: LocalFileHeader,
+) -> EntryMetadata {
+    EntryMetadata {
+        name: record.name(),
+        size: record.size as u64,
+        compressed_size: record.compressed_size as u64,
+        compression: record.compression,
+        crc32: record.crc32,
+        modified: format_dos_time(record.modified_date, record.modified_time),
+        encrypted: record.is_encrypted(),
+        comment: String::from_utf8_lossy(&record.comment).into_owned(),
+        extra_field: record.extra.clone(),
+        local_extra_field: local.extra,
+    }
+}
+
+pub fn get_archive_comment(zip: &[u8]) -> anyhow::Result<String> {
+    let end = find_end_of_central_directory(zip)?;
+
+    Ok(String::from_utf8_lossy(&end.comment).into_owned())
+}
+
+pub fn strip_zip_metadata(zip: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let records = read_central_directory(zip)?;
+    let mut entries = Vec::with_capacity(records.entries.len());
+
+    for record in &records.entries {
+        let local = read_local_header(zip, records.local_header_offset(record))?;
+        let data_end = local.data_start() + record.compressed_size as usize;
+
+        let data = zip
+            .get(local.data_start()..data_end)
+            .ok_or_else(|| anyhow::anyhow!("Data of {} is truncated", record.name()))?;
+
+        entries.push((
+            CentralDirectoryRecord {
+                extra: vec![],
+                comment: vec![],
+                ..record.clone()
+            },
+            data,
+        ));
+    }
+
+    rebuild_zip(&entries, &[])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipRecovery {
+    pub zip: Vec<u8>,
+    pub recovered: Vec<String>,
+    pub lost: Vec<String>,
+}
+
+// Walks the local headers from the start of the payload instead of trusting the central
+// directory, which is the first thing missing from a truncated download
+pub fn recover_zip(zip: &[u8]) -> anyhow::Result<ZipRecovery> {
+    let mut entries = vec![];
+    let mut lost = vec![];
+    let mut offset = 0;
+
+    while zip.get(offset..offset + 4) == Some(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes()) {
+        let Ok(local) = read_local_header(zip, offset) else {
+            lost.push(format!("entry at byte {}", offset));
+            break;
+        };
+
+        let Some((record, data_end, next)) = get_local_entry(zip, &local) else {
+            lost.push(String::from_utf8_lossy(&local.name).into_owned());
+            break;
+        };
+
+        entries.push((record, &zip[local.data_start()..data_end]));
+        offset = next;
+    }
+
+    let candidate = rebuild_zip(&entries, &[])?;
+    let mut archive = open_archive(&candidate)?;
+    let mut intact = vec![];
+
+    // Reading an entry to the end checks its CRC
+    for (index, entry) in entries.iter().enumerate() {
+        let is_intact = archive.by_index_raw(index)?.encrypted()
+            || match archive.by_index(index) {
+                Ok(mut file) => io::copy(&mut file, &mut io::sink()).is_ok(),
+                Err(_) => false,
+            };
+
+        match is_intact {
+            true => intact.push(entry.clone()),
+            false => lost.push(entry.0.name()),
+        }
+    }
+
+    Ok(ZipRecovery {
+        zip: rebuild_zip(&intact, &[])?,
+        recovered: intact.iter().map(|(record, _)| record.name()).collect(),
+        lost,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipInconsistency {
+    pub name: String,
+    pub message: String,
+}
+
+impl fmt::Display for ZipInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+// Readers disagree on whether the central directory or the local headers win, so any
+// difference between the two can show a scanner other content than the browser gets
+pub fn check_zip_consistency(zip: &[u8]) -> anyhow::Result<Vec<ZipInconsistency>> {
+    let records = read_central_directory(zip)?;
+    let mut inconsistencies = vec![];
+    let mut names = HashSet::new();
+    let mut offsets = HashSet::new();
+
+    let mut report = |name: String, message: String| {
+        inconsistencies.push(ZipInconsistency { name, message });
+    };
+
+    for record in &records.entries {
+        let name = record.name();
+        let offset = records.local_header_offset(record);
+
+        if !names.insert(record.name.clone()) {
+            report(name.clone(), "listed more than once".to_string());
+        }
+
+        if !offsets.insert(offset) {
+            report(
+                name.clone(),
+                format!("shares the local header at byte {}", offset),
+            );
+        }
+
+        let Ok(local) = read_local_header(zip, offset) else {
+            report(name, format!("no local header at byte {}", offset));
+            continue;
+        };
+
+        let mut compare = |field: &str, central: u64, local: u64| {
+            if central != local {
+                report(
+                    name.clone(),
+                    format!(
+                        "{} is {} in the central directory but {} locally",
+                        field, central, local
+                    ),
+                );
+            }
+        };
+
+        compare(
+            "compression",
+            record.compression.into(),
+            local.compression.into(),
+        );
+        compare(
+            "encryption flag",
+            (record.flags & FLAG_ENCRYPTED).into(),
+            (local.flags & FLAG_ENCRYPTED).into(),
+        );
+
+        // Those are zero locally when a data descriptor follows the data
+        if local.flags & FLAG_DATA_DESCRIPTOR == 0 {
+            compare("crc32", record.crc32.into(), local.crc32.into());
+            compare(
+                "compressed size",
+                record.compressed_size.into(),
+                local.compressed_size.into(),
+            );
+            compare("size", record.size.into(), local.size.into());
+        }
+
+        if local.name != record.name {
+            report(
+                name.clone(),
+                format!(
+                    "is named {} in its local header",
+                    String::from_utf8_lossy(&local.name)
+                ),
+            );
+        }
+    }
+
+    // Entries only reachable by walking the local headers are hidden from directory readers
+    let mut offset = records.prefix;
+
+    while zip.get(offset..offset + 4) == Some(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes()) {
+        let Ok(local) = read_local_header(zip, offset) else {
+            break;
+        };
+
+        if !offsets.contains(&offset) {
+            report(
+                String::from_utf8_lossy(&local.name).into_owned(),
+                format!(
+                    "local header at byte {} is missing from the central directory",
+                    offset
+                ),
+            );
+        }
+
+        let Some((_, _, next)) = get_local_entry(zip, &local) else {
+            break;
+        };
+        offset = next;
+    }
+
+    Ok(inconsistencies)
+}
+
+fn get_local_entry(
+    zip: &[u8],
+    local: &LocalFileHeader,
+) -> Option<(CentralDirectoryRecord, usize, usize)> {
+    let data_start = local.data_start();
+
+    let mut record = CentralDirectoryRecord {
+        version_made_by: local.version_needed,
+        version_needed: local.version_needed,
+        flags: local.flags,
+        compression: local.compression,
+        modified_time: local.modified_time,
+        modified_date: local.modified_date,
+        crc32: local.crc32,
+        compressed_size: local.compressed_size,
+        size: local.size,
+        internal_attributes: 0,
+        external_attributes: 0,
+        local_header_offset: 0,
+        name: local.name.clone(),
+        extra: local.extra.clone(),
+        comment: vec![],
+    };
+
+    if local.flags & FLAG_DATA_DESCRIPTOR == 0 {
+        let data_end = data_start.checked_add(local.compressed_size as usize)?;
+
+        return (data_end <= zip.len()).then_some((record, data_end, data_end));
+    }
+
+    // Sizes then follow the data, in a descriptor whose compressed size matches its position
+    let signature = DATA_DESCRIPTOR_SIGNATURE.to_le_bytes();
+
+    (data_start..zip.len().saturating_sub(15)).find_map(|position| {
+        let descriptor = &zip[position..position + 16];
+        let field = |index: usize| {
+            u32::from_le_bytes(descriptor[index..index + 4].try_into().unwrap_or_default())
+        };
+
+        if descriptor[..4] != signature || field(8) as usize != position - data_start {
+            return None;
+        }
+
+        record.crc32 = field(4);
+        record.compressed_size = field(8);
+        record.size = field(12);
+
+        Some((record.clone(), position, position + 16))
+    })
+}
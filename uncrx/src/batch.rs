@@ -0,0 +1,359 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+        Arc, Mutex,
+    },
+    thread::{self, Scope},
+    time::Duration,
+};
+
+use super::{
+    archive::{decrypt_zip, ensure_not_encrypted, strip_zip_metadata},
+    cache::{write_atomically, ExtractionCache},
+    errors::UncrxError,
+    helpers::{parse_crx_with_options, ParseOptions},
+    permissions::{apply_permissions, FileModes, Ownership},
+    wrapper::unwrap_crx,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchJob {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Extraction {
+    Written,
+    Cached,
+}
+
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub job: BatchJob,
+    pub result: anyhow::Result<Extraction>,
+}
+
+// Every stage has its own workers and hands items over through a bounded queue, so a
+// slow stage makes the previous ones wait instead of piling CRX files up in memory
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub read_jobs: usize,
+    pub parse_jobs: usize,
+    pub extract_jobs: usize,
+    pub queue_size: usize,
+    pub strip_zip_metadata: bool,
+    pub password: Option<String>,
+    pub parse: ParseOptions,
+    pub cache: Option<ExtractionCache>,
+    // Applies to parsing, decrypting and stripping each CRX file. Timed out work keeps
+    // running in the background, at most MAX_TIMED_OUT_THREADS at once, after which the next
+    // files fail until some of it finishes
+    pub timeout: Option<Duration>,
+    pub file_modes: Option<FileModes>,
+    pub ownership: Option<Ownership>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        let cpus = thread::available_parallelism().map_or(1, usize::from);
+
+        BatchOptions {
+            read_jobs: 4,
+            parse_jobs: cpus,
+            extract_jobs: 4,
+            queue_size: 16,
+            strip_zip_metadata: false,
+            password: None,
+            parse: ParseOptions::default(),
+            cache: None,
+            timeout: None,
+            file_modes: None,
+            ownership: None,
+        }
+    }
+}
+
+pub const BATCH_STATE_FILE_NAME: &str = ".uncrx-batch-state";
+
+// Completed inputs are appended one per line as they finish, so a run killed halfway
+// leaves a usable state file behind
+#[derive(Debug)]
+pub struct BatchState {
+    completed: HashSet<PathBuf>,
+    file: File,
+}
+
+impl BatchState {
+    pub fn open(path: &Path, resume: bool) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut completed = HashSet::new();
+
+        if resume && path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+
+                if !line.is_empty() {
+                    completed.insert(PathBuf::from(line));
+                }
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .truncate(false)
+            .open(path)?;
+
+        if !resume {
+            file.set_len(0)?;
+        }
+
+        Ok(BatchState { completed, file })
+    }
+
+    pub fn is_completed(&self, job: &BatchJob) -> bool {
+        self.completed.contains(&job.input)
+    }
+
+    pub fn record(&mut self, job: &BatchJob) -> anyhow::Result<()> {
+        writeln!(self.file, "{}", job.input.display())?;
+        self.file.flush()?;
+
+        self.completed.insert(job.input.clone());
+
+        Ok(())
+    }
+}
+
+type Item<T> = (BatchJob, anyhow::Result<T>);
+
+enum Payload {
+    Cached,
+    Zip { key: Option<String>, zip: Vec<u8> },
+}
+
+// Threads left running by timed out work, on top of one per parse job
+pub const MAX_TIMED_OUT_THREADS: usize = 16;
+
+pub fn run_batch<I>(jobs: I, options: &BatchOptions, mut on_outcome: impl FnMut(BatchOutcome))
+where
+    I: IntoIterator<Item = BatchJob>,
+    I::IntoIter: Send,
+{
+    let jobs = jobs.into_iter();
+    let queue_size = options.queue_size.max(1);
+
+    let timeout_threads = Arc::new(AtomicUsize::new(0));
+    let max_timeout_threads = options.parse_jobs.max(1) + MAX_TIMED_OUT_THREADS;
+
+    thread::scope(|scope| {
+        let (pending, pending_receiver) = mpsc::sync_channel(queue_size);
+        let (read, read_receiver) = mpsc::sync_channel(queue_size);
+        let (parsed, parsed_receiver) = mpsc::sync_channel(queue_size);
+        let (done, outcomes) = mpsc::sync_channel(queue_size);
+
+        scope.spawn(move || {
+            for job in jobs {
+                if pending.send((job, Ok(()))).is_err() {
+                    return;
+                }
+            }
+        });
+
+        spawn_stage(
+            scope,
+            options.read_jobs,
+            pending_receiver,
+            read,
+            |job, _| Ok(fs::read(&job.input)?),
+        );
+
+        spawn_stage(
+            scope,
+            options.parse_jobs,
+            read_receiver,
+            parsed,
+            |job, data| {
+                let key = options.cache.as_ref().map(|_| {
+                    ExtractionCache::key(
+                        &data,
+                        options.strip_zip_metadata,
+                        options.password.is_some(),
+                    )
+                });
+
+                if let (Some(cache), Some(key)) = (&options.cache, &key) {
+                    if cache.restore(key, &job.output)? {
+                        apply_permissions(
+                            &job.output,
+                            options.file_modes.as_ref(),
+                            options.ownership.as_ref(),
+                        )?;
+
+                        return Ok(Payload::Cached);
+                    }
+                }
+
+                let strip = options.strip_zip_metadata;
+                let parse = options.parse;
+
+                let zip = match options.timeout {
+                    Some(timeout) => {
+                        let password = options.password.clone();
+
+                        with_timeout(timeout, &timeout_threads, max_timeout_threads, move || {
+                            get_zip(&data, &parse, strip, password.as_deref())
+                        })?
+                    }
+                    None => get_zip(&data, &parse, strip, options.password.as_deref())?,
+                };
+
+                Ok(Payload::Zip { key, zip })
+            },
+        );
+
+        spawn_stage(
+            scope,
+            options.extract_jobs,
+            parsed_receiver,
+            done,
+            |job, payload| {
+                let Payload::Zip { key, zip } = payload else {
+                    return Ok(Extraction::Cached);
+                };
+
+                if let Some(parent) = job.output.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                // Renamed into place, so an interrupted run leaves no partial zip behind
+                write_atomically(&job.output, &zip)?;
+
+                apply_permissions(
+                    &job.output,
+                    options.file_modes.as_ref(),
+                    options.ownership.as_ref(),
+                )?;
+
+                if let (Some(cache), Some(key)) = (&options.cache, key) {
+                    cache.insert(&key, &zip)?;
+                }
+
+                Ok(Extraction::Written)
+            },
+        );
+
+        for (job, result) in outcomes {
+            on_outcome(BatchOutcome { job, result });
+        }
+    });
+}
+
+fn get_zip(
+    data: &[u8],
+    parse: &ParseOptions,
+    strip: bool,
+    password: Option<&str>,
+) -> anyhow::Result<Vec<u8>> {
+    let zip = parse_crx_with_options(&unwrap_crx(data)?, parse)?.zip;
+
+    let zip = match password {
+        Some(password) => decrypt_zip(&zip, password.as_bytes())?,
+        None => {
+            ensure_not_encrypted(&zip)?;
+            zip
+        }
+    };
+
+    match strip {
+        true => strip_zip_metadata(&zip),
+        false => Ok(zip),
+    }
+}
+
+// Threads can't be cancelled, so work that times out is left to finish on its own and
+// its result is dropped. The threads are counted, no new one is started past the limit
+fn with_timeout<T, F>(
+    timeout: Duration,
+    threads: &Arc<AtomicUsize>,
+    max_threads: usize,
+    work: F,
+) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    if threads.fetch_add(1, Ordering::SeqCst) >= max_threads {
+        threads.fetch_sub(1, Ordering::SeqCst);
+
+        return Err(anyhow::anyhow!(
+            "Too many timed out extractions are still running"
+        ));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let slot = ThreadSlot(Arc::clone(threads));
+
+    thread::spawn(move || {
+        let _slot = slot;
+        let _ = sender.send(work());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(UncrxError::TimedOut(timeout).into()),
+        Err(RecvTimeoutError::Disconnected) => Err(anyhow::anyhow!("Extraction panicked")),
+    }
+}
+
+// Frees the slot of a thread when it ends, panics included
+struct ThreadSlot(Arc<AtomicUsize>);
+
+impl Drop for ThreadSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Failed items skip the remaining stages and are passed along untouched
+fn spawn_stage<'scope, T, U, F>(
+    scope: &'scope Scope<'scope, '_>,
+    workers: usize,
+    input: Receiver<Item<T>>,
+    output: SyncSender<Item<U>>,
+    stage: F,
+) where
+    T: Send + 'scope,
+    U: Send + 'scope,
+    F: Fn(&BatchJob, T) -> anyhow::Result<U> + Send + Sync + 'scope,
+{
+    let input = Arc::new(Mutex::new(input));
+    let stage = Arc::new(stage);
+
+    for _ in 0..workers.max(1) {
+        let input = input.clone();
+        let stage = stage.clone();
+        let output = output.clone();
+
+        scope.spawn(move || loop {
+            let Ok((job, item)) = input.lock().expect("Batch queue is poisoned").recv() else {
+                return;
+            };
+
+            let result = item.and_then(|item| stage(&job, item));
+
+            if output.send((job, result)).is_err() {
+                return;
+            }
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use std::{env, fs};
+
+use clap::Parser;
+use uncrx::{
+    pack::read_private_key,
+    test_support::{build_fixture, generate_test_key, FixtureQuirk},
+};
+
+/// Build CRX fixtures with the quirks parser bugs are reproduced with
+#[derive(Parser)]
+#[command(name = "mkcrx")]
+struct Args {
+    /// Path of the CRX file to write
+    output: String,
+    /// CRX format version, 2 or 3
+    #[arg(long, default_value_t = 3)]
+    crx_version: u32,
+    /// zero-length-key, entries=N, trailing-garbage=N or zip64, repeatable
+    #[arg(long)]
+    quirk: Vec<FixtureQuirk>,
+    /// Private key used to sign the fixture, a throwaway one is generated if omitted
+    #[arg(short, long)]
+    key: Option<String>,
+}
+
+fn main() {
+    let args = Args::parse();
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+
+    let key = match args.key {
+        Some(path) => {
+            let pem = fs::read_to_string(current_dir.join(path)).expect("Failed to read key");
+            read_private_key(&pem).expect("Failed to parse key")
+        }
+        None => generate_test_key().expect("Failed to generate key"),
+    };
+
+    let crx = build_fixture(args.crx_version, &args.quirk, &key).expect("Failed to build fixture");
+
+    fs::write(current_dir.join(&args.output), &crx).expect("Failed to write file");
+
+    println!("Wrote {} ({} bytes)", args.output, crx.len());
+}
@@ -0,0 +1,101 @@
+use std::{
+    env,
+    io::Cursor,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use zip::{result::ZipError, ZipArchive};
+
+use super::archive::open_archive;
+
+// Chrome reserves file names starting with an underscore, hence no _metadata-style name
+pub const BUILD_INFO_FILE_NAME: &str = "uncrx-build-info.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub commit: Option<String>,
+    pub build_time: String,
+    pub builder: Option<String>,
+}
+
+impl BuildInfo {
+    pub fn new(commit: Option<String>, timestamp: u64, builder: Option<String>) -> Self {
+        BuildInfo {
+            commit,
+            build_time: format_unix_time(timestamp),
+            builder,
+        }
+    }
+}
+
+// Gathers the build info from git, the environment and the clock
+pub fn collect_build_info(directory: &Path, builder: Option<String>) -> BuildInfo {
+    let commit = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+
+    // SOURCE_DATE_EPOCH keeps the entry stable for reproducible builds
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+
+    let builder = builder.or_else(|| env::var("USER").or_else(|_| env::var("USERNAME")).ok());
+
+    BuildInfo::new(commit, timestamp, builder)
+}
+
+pub fn get_build_info(zip: &[u8]) -> anyhow::Result<Option<BuildInfo>> {
+    read_build_info(&mut open_archive(zip)?)
+}
+
+pub fn read_build_info(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+) -> anyhow::Result<Option<BuildInfo>> {
+    let file = match archive.by_name(BUILD_INFO_FILE_NAME) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    Ok(Some(serde_json::from_reader(file)?))
+}
+
+pub fn format_unix_time(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Civil-from-days conversion, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
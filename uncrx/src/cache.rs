@@ -0,0 +1,155 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::get_sha256;
+
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// Zip payloads keyed by the SHA-256 of the CRX file they were extracted from, plus the
+// options that change the written bytes
+#[derive(Debug, Clone)]
+pub struct ExtractionCache {
+    pub directory: PathBuf,
+}
+
+impl ExtractionCache {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        ExtractionCache {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn key(crx: &[u8], strip_zip_metadata: bool, decrypted: bool) -> String {
+        let mut key = get_sha256(crx);
+
+        if strip_zip_metadata {
+            key.push_str("-stripped");
+        }
+
+        if decrypted {
+            key.push_str("-decrypted");
+        }
+
+        key
+    }
+
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(&key[..2]).join(format!("{}.zip", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<PathBuf> {
+        Some(self.path(key)).filter(|path| path.is_file())
+    }
+
+    pub fn insert(&self, key: &str, zip: &[u8]) -> anyhow::Result<PathBuf> {
+        let path = self.path(key);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        write_atomically(&path, zip)?;
+
+        // Entries are only ever replaced as a whole, never written through
+        let mut permissions = fs::metadata(&path)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions)?;
+
+        Ok(path)
+    }
+
+    // Copied rather than linked, so permissions applied to the output or later edits of it
+    // never reach the cache entry
+    pub fn restore(&self, key: &str, output: &Path) -> anyhow::Result<bool> {
+        let Some(cached) = self.get(key) else {
+            return Ok(false);
+        };
+
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        write_atomically(output, &fs::read(&cached)?)?;
+
+        Ok(true)
+    }
+}
+
+// The validators of a downloaded file, sent back so the server can answer 304 Not Modified
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+// Downloads keyed by the SHA-256 of their URL, each body stored next to its validators
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    pub directory: PathBuf,
+}
+
+impl HttpCache {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        HttpCache {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, url: &str, extension: &str) -> PathBuf {
+        let key = get_sha256(url.as_bytes());
+
+        self.directory
+            .join(&key[..2])
+            .join(format!("{}.{}", key, extension))
+    }
+
+    pub fn get(&self, url: &str) -> Option<(CachedResponse, Vec<u8>)> {
+        let json = fs::read_to_string(self.path(url, "json")).ok()?;
+        let response: CachedResponse = serde_json::from_str(&json).ok()?;
+
+        // Guards against the astronomically unlikely hash collision
+        if response.url != url {
+            return None;
+        }
+
+        Some((response, fs::read(self.path(url, "body")).ok()?))
+    }
+
+    pub fn insert(&self, response: &CachedResponse, body: &[u8]) -> anyhow::Result<()> {
+        let path = self.path(&response.url, "body");
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // The body goes first, validators without it are never read
+        write_atomically(&path, body)?;
+        write_atomically(
+            &self.path(&response.url, "json"),
+            serde_json::to_string(response)?.as_bytes(),
+        )?;
+
+        Ok(())
+    }
+}
+
+// Written aside then renamed, so concurrent readers never see a partial file
+pub fn write_atomically(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let temporary = path.with_extension(format!(
+        "{}.{}.tmp",
+        process::id(),
+        TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    fs::write(&temporary, data)?;
+    fs::rename(&temporary, path)?;
+
+    Ok(())
+}
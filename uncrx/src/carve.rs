@@ -0,0 +1,45 @@
+use super::{
+    constants::CRX_MAGIC_VALUE,
+    helpers::{get_crx_length_with_options, get_crx_version, ParseOptions},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarvedCrx {
+    pub offset: usize,
+    pub length: usize,
+    pub version: u32,
+}
+
+pub fn find_crx_files(data: &[u8]) -> Vec<CarvedCrx> {
+    find_crx_files_with_options(data, &ParseOptions::default())
+}
+
+pub fn find_crx_files_with_options(data: &[u8], options: &ParseOptions) -> Vec<CarvedCrx> {
+    let mut carved = vec![];
+    let mut offset = 0;
+
+    while let Some(position) = find_magic(data, offset) {
+        let candidate = &data[position..];
+
+        match get_crx_length_with_options(candidate, options) {
+            Ok(length) => {
+                carved.push(CarvedCrx {
+                    offset: position,
+                    length,
+                    version: get_crx_version(candidate).unwrap_or_default(),
+                });
+                offset = position + length;
+            }
+            Err(_) => offset = position + 1,
+        }
+    }
+
+    carved
+}
+
+fn find_magic(data: &[u8], from: usize) -> Option<usize> {
+    data.get(from..)?
+        .windows(CRX_MAGIC_VALUE.len())
+        .position(|window| window == CRX_MAGIC_VALUE)
+        .map(|position| from + position)
+}
@@ -0,0 +1,180 @@
+use serde_json::Value;
+
+use super::{
+    archive::read_entry_to_string,
+    manifest::{get_declared_scripts, get_manifest, get_manifest_version},
+    types::CrxExtension,
+};
+
+// Minimum Chrome version shipping each API, matched by longest prefix
+pub const API_MINIMUM_VERSIONS: &[(&str, u32)] = &[
+    ("chrome.action", 88),
+    ("chrome.action.openPopup", 127),
+    ("chrome.declarativeNetRequest", 84),
+    ("chrome.declarativeNetRequest.updateSessionRules", 90),
+    ("chrome.declarativeNetRequest.getMatchedRules", 85),
+    ("chrome.dom", 88),
+    ("chrome.offscreen", 109),
+    ("chrome.readingList", 120),
+    ("chrome.runtime.getContexts", 116),
+    ("chrome.scripting", 88),
+    ("chrome.scripting.registerContentScripts", 96),
+    ("chrome.search", 87),
+    ("chrome.sidePanel", 114),
+    ("chrome.storage.session", 102),
+    ("chrome.tabGroups", 89),
+    ("chrome.tabs.group", 88),
+    ("chrome.userScripts", 120),
+];
+
+// Minimum Chrome version supporting each manifest feature
+pub const MANIFEST_MINIMUM_VERSIONS: &[(&str, u32)] = &[
+    ("manifest_version 3", 88),
+    ("background.type module", 91),
+    ("declarative_net_request", 84),
+    ("side_panel", 114),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityRequirement {
+    pub feature: String,
+    pub source: String,
+    pub minimum_version: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompatibilityReport {
+    pub declared_minimum_version: Option<u32>,
+    pub requirements: Vec<CompatibilityRequirement>,
+}
+
+impl CompatibilityReport {
+    pub fn required_minimum_version(&self) -> Option<u32> {
+        self.requirements
+            .iter()
+            .map(|requirement| requirement.minimum_version)
+            .max()
+    }
+
+    pub fn is_declared_version_sufficient(&self) -> bool {
+        match (
+            self.declared_minimum_version,
+            self.required_minimum_version(),
+        ) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(declared), Some(required)) => declared >= required,
+        }
+    }
+}
+
+pub fn find_api_references(source: &str) -> Vec<String> {
+    let is_identifier = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    let mut references = vec![];
+
+    for (index, _) in source.match_indices("chrome.") {
+        if source[..index]
+            .chars()
+            .next_back()
+            .is_some_and(is_identifier)
+        {
+            continue;
+        }
+
+        let reference: String = source[index..]
+            .chars()
+            .take_while(|&c| is_identifier(c) || c == '.')
+            .collect();
+        let reference = reference.trim_end_matches('.').to_string();
+
+        if reference.len() > "chrome.".len() && !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+
+    references
+}
+
+pub fn get_api_minimum_version(reference: &str) -> Option<u32> {
+    API_MINIMUM_VERSIONS
+        .iter()
+        .filter(|(api, _)| {
+            reference == *api
+                || reference
+                    .strip_prefix(api)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|(api, _)| api.len())
+        .map(|(_, version)| *version)
+}
+
+fn get_manifest_requirements(manifest: &Value) -> Vec<CompatibilityRequirement> {
+    let mut features = vec![];
+
+    if get_manifest_version(manifest) >= 3 {
+        features.push("manifest_version 3");
+    }
+
+    if manifest
+        .pointer("/background/type")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| kind == "module")
+    {
+        features.push("background.type module");
+    }
+
+    for key in ["declarative_net_request", "side_panel"] {
+        if manifest.get(key).is_some() {
+            features.push(key);
+        }
+    }
+
+    features
+        .into_iter()
+        .filter_map(|feature| {
+            MANIFEST_MINIMUM_VERSIONS
+                .iter()
+                .find(|(name, _)| *name == feature)
+                .map(|(name, version)| CompatibilityRequirement {
+                    feature: name.to_string(),
+                    source: "manifest.json".to_string(),
+                    minimum_version: *version,
+                })
+        })
+        .collect()
+}
+
+pub fn parse_chrome_version(version: &str) -> Option<u32> {
+    version.split('.').next()?.trim().parse().ok()
+}
+
+pub fn get_compatibility_report(extension: &CrxExtension) -> anyhow::Result<CompatibilityReport> {
+    let manifest = get_manifest(&extension.zip)?;
+
+    let mut report = CompatibilityReport {
+        declared_minimum_version: manifest
+            .get("minimum_chrome_version")
+            .and_then(Value::as_str)
+            .and_then(parse_chrome_version),
+        requirements: get_manifest_requirements(&manifest),
+    };
+
+    for script in get_declared_scripts(&manifest) {
+        // Scripts missing from the archive are reported by manifest validation
+        let Ok(source) = read_entry_to_string(&extension.zip, &script) else {
+            continue;
+        };
+
+        for reference in find_api_references(&source) {
+            if let Some(minimum_version) = get_api_minimum_version(&reference) {
+                report.requirements.push(CompatibilityRequirement {
+                    feature: reference,
+                    source: script.clone(),
+                    minimum_version,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,36 @@
+use std::ops::Range;
+
+pub const CRX_MAGIC_VALUE: [u8; 4] = [0x43, 0x72, 0x32, 0x34];
+pub const MAGIC_VALUE_RANGE: Range<usize> = 0..4;
+pub const CRX_VERSION_RANGE: Range<usize> = 4..8;
+pub const PUBLIC_KEY_LENGTH_RANGE: Range<usize> = 8..12;
+pub const SIGNATURE_LENGTH_RANGE: Range<usize> = 12..16;
+
+// CrxFileHeader, AsymmetricKeyProof and SignedData field numbers from Chromium's crx3.proto
+pub const CRX3_SHA256_WITH_RSA_FIELD: u32 = 2;
+pub const CRX3_SHA256_WITH_ECDSA_FIELD: u32 = 3;
+pub const CRX3_SIGNED_HEADER_DATA_FIELD: u32 = 10000;
+pub const CRX3_PUBLIC_KEY_FIELD: u32 = 1;
+pub const CRX3_SIGNATURE_FIELD: u32 = 2;
+pub const CRX3_CRX_ID_FIELD: u32 = 1;
+pub const CRX_ID_LENGTH: usize = 16;
+
+// DER SubjectPublicKeyInfo prefixes of EC keys on the curves ECDSA proofs are checked for,
+// each followed by the uncompressed point
+pub const EC_P256_PUBLIC_KEY_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+pub const EC_P384_PUBLIC_KEY_PREFIX: [u8; 23] = [
+    0x30, 0x76, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b,
+    0x81, 0x04, 0x00, 0x22, 0x03, 0x62, 0x00,
+];
+// id-ecPublicKey, the algorithm of EC keys whatever their curve
+pub const EC_PUBLIC_KEY_OID: [u8; 9] = [0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+// SHA-256 of the key the Chrome Web Store signs every published CRX3 file with, as checked by
+// Chromium's crx_verifier.cc
+pub const WEBSTORE_PUBLISHER_KEY_HASH: [u8; 32] = [
+    0x61, 0xf7, 0xf2, 0xa6, 0xbf, 0xcf, 0x74, 0xcd, 0x0b, 0xc1, 0xfe, 0x24, 0x97, 0xcc, 0x9b, 0x04,
+    0x25, 0x4c, 0x65, 0x8f, 0x79, 0xf2, 0x14, 0x53, 0x92, 0x86, 0x7e, 0xa8, 0x36, 0x63, 0x67, 0xcf,
+];
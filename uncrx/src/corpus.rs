@@ -0,0 +1,189 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use base64ct::{Base64, Encoding};
+use serde_json::Value;
+
+use super::{
+    archive::{unpack_zip_with_options, UnpackOptions},
+    id::{get_extension_id, get_extension_id_from_public_key, is_valid_extension_id},
+    manifest::{get_manifest, parse_manifest, MANIFEST_FILE_NAME},
+    validation::is_valid_version,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorpusMerge {
+    Added(PathBuf),
+    Present(PathBuf),
+}
+
+pub fn get_corpus_path(root: &Path, id: &str, version: &str) -> PathBuf {
+    root.join(id).join(version)
+}
+
+// Only 1 to 4 dot-separated integers, so the version is always a plain directory name
+fn get_valid_version(manifest: &Value) -> anyhow::Result<&str> {
+    manifest["version"]
+        .as_str()
+        .filter(|version| is_valid_version(version))
+        .ok_or_else(|| anyhow::anyhow!("The manifest has no valid version"))
+}
+
+// Unpacks into <ROOT>/<ID>/<VERSION> unless that directory already exists, nothing else
+// in the tree is ever written to
+pub fn merge_into_corpus(
+    root: &Path,
+    crx: &[u8],
+    zip: &[u8],
+    options: &UnpackOptions,
+) -> anyhow::Result<CorpusMerge> {
+    let id = get_extension_id(crx)?;
+    let manifest = get_manifest(zip)?;
+
+    add_to_corpus(root, &id, get_valid_version(&manifest)?, |staging| {
+        unpack_zip_with_options(zip, staging, options).map(|_| ())
+    })
+}
+
+fn add_to_corpus(
+    root: &Path,
+    id: &str,
+    version: &str,
+    fill: impl FnOnce(&Path) -> anyhow::Result<()>,
+) -> anyhow::Result<CorpusMerge> {
+    let target = get_corpus_path(root, id, version);
+
+    if target.exists() {
+        return Ok(CorpusMerge::Present(target));
+    }
+
+    // Filled next to the target first, so an interrupted run leaves no partial version
+    let staging = root
+        .join(id)
+        .join(format!(".{}.{}.tmp", version, process::id()));
+
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    if let Err(error) = fill(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+
+    if let Err(error) = fs::rename(&staging, &target) {
+        fs::remove_dir_all(&staging)?;
+
+        // Another run may have added the same version in the meantime
+        if target.exists() {
+            return Ok(CorpusMerge::Present(target));
+        }
+
+        return Err(error.into());
+    }
+
+    Ok(CorpusMerge::Added(target))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledExtension {
+    pub id: String,
+    pub version: String,
+    pub path: PathBuf,
+}
+
+// Chrome installs extensions into <PROFILE>/Extensions/<ID>/<VERSION>_<N>, where N counts
+// reinstalls of the same version. Directories not named after an id are identified by the
+// key Chrome writes into their manifest
+pub fn find_installed_extensions(directory: &Path) -> anyhow::Result<Vec<InstalledExtension>> {
+    let mut extensions = vec![];
+
+    for id_dir in get_sorted_dirs(directory)? {
+        for version_dir in get_sorted_dirs(&id_dir)? {
+            let Ok(content) = fs::read_to_string(version_dir.join(MANIFEST_FILE_NAME)) else {
+                continue;
+            };
+
+            // Half-written or corrupted installs are left out
+            let Ok(manifest) = parse_manifest(&content) else {
+                continue;
+            };
+
+            let name = id_dir.file_name().unwrap_or_default().to_string_lossy();
+
+            let (Some(id), Ok(version)) = (
+                get_installed_id(&name, &manifest),
+                get_valid_version(&manifest),
+            ) else {
+                continue;
+            };
+
+            extensions.push(InstalledExtension {
+                id,
+                version: version.to_string(),
+                path: version_dir,
+            });
+        }
+    }
+
+    Ok(extensions)
+}
+
+fn get_installed_id(name: &str, manifest: &Value) -> Option<String> {
+    if is_valid_extension_id(name) {
+        return Some(name.to_string());
+    }
+
+    let key = Base64::decode_vec(manifest["key"].as_str()?.trim()).ok()?;
+
+    Some(get_extension_id_from_public_key(&key))
+}
+
+// Copies an installed extension into <ROOT>/<ID>/<VERSION>, the same way merge_into_corpus
+// unpacks CRX files
+pub fn import_into_corpus(
+    root: &Path,
+    extension: &InstalledExtension,
+) -> anyhow::Result<CorpusMerge> {
+    add_to_corpus(root, &extension.id, &extension.version, |staging| {
+        copy_directory(&extension.path, staging)
+    })
+}
+
+// Symlinks are skipped, Chrome never writes them and they could point anywhere
+fn copy_directory(source: &Path, target: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(target)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let destination = target.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_directory(&entry.path(), &destination)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn get_sorted_dirs(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut dirs = vec![];
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+
+    dirs.sort();
+
+    Ok(dirs)
+}
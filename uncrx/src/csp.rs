@@ -0,0 +1,181 @@
+use std::fmt;
+
+use serde_json::Value;
+
+const CSP_KEY: &str = "content_security_policy";
+const RESTRICTED_DIRECTIVES: [&str; 3] = ["script-src", "object-src", "worker-src"];
+const LOCALHOST_PREFIXES: [&str; 4] = [
+    "http://localhost",
+    "http://127.0.0.1",
+    "https://localhost",
+    "https://127.0.0.1",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentSecurityPolicy {
+    ManifestV2(String),
+    ManifestV3 {
+        extension_pages: Option<String>,
+        sandbox: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CspDirective {
+    pub name: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CspViolation {
+    pub context: String,
+    pub directive: Option<String>,
+    pub source: Option<String>,
+    pub reason: String,
+}
+
+impl fmt::Display for CspViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.directive, &self.source) {
+            (Some(directive), Some(source)) => write!(
+                f,
+                "{}: {} in {} is not allowed ({})",
+                self.context, source, directive, self.reason
+            ),
+            _ => write!(f, "{}: {}", self.context, self.reason),
+        }
+    }
+}
+
+pub fn get_content_security_policy(
+    manifest: &Value,
+) -> anyhow::Result<Option<ContentSecurityPolicy>> {
+    match manifest.get(CSP_KEY) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(policy)) => Ok(Some(ContentSecurityPolicy::ManifestV2(policy.clone()))),
+        Some(Value::Object(policies)) => {
+            let get_policy = |key: &str| -> anyhow::Result<Option<String>> {
+                match policies.get(key) {
+                    None => Ok(None),
+                    Some(Value::String(policy)) => Ok(Some(policy.clone())),
+                    Some(_) => Err(anyhow::anyhow!("{}.{} must be a string", CSP_KEY, key)),
+                }
+            };
+
+            Ok(Some(ContentSecurityPolicy::ManifestV3 {
+                extension_pages: get_policy("extension_pages")?,
+                sandbox: get_policy("sandbox")?,
+            }))
+        }
+        Some(_) => Err(anyhow::anyhow!("{} must be a string or an object", CSP_KEY)),
+    }
+}
+
+pub fn parse_policy(policy: &str) -> Vec<CspDirective> {
+    policy
+        .split(';')
+        .filter_map(|directive| {
+            let mut tokens = directive.split_whitespace();
+            let name = tokens.next()?.to_ascii_lowercase();
+
+            Some(CspDirective {
+                name,
+                sources: tokens.map(|source| source.to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+pub fn evaluate_csp(policy: &ContentSecurityPolicy, manifest_version: u64) -> Vec<CspViolation> {
+    match (policy, manifest_version >= 3) {
+        (ContentSecurityPolicy::ManifestV2(policy), false) => {
+            evaluate_policy(CSP_KEY, policy, is_allowed_v2_source)
+        }
+        (
+            ContentSecurityPolicy::ManifestV3 {
+                extension_pages, ..
+            },
+            true,
+        ) => match extension_pages {
+            Some(policy) => evaluate_policy(
+                &format!("{}.extension_pages", CSP_KEY),
+                policy,
+                is_allowed_v3_source,
+            ),
+            None => vec![],
+        },
+        (ContentSecurityPolicy::ManifestV2(_), true) => vec![CspViolation {
+            context: CSP_KEY.to_string(),
+            directive: None,
+            source: None,
+            reason: "manifest v3 requires an object with extension_pages/sandbox keys".to_string(),
+        }],
+        (ContentSecurityPolicy::ManifestV3 { .. }, false) => vec![CspViolation {
+            context: CSP_KEY.to_string(),
+            directive: None,
+            source: None,
+            reason: "manifest v2 requires a policy string".to_string(),
+        }],
+    }
+}
+
+fn evaluate_policy(
+    context: &str,
+    policy: &str,
+    is_allowed: fn(&str) -> Result<(), &'static str>,
+) -> Vec<CspViolation> {
+    parse_policy(policy)
+        .into_iter()
+        .filter(|directive| RESTRICTED_DIRECTIVES.contains(&directive.name.as_str()))
+        .flat_map(|directive| {
+            directive
+                .sources
+                .iter()
+                .filter_map(|source| {
+                    is_allowed(source).err().map(|reason| CspViolation {
+                        context: context.to_string(),
+                        directive: Some(directive.name.clone()),
+                        source: Some(source.clone()),
+                        reason: reason.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn is_localhost(source: &str) -> bool {
+    LOCALHOST_PREFIXES.iter().any(|prefix| {
+        source
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(':') || rest.starts_with('/'))
+    })
+}
+
+fn is_allowed_v3_source(source: &str) -> Result<(), &'static str> {
+    match source.to_ascii_lowercase().as_str() {
+        "'self'" | "'none'" | "'wasm-unsafe-eval'" => Ok(()),
+        "'unsafe-eval'" => Err("unsafe-eval is forbidden in manifest v3"),
+        "'unsafe-inline'" => Err("inline script is forbidden"),
+        source if is_localhost(source) => Ok(()),
+        _ => Err("remote script is forbidden in manifest v3"),
+    }
+}
+
+fn is_allowed_v2_source(source: &str) -> Result<(), &'static str> {
+    match source.to_ascii_lowercase().as_str() {
+        "'self'" | "'none'" | "'unsafe-eval'" | "'wasm-unsafe-eval'" => Ok(()),
+        "'unsafe-inline'" => Err("inline script is forbidden"),
+        source if is_localhost(source) => Ok(()),
+        source if source.contains('*') => Err("wildcard sources are forbidden"),
+        source if source.starts_with("https://") => Ok(()),
+        source
+            if source.starts_with("'sha256-")
+                || source.starts_with("'sha384-")
+                || source.starts_with("'sha512-") =>
+        {
+            Ok(())
+        }
+        _ => Err("remote script must be loaded over https"),
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    io::{self, Read},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ureq::http::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    HeaderMap, HeaderName, StatusCode,
+};
+
+use super::{
+    cache::{CachedResponse, HttpCache},
+    errors::UncrxError,
+};
+
+pub const DEFAULT_MAX_DOWNLOAD_SIZE: u64 = 256 * 1024 * 1024;
+
+// Servers label CRX files inconsistently, but a textual type is an error or login page
+const REJECTED_CONTENT_TYPES: [&str; 4] = [
+    "text/",
+    "application/json",
+    "application/xml",
+    "application/xhtml+xml",
+];
+
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    pub max_size: u64,
+    // Bytes per second, unlimited when omitted
+    pub rate_limit: Option<u64>,
+    pub cache: Option<HttpCache>,
+    // Fails every download before any connection is attempted
+    pub offline: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        DownloadOptions {
+            max_size: DEFAULT_MAX_DOWNLOAD_SIZE,
+            rate_limit: None,
+            cache: None,
+            offline: false,
+        }
+    }
+}
+
+pub fn is_url(input: &str) -> bool {
+    let lowercase = input.to_ascii_lowercase();
+
+    lowercase.starts_with("https://") || lowercase.starts_with("http://")
+}
+
+pub fn is_accepted_content_type(content_type: &str) -> bool {
+    let content_type = content_type.trim().to_ascii_lowercase();
+
+    !REJECTED_CONTENT_TYPES
+        .iter()
+        .any(|rejected| content_type.starts_with(rejected))
+}
+
+pub fn download(url: &str, options: &DownloadOptions) -> anyhow::Result<Vec<u8>> {
+    if options.offline {
+        return Err(UncrxError::Offline(url.to_string()).into());
+    }
+
+    let max_size = options.max_size;
+
+    let cached = options.cache.as_ref().and_then(|cache| cache.get(url));
+
+    let mut request = ureq::get(url);
+
+    if let Some((validators, _)) = &cached {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let mut response = request.call()?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some((_, body)) = cached {
+            return Ok(body);
+        }
+    }
+
+    if let Some(content_type) = get_header(response.headers(), CONTENT_TYPE) {
+        if !is_accepted_content_type(content_type) {
+            return Err(UncrxError::UnexpectedContentType(content_type.to_string()).into());
+        }
+    }
+
+    // Refused upfront when announced, the body limit catches servers that lie about it
+    let length =
+        get_header(response.headers(), CONTENT_LENGTH).and_then(|value| value.parse::<u64>().ok());
+
+    if length.is_some_and(|length| length > max_size) {
+        return Err(UncrxError::DownloadTooLarge(max_size).into());
+    }
+
+    let mut reader = response.body_mut().with_config().limit(max_size).reader();
+
+    let mut data = vec![];
+
+    let result = match options.rate_limit {
+        Some(rate) => RateLimitedReader::new(reader, rate).read_to_end(&mut data),
+        None => reader.read_to_end(&mut data),
+    };
+
+    // The body reader reports the limit as an io::Error wrapping the ureq one
+    match result.map_err(ureq::Error::from) {
+        Ok(_) => {}
+        Err(ureq::Error::BodyExceedsLimit(_)) => {
+            return Err(UncrxError::DownloadTooLarge(max_size).into())
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    if let Some(cache) = &options.cache {
+        let header = |name| get_header(response.headers(), name).map(str::to_string);
+
+        let validators = CachedResponse {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+
+        // Without validators the server could never confirm the cached copy is current
+        if validators.etag.is_some() || validators.last_modified.is_some() {
+            cache.insert(&validators, &data)?;
+        }
+    }
+
+    Ok(data)
+}
+
+fn get_header(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+// Sleeps whenever reading got ahead of the allowed rate, reading a tenth of a second worth
+// of data at most at once so the transfer stays smooth
+struct RateLimitedReader<R> {
+    inner: R,
+    rate: u64,
+    start: Instant,
+    read: u64,
+}
+
+impl<R: Read> RateLimitedReader<R> {
+    fn new(inner: R, rate: u64) -> Self {
+        RateLimitedReader {
+            inner,
+            rate: rate.max(1),
+            start: Instant::now(),
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for RateLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let chunk = buf.len().min((self.rate / 10).max(1) as usize);
+        let read = self.inner.read(&mut buf[..chunk])?;
+
+        self.read += read as u64;
+
+        let expected = Duration::from_secs_f64(self.read as f64 / self.rate as f64);
+        let elapsed = self.start.elapsed();
+
+        if expected > elapsed {
+            thread::sleep(expected - elapsed);
+        }
+
+        Ok(read)
+    }
+}
@@ -0,0 +1,106 @@
+use std::{error::Error, fmt, time::Duration};
+
+use super::types::CrxField;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UncrxError {
+    EncryptedEntries(Vec<String>),
+    InvalidPassword,
+    TimedOut(Duration),
+    InvalidMagic([u8; 4]),
+    UnsupportedVersion(u32),
+    Truncated {
+        needed: usize,
+        found: usize,
+    },
+    MissingZip {
+        offset: usize,
+        found: Vec<u8>,
+    },
+    HeaderFieldTooLarge {
+        field: CrxField,
+        length: u32,
+        limit: u32,
+    },
+    DownloadTooLarge(u64),
+    UnexpectedContentType(String),
+    Offline(String),
+    InvalidExtensionId(String),
+}
+
+impl Error for UncrxError {}
+
+impl fmt::Display for UncrxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UncrxError::EncryptedEntries(paths) => {
+                write!(f, "Encrypted zip entries: {}", paths.join(", "))
+            }
+            UncrxError::InvalidPassword => write!(f, "Invalid password for encrypted zip entries"),
+            UncrxError::TimedOut(timeout) => write!(f, "Timed out after {:?}", timeout),
+            UncrxError::InvalidMagic(found) => {
+                write!(
+                    f,
+                    "Invalid CRX magic at byte 0: expected 'Cr24', found '{}'",
+                    escape_bytes(found)
+                )?;
+
+                match found {
+                    [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6] => {
+                        write!(f, ". This looks like a plain zip, try --force-zip")
+                    }
+                    [0x1f, 0x8b, ..] => write!(f, ". This looks like a gzip file"),
+                    [b'<', ..] => write!(f, ". This looks like an HTML page, not a download"),
+                    _ => Ok(()),
+                }
+            }
+            UncrxError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported CRX version at byte 4: expected 2 or 3, found {}",
+                version
+            ),
+            UncrxError::Truncated { needed, found } => write!(
+                f,
+                "Expected at least {} bytes, found {}. The file looks truncated",
+                needed, found
+            ),
+            UncrxError::MissingZip { offset, found } => write!(
+                f,
+                "Expected a zip archive at byte {}, found '{}'. The header lengths may be corrupted",
+                offset,
+                escape_bytes(found)
+            ),
+            UncrxError::HeaderFieldTooLarge {
+                field,
+                length,
+                limit,
+            } => write!(
+                f,
+                "The {} field declares {} bytes, more than the limit of {}",
+                field, length, limit
+            ),
+            UncrxError::DownloadTooLarge(limit) => {
+                write!(f, "The download is larger than {} bytes", limit)
+            }
+            UncrxError::UnexpectedContentType(content_type) => {
+                write!(f, "The server sent {} instead of a CRX file", content_type)
+            }
+            UncrxError::Offline(url) => {
+                write!(f, "Downloading {} needs network access, which offline mode forbids", url)
+            }
+            UncrxError::InvalidExtensionId(id) => {
+                write!(f, "{} is not a valid extension id", id)
+            }
+        }
+    }
+}
+
+fn escape_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            b' '..=b'~' => (*byte as char).to_string(),
+            _ => format!("\\x{:02x}", byte),
+        })
+        .collect()
+}
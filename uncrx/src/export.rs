@@ -0,0 +1,256 @@
+use std::{fmt, fs, io::Write, path::Path, str::FromStr};
+
+use serde_json::Value;
+
+use super::{
+    corpus::get_sorted_dirs,
+    id::is_valid_extension_id,
+    manifest::{parse_manifest, MANIFEST_FILE_NAME},
+    stats::get_directory_size,
+};
+
+pub const CORPUS_RECORD_COLUMNS: [&str; 7] = [
+    "id",
+    "version",
+    "name",
+    "manifest_version",
+    "permissions",
+    "host_permissions",
+    "size",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    // Picked from the output file extension, CSV unless it names Parquet
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("parquet") => ExportFormat::Parquet,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("expected csv or parquet, got {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Parquet => write!(f, "parquet"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusRecord {
+    pub id: String,
+    pub version: String,
+    pub name: Option<String>,
+    pub manifest_version: Option<u64>,
+    pub permissions: Vec<String>,
+    pub host_permissions: Vec<String>,
+    pub size: u64,
+}
+
+impl CorpusRecord {
+    pub fn new(id: &str, version: &str, manifest: &Value, size: u64) -> Self {
+        let strings = |key: &str| -> Vec<String> {
+            manifest[key]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        };
+
+        CorpusRecord {
+            id: id.to_string(),
+            version: version.to_string(),
+            name: manifest["name"].as_str().map(str::to_string),
+            manifest_version: manifest["manifest_version"].as_u64(),
+            permissions: strings("permissions"),
+            host_permissions: strings("host_permissions"),
+            size,
+        }
+    }
+
+    // Lists are space separated, neither permissions nor match patterns contain spaces
+    fn to_row(&self) -> [String; 7] {
+        [
+            self.id.clone(),
+            self.version.clone(),
+            self.name.clone().unwrap_or_default(),
+            self.manifest_version
+                .map(|version| version.to_string())
+                .unwrap_or_default(),
+            self.permissions.join(" "),
+            self.host_permissions.join(" "),
+            self.size.to_string(),
+        ]
+    }
+}
+
+// One record per <ROOT>/<ID>/<VERSION> directory holding a readable manifest.json, as laid
+// out by merge_into_corpus
+pub fn get_corpus_records(root: &Path) -> anyhow::Result<Vec<CorpusRecord>> {
+    let mut records = vec![];
+
+    for id_dir in get_sorted_dirs(root)? {
+        let id = file_name(&id_dir);
+
+        if !is_valid_extension_id(&id) {
+            continue;
+        }
+
+        for version_dir in get_sorted_dirs(&id_dir)? {
+            let version = file_name(&version_dir);
+
+            // Staging directories of merges still in progress
+            if version.starts_with('.') {
+                continue;
+            }
+
+            let Ok(content) = fs::read_to_string(version_dir.join(MANIFEST_FILE_NAME)) else {
+                continue;
+            };
+
+            let Ok(manifest) = parse_manifest(&content) else {
+                continue;
+            };
+
+            let size = get_directory_size(&version_dir)?;
+
+            records.push(CorpusRecord::new(&id, &version, &manifest, size));
+        }
+    }
+
+    Ok(records)
+}
+
+pub fn write_csv(records: &[CorpusRecord], writer: impl Write) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+
+    writer.write_record(CORPUS_RECORD_COLUMNS)?;
+
+    for record in records {
+        writer.write_record(record.to_row())?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+pub fn write_parquet(records: &[CorpusRecord], writer: impl Write + Send) -> anyhow::Result<()> {
+    use std::sync::Arc;
+
+    use parquet::{
+        data_type::{ByteArray, ByteArrayType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+
+    let schema = parse_message_type(
+        "message corpus {
+            REQUIRED BYTE_ARRAY id (UTF8);
+            REQUIRED BYTE_ARRAY version (UTF8);
+            OPTIONAL BYTE_ARRAY name (UTF8);
+            OPTIONAL INT64 manifest_version;
+            REQUIRED BYTE_ARRAY permissions (UTF8);
+            REQUIRED BYTE_ARRAY host_permissions (UTF8);
+            REQUIRED INT64 size;
+        }",
+    )?;
+
+    let mut writer = SerializedFileWriter::new(
+        writer,
+        Arc::new(schema),
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+
+    let rows: Vec<[String; 7]> = records.iter().map(CorpusRecord::to_row).collect();
+    let mut row_group = writer.next_row_group()?;
+
+    for (index, column_name) in CORPUS_RECORD_COLUMNS.iter().enumerate() {
+        let mut column = row_group
+            .next_column()?
+            .ok_or_else(|| anyhow::anyhow!("The schema has no {} column", column_name))?;
+
+        match *column_name {
+            "name" => write_parquet_column::<ByteArrayType>(
+                &mut column,
+                records
+                    .iter()
+                    .map(|record| record.name.as_deref().map(ByteArray::from))
+                    .collect(),
+            )?,
+            "manifest_version" => write_parquet_column::<Int64Type>(
+                &mut column,
+                records
+                    .iter()
+                    .map(|record| record.manifest_version.map(|version| version as i64))
+                    .collect(),
+            )?,
+            "size" => write_parquet_column::<Int64Type>(
+                &mut column,
+                records
+                    .iter()
+                    .map(|record| Some(record.size as i64))
+                    .collect(),
+            )?,
+            _ => write_parquet_column::<ByteArrayType>(
+                &mut column,
+                rows.iter()
+                    .map(|row| Some(ByteArray::from(row[index].as_str())))
+                    .collect(),
+            )?,
+        }
+
+        column.close()?;
+    }
+
+    row_group.close()?;
+    writer.close()?;
+
+    Ok(())
+}
+
+// Definition levels are only written for optional columns, where they mark the missing values
+#[cfg(feature = "parquet")]
+fn write_parquet_column<T: parquet::data_type::DataType>(
+    column: &mut parquet::file::writer::SerializedColumnWriter,
+    values: Vec<Option<T::T>>,
+) -> anyhow::Result<()> {
+    let writer = column.typed::<T>();
+    let optional = writer.get_descriptor().max_def_level() > 0;
+
+    let levels: Vec<i16> = values.iter().map(|value| value.is_some() as i16).collect();
+    let values: Vec<T::T> = values.into_iter().flatten().collect();
+
+    writer.write_batch(&values, optional.then_some(&levels[..]), None)?;
+
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,65 @@
+use std::{fs, path::Path};
+
+use super::{
+    archive::{decrypt_zip, unpack_zip_with_options, UnpackOptions},
+    permissions::{apply_permissions, FileModes, Ownership},
+    staging::StagingDir,
+    types::CrxExtension,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractOptions {
+    pub unpack: UnpackOptions,
+    // Encrypted entries are refused without it
+    pub password: Option<String>,
+    pub file_modes: Option<FileModes>,
+    pub ownership: Option<Ownership>,
+}
+
+// Unpacks the zip payload of an extension into a directory that must not exist yet, or be
+// empty. Files are unpacked in a staging directory next to it first and moved into place
+// once complete, so a failure halfway leaves nothing behind. Returns the number of entries
+pub fn extract_to_dir(
+    extension: &CrxExtension,
+    directory: &Path,
+    options: &ExtractOptions,
+) -> anyhow::Result<usize> {
+    if fs::read_dir(directory).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(anyhow::anyhow!("{} is not empty", directory.display()));
+    }
+
+    let zip = match &options.password {
+        Some(password) => decrypt_zip(&extension.zip, password.as_bytes())?,
+        None => extension.zip.clone(),
+    };
+
+    // The staging directory is private to the owner, the files are unpacked in a directory
+    // created inside it with the usual mode, the one moved into place
+    let staging = StagingDir::create_in(get_parent(directory), ".uncrx-extract-")?;
+    let unpacked = staging.path().join("extension");
+    fs::create_dir(&unpacked)?;
+
+    let count = unpack_zip_with_options(&zip, &unpacked, &options.unpack)?;
+
+    apply_permissions(
+        &unpacked,
+        options.file_modes.as_ref(),
+        options.ownership.as_ref(),
+    )?;
+
+    if directory.exists() {
+        fs::remove_dir(directory)?;
+    }
+
+    fs::rename(&unpacked, directory)?;
+
+    Ok(count)
+}
+
+// On the same filesystem as the directory, so it can be renamed into place
+fn get_parent(directory: &Path) -> &Path {
+    match directory.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+
+use super::{
+    constants::{
+        CRX3_CRX_ID_FIELD, CRX3_PUBLIC_KEY_FIELD, CRX3_SHA256_WITH_ECDSA_FIELD,
+        CRX3_SHA256_WITH_RSA_FIELD, CRX3_SIGNATURE_FIELD, CRX3_SIGNED_HEADER_DATA_FIELD,
+        CRX_ID_LENGTH, CRX_MAGIC_VALUE, CRX_VERSION_RANGE, MAGIC_VALUE_RANGE,
+        PUBLIC_KEY_LENGTH_RANGE, SIGNATURE_LENGTH_RANGE,
+    },
+    errors::UncrxError,
+    limits::{check_header_lengths, HeaderLimits},
+    protobuf::{find_bytes_field, read_fields, FieldValue},
+    stream::read_crx_header_with_options,
+    types::{Crx3Header, Crx3Proof, CrxExtension, CrxField, CrxLayout, CrxRegion},
+    zip_records::{find_zip_end, END_OF_CENTRAL_DIRECTORY_SIGNATURE, LOCAL_FILE_HEADER_SIGNATURE},
+};
+
+// Everything the parsers are configured with, the defaults suit almost every CRX file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub header_limits: HeaderLimits,
+}
+
+pub fn get_crx_header(data: &[u8]) -> anyhow::Result<[u8; 4]> {
+    let slice = get_slice_from_range(data, MAGIC_VALUE_RANGE)?;
+
+    let mut magic = [0u8; 4];
+    magic.copy_from_slice(slice);
+
+    Ok(magic)
+}
+
+pub fn get_crx_version(data: &[u8]) -> anyhow::Result<u32> {
+    let slice = get_slice_from_range(data, CRX_VERSION_RANGE)?;
+
+    let mut version = [0u8; 4];
+    version.copy_from_slice(slice);
+
+    Ok(u32::from_le_bytes(version))
+}
+
+pub fn is_valid_crx(magic: &[u8; 4]) -> anyhow::Result<bool> {
+    Ok(magic == &CRX_MAGIC_VALUE)
+}
+
+pub fn get_public_key_length(data: &[u8]) -> anyhow::Result<u32> {
+    let slice = get_slice_from_range(data, PUBLIC_KEY_LENGTH_RANGE)?;
+
+    let mut public_key_length = [0u8; 4];
+    public_key_length.copy_from_slice(slice);
+
+    Ok(u32::from_le_bytes(public_key_length))
+}
+
+pub fn get_signature_key_length(data: &[u8]) -> anyhow::Result<u32> {
+    let slice = get_slice_from_range(data, SIGNATURE_LENGTH_RANGE)?;
+
+    let mut signature_length = [0u8; 4];
+    signature_length.copy_from_slice(slice);
+
+    Ok(u32::from_le_bytes(signature_length))
+}
+
+pub fn get_slice_from_range(data: &[u8], range: Range<usize>) -> anyhow::Result<&[u8]> {
+    if data.len() < range.end {
+        return Err(UncrxError::Truncated {
+            needed: range.end,
+            found: data.len(),
+        }
+        .into());
+    }
+
+    Ok(&data[range])
+}
+
+pub fn get_sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn is_sha256_match(data: &[u8], expected: &str) -> bool {
+    get_sha256(data).eq_ignore_ascii_case(expected.trim())
+}
+
+// Files saved from Windows or old download managers are often named .CRX
+pub fn is_crx_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("crx"))
+}
+
+// Length of everything before the zip payload, as declared by the fixed size fields
+pub fn get_header_length(data: &[u8]) -> anyhow::Result<usize> {
+    get_header_length_with_options(data, &ParseOptions::default())
+}
+
+pub fn get_header_length_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> anyhow::Result<usize> {
+    let header = get_crx_header(data)?;
+
+    if !is_valid_crx(&header)? {
+        return Err(UncrxError::InvalidMagic(header).into());
+    }
+
+    let version = get_crx_version(data)?;
+
+    if version != 2 && version != 3 {
+        return Err(UncrxError::UnsupportedVersion(version).into());
+    }
+
+    let public_key_length = get_public_key_length(data)?;
+
+    let (header, signature_key_length) = if version <= 2 {
+        (16, get_signature_key_length(data)?)
+    } else {
+        (12, 0)
+    };
+
+    check_header_lengths(
+        version,
+        public_key_length,
+        signature_key_length,
+        &options.header_limits,
+    )?;
+
+    Ok(header + signature_key_length as usize + public_key_length as usize)
+}
+
+pub fn get_zip_start_offset(data: &[u8]) -> anyhow::Result<usize> {
+    get_zip_start_offset_with_options(data, &ParseOptions::default())
+}
+
+pub fn get_zip_start_offset_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> anyhow::Result<usize> {
+    let zip_start_offset = get_header_length_with_options(data, options)?;
+
+    let signature = get_slice_from_range(data, zip_start_offset..zip_start_offset + 4)?;
+
+    if signature != LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes()
+        && signature != END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes()
+    {
+        return Err(UncrxError::MissingZip {
+            offset: zip_start_offset,
+            found: signature.to_vec(),
+        }
+        .into());
+    }
+
+    Ok(zip_start_offset)
+}
+
+pub fn get_zip_payload(data: &[u8]) -> anyhow::Result<&[u8]> {
+    get_zip_payload_with_options(data, &ParseOptions::default())
+}
+
+pub fn get_zip_payload_with_options<'a>(
+    data: &'a [u8],
+    options: &ParseOptions,
+) -> anyhow::Result<&'a [u8]> {
+    let zip_start_offset = get_zip_start_offset_with_options(data, options)?;
+
+    Ok(&data[zip_start_offset..])
+}
+
+// Length of the CRX file starting at byte 0, ignoring any data that follows it
+pub fn get_crx_length(data: &[u8]) -> anyhow::Result<usize> {
+    get_crx_length_with_options(data, &ParseOptions::default())
+}
+
+pub fn get_crx_length_with_options(data: &[u8], options: &ParseOptions) -> anyhow::Result<usize> {
+    let zip_start_offset = get_zip_start_offset_with_options(data, options)?;
+
+    Ok(zip_start_offset + find_zip_end(&data[zip_start_offset..])?)
+}
+
+pub fn get_crx_layout(data: &[u8]) -> anyhow::Result<CrxLayout> {
+    get_layout(data, get_zip_start_offset(data)?..data.len())
+}
+
+// Only the fixed size fields are read from the data, which may end before the zip payload
+fn get_layout(data: &[u8], zip: Range<usize>) -> anyhow::Result<CrxLayout> {
+    let version = get_crx_version(data)?;
+    let length = get_public_key_length(data)? as usize;
+
+    let mut regions = vec![
+        (CrxField::Magic, MAGIC_VALUE_RANGE),
+        (CrxField::Version, CRX_VERSION_RANGE),
+    ];
+
+    if version <= 2 {
+        let signature_length = get_signature_key_length(data)? as usize;
+        let signature_start = SIGNATURE_LENGTH_RANGE.end + length;
+
+        regions.extend([
+            (CrxField::PublicKeyLength, PUBLIC_KEY_LENGTH_RANGE),
+            (CrxField::SignatureLength, SIGNATURE_LENGTH_RANGE),
+            (
+                CrxField::PublicKey,
+                SIGNATURE_LENGTH_RANGE.end..signature_start,
+            ),
+            (
+                CrxField::Signature,
+                signature_start..signature_start + signature_length,
+            ),
+        ]);
+    } else {
+        // CRX3 stores the protobuf header size where CRX2 stores the public key length
+        regions.extend([
+            (CrxField::HeaderSize, PUBLIC_KEY_LENGTH_RANGE),
+            (
+                CrxField::Header,
+                PUBLIC_KEY_LENGTH_RANGE.end..PUBLIC_KEY_LENGTH_RANGE.end + length,
+            ),
+        ]);
+    }
+
+    regions.push((CrxField::Zip, zip));
+
+    let regions = regions
+        .into_iter()
+        .map(|(field, range)| CrxRegion { field, range })
+        .collect();
+
+    Ok(CrxLayout { regions })
+}
+
+// Decodes the protobuf stored between the fixed size fields and the zip payload of a CRX3
+// file. Unknown fields are skipped, as Chrome does
+pub fn parse_crx3_header(header: &[u8]) -> anyhow::Result<Crx3Header> {
+    let mut parsed = Crx3Header::default();
+
+    for field in read_fields(header)? {
+        let FieldValue::Bytes(bytes) = field.value else {
+            continue;
+        };
+
+        match field.number {
+            CRX3_SHA256_WITH_RSA_FIELD => parsed.sha256_with_rsa.push(parse_crx3_proof(bytes)?),
+            CRX3_SHA256_WITH_ECDSA_FIELD => parsed.sha256_with_ecdsa.push(parse_crx3_proof(bytes)?),
+            CRX3_SIGNED_HEADER_DATA_FIELD => {
+                parsed.crx_id = find_bytes_field(&read_fields(bytes)?, CRX3_CRX_ID_FIELD)
+                    .map(|crx_id| crx_id.to_vec());
+                parsed.signed_header_data = bytes.to_vec();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_crx3_proof(proof: &[u8]) -> anyhow::Result<Crx3Proof> {
+    let fields = read_fields(proof)?;
+
+    Ok(Crx3Proof {
+        public_key: find_bytes_field(&fields, CRX3_PUBLIC_KEY_FIELD)
+            .unwrap_or_default()
+            .to_vec(),
+        signature: find_bytes_field(&fields, CRX3_SIGNATURE_FIELD)
+            .unwrap_or_default()
+            .to_vec(),
+    })
+}
+
+// The RSA proof made with the key the extension id derives from, or the first one when the
+// header declares no crx_id
+fn get_developer_proof(header: &Crx3Header) -> Option<&Crx3Proof> {
+    let crx_id = header.crx_id.as_deref();
+
+    header
+        .sha256_with_rsa
+        .iter()
+        .find(|proof| Some(&Sha256::digest(&proof.public_key)[..CRX_ID_LENGTH]) == crx_id)
+        .or_else(|| header.sha256_with_rsa.first())
+}
+
+pub fn parse_crx(data: &[u8]) -> anyhow::Result<CrxExtension> {
+    parse_crx_with_options(data, &ParseOptions::default())
+}
+
+pub fn parse_crx_with_options(data: &[u8], options: &ParseOptions) -> anyhow::Result<CrxExtension> {
+    let zip = get_zip_payload_with_options(data, options)?.to_vec();
+
+    get_extension(&data[..data.len() - zip.len()], zip)
+}
+
+// Reads the header a field at a time and the zip payload straight into the extension, so
+// the file is never held in memory next to its payload
+pub fn parse_crx_reader<R: Read>(reader: R) -> anyhow::Result<CrxExtension> {
+    parse_crx_reader_with_options(reader, &ParseOptions::default())
+}
+
+pub fn parse_crx_reader_with_options<R: Read>(
+    reader: R,
+    options: &ParseOptions,
+) -> anyhow::Result<CrxExtension> {
+    let (header, mut payload) = read_crx_header_with_options(reader, options)?;
+
+    let mut zip = vec![];
+    payload.read_to_end(&mut zip)?;
+
+    get_extension(&header, zip)
+}
+
+// Like parse_crx_reader, starting from the current position of the reader. The payload
+// length is known from the end of the stream, the buffer is allocated once to that size
+pub fn parse_crx_seekable<R: Read + Seek>(reader: R) -> anyhow::Result<CrxExtension> {
+    parse_crx_seekable_with_options(reader, &ParseOptions::default())
+}
+
+pub fn parse_crx_seekable_with_options<R: Read + Seek>(
+    mut reader: R,
+    options: &ParseOptions,
+) -> anyhow::Result<CrxExtension> {
+    let start = reader.stream_position()?;
+    let (header, _) = read_crx_header_with_options(&mut reader, options)?;
+
+    let zip_start = start + header.len() as u64;
+    let zip_length = reader.seek(SeekFrom::End(0))?.saturating_sub(zip_start);
+    reader.seek(SeekFrom::Start(zip_start))?;
+
+    let mut zip = Vec::with_capacity(zip_length as usize);
+    reader.take(zip_length).read_to_end(&mut zip)?;
+
+    get_extension(&header, zip)
+}
+
+// Everything but the payload comes from the header, all the bytes before the zip
+fn get_extension(data: &[u8], zip: Vec<u8>) -> anyhow::Result<CrxExtension> {
+    let version = get_crx_version(data)?;
+
+    let length = get_public_key_length(data)? as usize;
+
+    let (public_key, signature, header) = if version <= 2 {
+        let signature_start = SIGNATURE_LENGTH_RANGE.end + length;
+        let signature_length = get_signature_key_length(data)? as usize;
+
+        let public_key = get_slice_from_range(data, SIGNATURE_LENGTH_RANGE.end..signature_start)?;
+        let signature =
+            get_slice_from_range(data, signature_start..signature_start + signature_length)?;
+
+        (
+            public_key.to_vec(),
+            Some(signature.to_vec()).filter(|signature| !signature.is_empty()),
+            None,
+        )
+    } else {
+        let header = get_slice_from_range(
+            data,
+            PUBLIC_KEY_LENGTH_RANGE.end..PUBLIC_KEY_LENGTH_RANGE.end + length,
+        )?;
+        let header = parse_crx3_header(header)?;
+        let proof = get_developer_proof(&header);
+
+        (
+            proof
+                .map(|proof| proof.public_key.clone())
+                .unwrap_or_default(),
+            proof.map(|proof| proof.signature.clone()),
+            Some(header),
+        )
+    };
+
+    let layout = get_layout(data, data.len()..data.len() + zip.len())?;
+
+    let extension = CrxExtension {
+        version,
+        public_key,
+        signature,
+        zip,
+        header,
+        layout,
+    };
+
+    Ok(extension)
+}
+
+// Installers sometimes concatenate several CRX files, each zip's end record marks where
+// the next one starts
+pub fn parse_all_crx(data: &[u8]) -> anyhow::Result<Vec<CrxExtension>> {
+    let mut extensions = vec![];
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let crx = &data[offset..];
+
+        let length = get_crx_length(crx)
+            .map_err(|error| anyhow::anyhow!("Invalid CRX file at byte {}: {}", offset, error))?;
+
+        extensions.push(parse_crx(&crx[..length])?);
+        offset += length;
+    }
+
+    Ok(extensions)
+}
@@ -0,0 +1,69 @@
+use sha2::{Digest, Sha256};
+
+use super::{
+    constants::CRX_ID_LENGTH,
+    helpers::{get_crx_version, get_public_key_length, get_slice_from_range, parse_crx3_header},
+    types::Crx3Header,
+};
+
+pub fn encode_extension_id(crx_id: &[u8]) -> String {
+    // Chrome maps each nibble to a letter between 'a' and 'p'
+    crx_id
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .map(|nibble| (b'a' + nibble) as char)
+        .collect()
+}
+
+pub fn is_valid_extension_id(id: &str) -> bool {
+    id.len() == CRX_ID_LENGTH * 2 && id.bytes().all(|c| (b'a'..=b'p').contains(&c))
+}
+
+pub fn get_extension_id_from_public_key(public_key: &[u8]) -> String {
+    encode_extension_id(&Sha256::digest(public_key)[..CRX_ID_LENGTH])
+}
+
+pub fn get_extension_id(data: &[u8]) -> anyhow::Result<String> {
+    let version = get_crx_version(data)?;
+    let length = get_public_key_length(data)? as usize;
+
+    if version <= 2 {
+        let public_key = get_slice_from_range(data, 16..16 + length)?;
+        return get_extension_id_from_parts(public_key, None);
+    }
+
+    let header = parse_crx3_header(get_slice_from_range(data, 12..12 + length)?)?;
+
+    get_extension_id_from_parts(&[], Some(&header))
+}
+
+// CRX3 files are known by the crx_id their header declares, and headers without signed data
+// by their first RSA proof, like Chrome does. CRX2 files are known by their public key
+pub fn get_extension_id_from_parts(
+    public_key: &[u8],
+    header: Option<&Crx3Header>,
+) -> anyhow::Result<String> {
+    let Some(header) = header else {
+        if public_key.is_empty() {
+            return Err(anyhow::anyhow!("CRX2 file has no public key"));
+        }
+
+        return Ok(get_extension_id_from_public_key(public_key));
+    };
+
+    if let Some(crx_id) = header
+        .crx_id
+        .as_deref()
+        .filter(|crx_id| crx_id.len() == CRX_ID_LENGTH)
+    {
+        return Ok(encode_extension_id(crx_id));
+    }
+
+    let proof = header
+        .sha256_with_rsa
+        .first()
+        .filter(|proof| !proof.public_key.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("CRX3 header has no crx_id nor public key"))?;
+
+    Ok(get_extension_id_from_public_key(&proof.public_key))
+}
@@ -0,0 +1,2428 @@
+pub mod analysis;
+pub mod archive;
+pub mod batch;
+pub mod build_info;
+pub mod cache;
+pub mod carve;
+pub mod compat;
+pub mod constants;
+pub mod corpus;
+pub mod csp;
+pub mod download;
+pub mod errors;
+pub mod export;
+pub mod extract;
+pub mod hash;
+pub mod helpers;
+pub mod id;
+pub mod licenses;
+pub mod limits;
+pub mod managed_schema;
+pub mod manifest;
+pub mod opened;
+pub mod output;
+pub mod pack;
+pub mod permissions;
+pub mod policy;
+pub mod polyglot;
+pub mod protobuf;
+pub mod provenance;
+#[cfg(feature = "smoke-test")]
+pub mod smoke_test;
+pub mod staging;
+pub mod stats;
+pub mod stream;
+pub mod swhid;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod types;
+pub mod update;
+pub mod validation;
+pub mod verify;
+pub mod webdriver;
+pub mod webstore;
+pub mod wrapper;
+pub mod zip_records;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        archive::{
+            check_zip_consistency, decrypt_zip, find_nested_crx_files, get_archive_comment,
+            get_encrypted_entries, get_entries, read_entry_to_string, recover_zip,
+            strip_zip_metadata, unpack_zip, unpack_zip_with_options, UnpackOptions,
+        },
+        batch::{run_batch, BatchJob, BatchOptions, BatchState, Extraction},
+        build_info::{format_unix_time, get_build_info, BuildInfo},
+        cache::{ExtractionCache, HttpCache},
+        carve::find_crx_files,
+        compat::{find_api_references, get_api_minimum_version},
+        constants::{
+            CRX3_PUBLIC_KEY_FIELD, CRX3_SHA256_WITH_ECDSA_FIELD, CRX3_SIGNATURE_FIELD,
+            EC_P256_PUBLIC_KEY_PREFIX,
+        },
+        corpus::{find_installed_extensions, import_into_corpus, merge_into_corpus, CorpusMerge},
+        csp::{evaluate_csp, get_content_security_policy},
+        download::{download, is_accepted_content_type, is_url, DownloadOptions},
+        errors::UncrxError,
+        export::{get_corpus_records, write_csv, ExportFormat},
+        extract::{extract_to_dir, ExtractOptions},
+        hash::HashAlgorithm,
+        helpers::{
+            get_crx_layout, get_sha256, is_crx_path, is_sha256_match, parse_all_crx, parse_crx,
+            parse_crx3_header, parse_crx_reader, parse_crx_seekable, parse_crx_with_options,
+            ParseOptions,
+        },
+        id::{encode_extension_id, get_extension_id, get_extension_id_from_public_key},
+        licenses::{find_licenses, summarize_licenses, LicenseSource},
+        limits::{HeaderLimits, DEFAULT_MAX_SIGNATURE_LENGTH},
+        managed_schema::get_policy_options,
+        manifest::{bump_version, get_manifest, read_manifest, set_manifest_version, VersionBump},
+        opened::OpenedCrx,
+        output::{MemoryFormat, OutputFormat, OutputFormats},
+        pack::{
+            build_crx3, pack_entries, verify_reproducible, zip_directory, PackOptions,
+            CRX3_SIGNATURE_CONTEXT,
+        },
+        permissions::{set_file_modes, FileModes, Ownership},
+        policy::{generate_force_install_policy, ForceInstallEntry, PolicyFormat},
+        polyglot::{find_polyglot_formats, PolyglotFormat},
+        protobuf::write_bytes_field,
+        provenance::{
+            find_tree_changes, get_entry_hashes, read_extraction_manifest,
+            write_extraction_manifest, ExtractionManifest, TreeChangeKind,
+        },
+        staging::{run_scanners, StagingDir},
+        stats::{collect_corpus_stats, get_signature_algorithms, StatsCollector},
+        stream::{copy_zip_payload, read_zip_listing},
+        swhid::{get_content_swhid, get_tree_swhids},
+        test_support::{build_crx2, build_fixture, build_zip, generate_test_key, FixtureQuirk},
+        types::CrxField,
+        update::{
+            answer_update_checks, generate_update_manifest, get_update_manifest_entry,
+            keep_latest_versions, parse_update_request, scan_update_directory, HostedExtension,
+            UpdateCheck,
+        },
+        validation::validate_manifest,
+        verify::{
+            get_verification_result, is_webstore_publisher_key, verify_crx, verify_proofs,
+            ProofAlgorithm, ProofStatus, VerificationResult,
+        },
+        webdriver::{test_install, InstallResult},
+        webstore::download_url,
+        wrapper::{get_crx_wrapper, get_path_wrapper, unwrap_crx, CrxWrapper},
+    };
+    use flate2::{write::GzEncoder, Compression};
+    use lzma_rust2::{XzOptions, XzWriter};
+    use proptest::prelude::*;
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+    };
+    use rsa::{pkcs8::EncodePublicKey, RsaPrivateKey};
+    use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+    use std::{
+        borrow::Cow,
+        env, fs,
+        io::{Cursor, Read, Write},
+        net::TcpListener,
+        path::{Path, PathBuf},
+        sync::OnceLock,
+        thread,
+        time::{Duration, Instant},
+    };
+    use zip::{write::FullFileOptions, CompressionMethod, ZipWriter};
+
+    fn create_directory_if_not_exists(dir_path: &PathBuf) {
+        if fs::metadata(dir_path).is_err() {
+            fs::create_dir_all(dir_path).expect("Failed to create directory");
+        }
+    }
+
+    fn get_request_length(request: &[u8]) -> usize {
+        let request = String::from_utf8_lossy(request);
+        let headers = request.split("\r\n\r\n").next().unwrap_or_default();
+
+        let content_length = headers
+            .lines()
+            .find_map(|line| {
+                line.to_lowercase()
+                    .strip_prefix("content-length:")
+                    .map(str::trim)
+                    .and_then(|value| value.parse().ok())
+            })
+            .unwrap_or(0);
+
+        headers.len() + 4 + content_length
+    }
+
+    #[test]
+    fn it_works() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let file_path = current_dir.join("src/mock/test-extension.crx");
+        let data = fs::read(file_path.to_str().unwrap()).expect("Failed to read file");
+
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+
+        let output_dir = current_dir.join("out");
+        create_directory_if_not_exists(&output_dir);
+
+        let output_file = current_dir.join("out/extension.zip");
+        fs::write(output_file, &extension.zip).expect("Failed to write file");
+    }
+
+    #[test]
+    fn reads_manifest_from_zip() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+        let manifest = get_manifest(&extension.zip).expect("Failed to read manifest");
+
+        assert_eq!(manifest["name"], "Test Extension");
+        assert_eq!(manifest["manifest_version"], 3);
+        assert_eq!(
+            read_manifest(&data).expect("Failed to read manifest"),
+            manifest
+        );
+    }
+
+    #[test]
+    fn copies_zip_payload_from_reader() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let mut zip = vec![];
+        let header = copy_zip_payload(Cursor::new(&data), &mut zip).expect("Failed to copy zip");
+
+        assert_eq!(zip, parse_crx(&data).expect("Failed to parse crx").zip);
+        assert_eq!(header, data[..data.len() - zip.len()]);
+        assert_eq!(
+            get_extension_id(&header).expect("Failed to get extension id"),
+            "hiebjbihmknnnjiaofclmmecnhigoooc"
+        );
+
+        // Declares a 512 KiB header, which is never allocated
+        let mut output = vec![];
+        let error = copy_zip_payload(&b"Cr24\x03\x00\x00\x00\x00\x00\x08\x00"[..], &mut output)
+            .expect_err("The header is truncated");
+        assert!(matches!(
+            error.downcast_ref::<UncrxError>(),
+            Some(UncrxError::Truncated { found: 12, .. })
+        ));
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn caps_declared_header_lengths() {
+        let crx2 = b"Cr24\x02\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x10";
+
+        let error = parse_crx(crx2).expect_err("The signature is too long");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::HeaderFieldTooLarge {
+                field: CrxField::SignatureLength,
+                length: 0x10000000,
+                limit: DEFAULT_MAX_SIGNATURE_LENGTH
+            })
+        );
+
+        let crx3 = b"Cr24\x03\x00\x00\x00\xff\xff\xff\x7f";
+        let error = copy_zip_payload(&crx3[..], vec![]).expect_err("The header is too large");
+        assert!(matches!(
+            error.downcast_ref::<UncrxError>(),
+            Some(UncrxError::HeaderFieldTooLarge {
+                field: CrxField::HeaderSize,
+                ..
+            })
+        ));
+
+        let options = ParseOptions {
+            header_limits: HeaderLimits {
+                max_signature_length: u32::MAX,
+                ..HeaderLimits::default()
+            },
+        };
+        let error = parse_crx_with_options(crx2, &options).expect_err("The signature is truncated");
+
+        assert!(matches!(
+            error.downcast_ref::<UncrxError>(),
+            Some(UncrxError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn lists_entries_from_end_of_reader() {
+        let key = &get_test_keys()[0];
+        let crx = build_fixture(
+            3,
+            &[FixtureQuirk::Entries(3), FixtureQuirk::TrailingGarbage(100)],
+            key,
+        )
+        .expect("Failed to build fixture");
+
+        let listing = read_zip_listing(Cursor::new(&crx)).expect("Failed to list entries");
+        let crx = OpenedCrx::open(&crx).expect("Failed to open crx");
+
+        assert_eq!(
+            listing.entries,
+            crx.entries().expect("Failed to read zip entries")
+        );
+        assert_eq!(listing.entries.len(), 4);
+        assert_eq!(listing.comment, crx.comment());
+
+        // Drops the last bytes of the central directory, keeping its end record
+        let mut truncated = crx.data().to_vec();
+        truncated.truncate(truncated.len() - 100 - 22 - 10);
+        truncated.extend_from_slice(&crx.data()[crx.data().len() - 122..]);
+        assert!(read_zip_listing(Cursor::new(&truncated)).is_err());
+    }
+
+    #[test]
+    fn verifies_proofs_from_reader() {
+        let key = &get_test_keys()[0];
+        let zip = build_zip(
+            &[("manifest.json".to_string(), b"{}".to_vec())],
+            CompressionMethod::Stored,
+        )
+        .expect("Failed to build zip");
+        let crx = build_crx3(&zip, key).expect("Failed to build crx");
+
+        let proofs = verify_proofs(Cursor::new(&crx)).expect("Failed to verify proofs");
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].algorithm, ProofAlgorithm::Sha256WithRsa);
+        assert_eq!(proofs[0].status, ProofStatus::Valid);
+        assert_eq!(
+            proofs[0].public_key,
+            key.to_public_key()
+                .to_public_key_der()
+                .expect("Failed to encode key")
+                .into_vec()
+        );
+
+        // Changes the stored manifest, the header is left untouched
+        let mut tampered = crx.clone();
+        let position = zip
+            .windows(2)
+            .position(|window| window == b"{}")
+            .expect("The manifest is stored");
+        let offset = crx.len() - zip.len() + position;
+        tampered[offset] = b'[';
+
+        let proofs = verify_proofs(Cursor::new(&tampered)).expect("Failed to verify proofs");
+        assert_eq!(proofs[0].status, ProofStatus::Invalid);
+
+        let (result, proofs) = verify_crx(Cursor::new(&crx)).expect("Failed to verify crx");
+        assert_eq!(result, VerificationResult::Valid);
+        assert!(!is_webstore_publisher_key(&proofs[0].public_key));
+        assert_eq!(
+            verify_crx(Cursor::new(&tampered))
+                .expect("Failed to verify crx")
+                .0,
+            VerificationResult::InvalidSignature
+        );
+        assert_eq!(
+            get_verification_result(Some(&[0; 16]), &proofs),
+            VerificationResult::MissingDeveloperProof
+        );
+
+        // A P-256 proof appended to the header, signing the same message as the RSA one
+        let header_length = u32::from_le_bytes(crx[8..12].try_into().expect("Short header"));
+        let header = &crx[12..12 + header_length as usize];
+        let signed_data = parse_crx3_header(header)
+            .expect("Failed to parse header")
+            .signed_header_data;
+
+        let mut message = CRX3_SIGNATURE_CONTEXT.to_vec();
+        message.extend((signed_data.len() as u32).to_le_bytes());
+        message.extend(&signed_data);
+        message.extend(&zip);
+
+        let random = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &random)
+            .expect("Failed to generate key");
+        let ecdsa_key =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &random)
+                .expect("Failed to read key");
+        let signature = ecdsa_key.sign(&random, &message).expect("Failed to sign");
+
+        let mut public_key = EC_P256_PUBLIC_KEY_PREFIX.to_vec();
+        public_key.extend(ecdsa_key.public_key().as_ref());
+
+        let mut proof = vec![];
+        write_bytes_field(&mut proof, CRX3_PUBLIC_KEY_FIELD, &public_key);
+        write_bytes_field(&mut proof, CRX3_SIGNATURE_FIELD, signature.as_ref());
+
+        let build_with_proof = |proof: &[u8]| {
+            let mut header = header.to_vec();
+            write_bytes_field(&mut header, CRX3_SHA256_WITH_ECDSA_FIELD, proof);
+
+            let mut ecdsa_crx = crx[..8].to_vec();
+            ecdsa_crx.extend((header.len() as u32).to_le_bytes());
+            ecdsa_crx.extend(header);
+            ecdsa_crx.extend(&zip);
+            ecdsa_crx
+        };
+
+        let (result, proofs) =
+            verify_crx(Cursor::new(build_with_proof(&proof))).expect("Failed to verify crx");
+        assert_eq!(result, VerificationResult::Valid);
+        assert_eq!(proofs[1].algorithm, ProofAlgorithm::Sha256WithEcdsa);
+        assert_eq!(proofs[1].status, ProofStatus::Valid);
+
+        let last = proof.len() - 1;
+        proof[last] ^= 1;
+        let (result, _) =
+            verify_crx(Cursor::new(build_with_proof(&proof))).expect("Failed to verify crx");
+        assert_eq!(result, VerificationResult::InvalidSignature);
+
+        let crx2 = build_crx2(&zip, key).expect("Failed to build crx");
+        assert!(verify_proofs(Cursor::new(&crx2)).is_err());
+    }
+
+    #[test]
+    fn parses_crx3_header() {
+        let key = &get_test_keys()[0];
+        let public_key = key
+            .to_public_key()
+            .to_public_key_der()
+            .expect("Failed to encode key")
+            .into_vec();
+        let zip = build_zip(
+            &[("manifest.json".to_string(), b"{}".to_vec())],
+            CompressionMethod::Stored,
+        )
+        .expect("Failed to build zip");
+
+        let crx = build_crx3(&zip, key).expect("Failed to build crx");
+        let extension = parse_crx(&crx).expect("Failed to parse crx");
+        let header = extension.header.as_ref().expect("CRX3 files have a header");
+
+        assert_eq!(header.sha256_with_rsa.len(), 1);
+        assert!(header.sha256_with_ecdsa.is_empty());
+        assert_eq!(header.sha256_with_rsa[0].public_key, public_key);
+        assert_eq!(
+            header.crx_id.as_deref().map(encode_extension_id),
+            Some(get_extension_id_from_public_key(&public_key))
+        );
+        assert_eq!(extension.public_key, public_key);
+        assert_eq!(
+            extension.signature.as_ref(),
+            Some(&header.sha256_with_rsa[0].signature)
+        );
+
+        assert_eq!(
+            extension
+                .extension_id()
+                .expect("Failed to get extension id"),
+            get_extension_id(&crx).expect("Failed to get extension id")
+        );
+
+        // The declared crx_id wins over the proof key, whichever way the id is computed
+        let mut extension = extension;
+        if let Some(header) = extension.header.as_mut() {
+            header.crx_id = Some(vec![0; 16]);
+        }
+        assert_eq!(
+            extension
+                .extension_id()
+                .expect("Failed to get extension id"),
+            "a".repeat(32)
+        );
+
+        // CRX2 keeps the signature right after the public key
+        let crx2 = build_crx2(&zip, key).expect("Failed to build crx");
+        let mut extension = parse_crx(&crx2).expect("Failed to parse crx");
+        assert!(extension.header.is_none());
+        assert_eq!(
+            extension
+                .extension_id()
+                .expect("Failed to get extension id"),
+            get_extension_id_from_public_key(&public_key)
+        );
+        assert_eq!(extension.public_key, public_key);
+        assert_eq!(
+            extension.signature.as_deref(),
+            Some(&crx2[16 + public_key.len()..crx2.len() - zip.len()])
+        );
+
+        extension.public_key.clear();
+        assert!(extension.extension_id().is_err());
+    }
+
+    #[test]
+    fn parses_crx_from_readers() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let key = &get_test_keys()[0];
+        let crx2 = build_crx2(&parse_crx(&data).expect("Failed to parse crx").zip, key)
+            .expect("Failed to build crx");
+
+        for crx in [data, crx2] {
+            let expected = parse_crx(&crx).expect("Failed to parse crx");
+
+            // The seekable variant starts wherever the reader is, e.g. inside a bigger file
+            let mut prefixed = b"prefix".to_vec();
+            prefixed.extend(&crx);
+            let mut reader = Cursor::new(prefixed);
+            reader.set_position(6);
+
+            for extension in [
+                parse_crx_reader(crx.as_slice()).expect("Failed to parse crx"),
+                parse_crx_seekable(reader).expect("Failed to parse crx"),
+            ] {
+                assert_eq!(extension.version, expected.version);
+                assert_eq!(extension.public_key, expected.public_key);
+                assert_eq!(extension.signature, expected.signature);
+                assert_eq!(extension.zip, expected.zip);
+                assert_eq!(extension.layout, expected.layout);
+                assert_eq!(
+                    extension
+                        .extension_id()
+                        .expect("Failed to get extension id"),
+                    expected.extension_id().expect("Failed to get extension id")
+                );
+            }
+        }
+
+        assert!(parse_crx_reader(&b"Cr24"[..]).is_err());
+    }
+
+    #[test]
+    fn unwraps_compressed_crx() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&data).expect("Failed to compress crx");
+        let gzip = encoder.finish().expect("Failed to compress crx");
+
+        assert_eq!(get_crx_wrapper(&gzip), Some(CrxWrapper::Gzip));
+        assert_eq!(
+            unwrap_crx(&gzip).expect("Failed to unwrap crx").as_ref(),
+            &data[..]
+        );
+        assert!(matches!(
+            unwrap_crx(&data).expect("Failed to unwrap crx"),
+            Cow::Borrowed(_)
+        ));
+
+        assert_eq!(
+            get_path_wrapper(Path::new("extension.CRX.zst")),
+            Some(CrxWrapper::Zstd)
+        );
+        assert_eq!(get_path_wrapper(Path::new("extension.tar.gz")), None);
+        assert!(unwrap_crx(b"\xfd7zXZ\x00").is_err());
+
+        let mut writer = XzWriter::new(vec![], XzOptions::default()).expect("Failed to start xz");
+        writer.write_all(&data).expect("Failed to compress crx");
+        let xz = writer.finish().expect("Failed to compress crx");
+        assert_eq!(get_crx_wrapper(&xz), Some(CrxWrapper::Xz));
+        assert_eq!(
+            unwrap_crx(&xz).expect("Failed to unwrap crx").as_ref(),
+            &data[..]
+        );
+
+        // Two frames, split like pzstd does
+        let (first, second) = data.split_at(data.len() / 2);
+        let mut zstd = compress_to_vec(first, CompressionLevel::Fastest);
+        zstd.extend(compress_to_vec(second, CompressionLevel::Fastest));
+        assert_eq!(get_crx_wrapper(&zstd), Some(CrxWrapper::Zstd));
+        assert_eq!(
+            unwrap_crx(&zstd).expect("Failed to unwrap crx").as_ref(),
+            &data[..]
+        );
+    }
+
+    #[test]
+    fn reads_everything_through_one_opened_crx() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let crx = OpenedCrx::open(&data).expect("Failed to open crx");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        assert_eq!(crx.zip(), zip);
+        assert_eq!(crx.crx_version().expect("Failed to read version"), 3);
+        assert_eq!(
+            crx.entries().expect("Failed to read entries"),
+            get_entries(&zip).expect("Failed to read entries")
+        );
+        assert_eq!(
+            crx.manifest().expect("Failed to read manifest"),
+            get_manifest(&zip).expect("Failed to read manifest")
+        );
+        assert!(crx
+            .read_entry("js/script.js")
+            .expect("Failed to read entry")
+            .starts_with(b"console.log"));
+        assert_eq!(crx.build_info().expect("Failed to read build info"), None);
+        assert_eq!(crx.comment(), "");
+    }
+
+    #[test]
+    fn evaluates_content_security_policy() {
+        let manifest = serde_json::json!({
+            "manifest_version": 3,
+            "content_security_policy": {
+                "extension_pages": "script-src 'self' 'unsafe-eval' https://cdn.example.com; object-src 'self'"
+            }
+        });
+
+        let policy = get_content_security_policy(&manifest)
+            .expect("Failed to read policy")
+            .expect("Policy is missing");
+        let violations = evaluate_csp(&policy, 3);
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].source.as_deref(), Some("'unsafe-eval'"));
+        assert_eq!(
+            violations[1].source.as_deref(),
+            Some("https://cdn.example.com")
+        );
+
+        let legacy = serde_json::json!({
+            "manifest_version": 2,
+            "content_security_policy": "script-src 'self' 'unsafe-eval' https://cdn.example.com; object-src 'self'"
+        });
+        let policy = get_content_security_policy(&legacy)
+            .expect("Failed to read policy")
+            .expect("Policy is missing");
+
+        assert!(evaluate_csp(&policy, 2).is_empty());
+    }
+
+    #[test]
+    fn validates_manifest_with_error_paths() {
+        let manifest = serde_json::json!({
+            "manifest_version": 3,
+            "name": "Broken",
+            "version": "1.02",
+            "permissions": ["storage", "https://*/*", 42],
+            "background": { "scripts": ["background.js"] },
+            "browser_action": {}
+        });
+
+        let paths: Vec<String> = validate_manifest(&manifest)
+            .into_iter()
+            .map(|error| error.path)
+            .collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                "/permissions/2",
+                "/browser_action",
+                "/version",
+                "/background/scripts",
+                "/permissions/1",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_chrome_api_references() {
+        let source = "chrome.sidePanel.open({}); mychrome.foo(); chrome.storage.session.get('a');";
+
+        let references = find_api_references(source);
+
+        assert_eq!(
+            references,
+            vec!["chrome.sidePanel.open", "chrome.storage.session.get"]
+        );
+        assert_eq!(get_api_minimum_version(&references[0]), Some(114));
+        assert_eq!(get_api_minimum_version(&references[1]), Some(102));
+        assert_eq!(get_api_minimum_version("chrome.tabs.query"), None);
+        assert_eq!(get_api_minimum_version("chrome.actionable"), None);
+    }
+
+    #[test]
+    fn packs_directory_with_ignore_rules() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let source_dir = current_dir.join("out/pack-ignore");
+        create_directory_if_not_exists(&source_dir.join("node_modules/lib"));
+        create_directory_if_not_exists(&source_dir.join("js"));
+
+        for (name, content) in [
+            ("manifest.json", "{}"),
+            ("js/script.js", ""),
+            ("js/script.test.js", ""),
+            ("node_modules/lib/index.js", ""),
+            (".DS_Store", ""),
+            ("key.pem", ""),
+            (".crxignore", "node_modules/\n*.test.js\n"),
+        ] {
+            fs::write(source_dir.join(name), content).expect("Failed to write file");
+        }
+
+        let names = |options: &PackOptions| -> Vec<String> {
+            pack_entries(&source_dir, options)
+                .expect("Failed to walk directory")
+                .map(|entry| entry.expect("Failed to read entry").name)
+                .collect()
+        };
+
+        assert_eq!(
+            names(&PackOptions::default()),
+            vec!["js/script.js", "manifest.json"]
+        );
+        assert_eq!(
+            names(&PackOptions {
+                include: vec!["*.json".to_string()],
+                ..Default::default()
+            }),
+            vec!["manifest.json"]
+        );
+    }
+
+    #[test]
+    fn bumps_manifest_version() {
+        assert_eq!(bump_version("1.2.3", VersionBump::Patch).unwrap(), "1.2.4");
+        assert_eq!(
+            bump_version("1.2.3.4", VersionBump::Minor).unwrap(),
+            "1.3.0"
+        );
+        assert_eq!(bump_version("1", VersionBump::Patch).unwrap(), "1.0.1");
+        assert_eq!(bump_version("1.9", VersionBump::Major).unwrap(), "2.0.0");
+        assert!(bump_version("1.65535", VersionBump::Minor).is_err());
+
+        let content = "{\n  \"version_name\": \"1.0\",\n  \"version\" : \"1.0\"\n}";
+
+        assert_eq!(
+            set_manifest_version(content, "1.0.1").unwrap(),
+            "{\n  \"version_name\": \"1.0\",\n  \"version\" : \"1.0.1\"\n}"
+        );
+    }
+
+    #[test]
+    fn packs_reproducible_zip_payload() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let source_dir = current_dir.join("out/pack-reproducible");
+        create_directory_if_not_exists(&source_dir);
+
+        fs::write(source_dir.join("manifest.json"), "{}").expect("Failed to write file");
+        fs::write(source_dir.join("a.js"), "a").expect("Failed to write file");
+
+        let build_info = BuildInfo {
+            commit: None,
+            build_time: format_unix_time(951782400),
+            builder: Some("ci".to_string()),
+        };
+        let options = PackOptions {
+            build_info: Some(build_info.clone()),
+            ..Default::default()
+        };
+        let zip =
+            verify_reproducible(&source_dir, &options, None).expect("Build is not reproducible");
+
+        assert_eq!(
+            zip_directory(&source_dir, &options).expect("Failed to zip directory"),
+            zip
+        );
+        assert_eq!(build_info.build_time, "2000-02-29T00:00:00Z");
+        assert_eq!(
+            get_build_info(&zip).expect("Failed to read build info"),
+            Some(build_info)
+        );
+    }
+
+    #[test]
+    fn generates_update_manifest() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        assert_eq!(
+            get_extension_id(&data).expect("Failed to compute id"),
+            "hiebjbihmknnnjiaofclmmecnhigoooc"
+        );
+
+        let entry = get_update_manifest_entry(&data, "https://host/ext.crx?a=1&b=2")
+            .expect("Failed to read crx");
+        let xml = generate_update_manifest(&[entry]);
+
+        assert!(xml.contains("<app appid='hiebjbihmknnnjiaofclmmecnhigoooc'>"));
+        assert!(xml.contains(
+            "<updatecheck codebase='https://host/ext.crx?a=1&amp;b=2' version='1.0.0' />"
+        ));
+    }
+
+    #[test]
+    fn answers_update_checks() {
+        let checks = parse_update_request(
+            "os=linux&x=id%3Dhiebjbihmknnnjiaofclmmecnhigoooc%26v%3D0.9%26uc&x=id%3Dunknown%26uc",
+        );
+
+        assert_eq!(
+            checks,
+            vec![
+                UpdateCheck {
+                    app_id: "hiebjbihmknnnjiaofclmmecnhigoooc".to_string(),
+                    version: Some("0.9".to_string()),
+                },
+                UpdateCheck {
+                    app_id: "unknown".to_string(),
+                    version: None,
+                },
+            ]
+        );
+
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let entry =
+            get_update_manifest_entry(&data, "https://host/ext.crx").expect("Failed to read crx");
+
+        let xml = answer_update_checks(&checks, &[entry]);
+
+        assert!(xml.contains("version='1.0.0'"));
+        assert!(xml.contains("<app appid='unknown' status='error-unknownApplication' />"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scans_update_directory() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let directory = current_dir.join("out/update-directory");
+        let _ = fs::remove_dir_all(&directory);
+        create_directory_if_not_exists(&directory);
+
+        fs::copy(
+            current_dir.join("src/mock/test-extension.crx"),
+            directory.join("my ext#1.crx"),
+        )
+        .expect("Failed to copy file");
+        std::os::unix::fs::symlink(directory.join("missing"), directory.join("broken.crx"))
+            .expect("Failed to create symlink");
+
+        let hosted =
+            scan_update_directory(&directory, "https://host/").expect("Failed to scan directory");
+
+        assert_eq!(hosted.len(), 1);
+        assert_eq!(hosted[0].entry.codebase, "https://host/my%20ext%231.crx");
+    }
+
+    #[test]
+    fn generates_force_install_policy() {
+        let entries = [ForceInstallEntry {
+            id: "hiebjbihmknnnjiaofclmmecnhigoooc".to_string(),
+            update_url: "https://host/update.xml".to_string(),
+        }];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&generate_force_install_policy(&entries, PolicyFormat::Json))
+                .expect("Policy is not valid JSON");
+
+        assert_eq!(
+            json["ExtensionInstallForcelist"][0],
+            "hiebjbihmknnnjiaofclmmecnhigoooc;https://host/update.xml"
+        );
+        assert!(
+            generate_force_install_policy(&entries, PolicyFormat::Registry)
+                .contains("\"1\"=\"hiebjbihmknnnjiaofclmmecnhigoooc;https://host/update.xml\"")
+        );
+    }
+
+    #[test]
+    fn lists_managed_policy_options() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "AutoSave": { "type": "boolean", "title": "Automatically save" },
+                "Servers": {
+                    "type": "array",
+                    "items": { "type": "object", "properties": { "host": { "type": "string" } } }
+                }
+            }
+        });
+
+        let options: Vec<(String, String)> = get_policy_options(&schema)
+            .into_iter()
+            .map(|option| (option.name, option.kind))
+            .collect();
+
+        assert_eq!(
+            options,
+            vec![
+                ("AutoSave".to_string(), "boolean".to_string()),
+                ("Servers".to_string(), "array<object>".to_string()),
+                ("Servers[].host".to_string(), "string".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_zip_comments_and_extra_fields() {
+        let mut options = FullFileOptions::default();
+        options
+            .add_extra_data(0x5455, [1, 0, 0, 0, 0], false)
+            .expect("Failed to add extra data");
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .set_comment("built by ci")
+            .expect("Failed to set comment");
+        writer
+            .start_file("manifest.json", options)
+            .expect("Failed to start file");
+        writer.write_all(b"{}").expect("Failed to write file");
+        let zip = writer.finish().expect("Failed to finish zip").into_inner();
+
+        let entries = get_entries(&zip).expect("Failed to read entries");
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].local_extra_field.is_empty());
+        assert_eq!(
+            get_archive_comment(&zip).expect("Failed to read comment"),
+            "built by ci"
+        );
+
+        let stripped = strip_zip_metadata(&zip).expect("Failed to strip metadata");
+        let entries = get_entries(&stripped).expect("Failed to read entries");
+
+        assert!(entries[0].extra_field.is_empty());
+        assert!(entries[0].local_extra_field.is_empty());
+        assert_eq!(
+            get_archive_comment(&stripped).expect("Failed to read comment"),
+            ""
+        );
+        assert_eq!(
+            read_entry_to_string(&stripped, "manifest.json").expect("Failed to read entry"),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn detects_and_decrypts_encrypted_entries() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let zip =
+            fs::read(current_dir.join("src/mock/encrypted.zip")).expect("Failed to read file");
+
+        assert_eq!(
+            get_encrypted_entries(&zip).expect("Failed to read entries"),
+            vec!["manifest.json"]
+        );
+
+        let error = get_manifest(&zip).expect_err("Encrypted manifest should not be read");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::EncryptedEntries(vec![
+                "manifest.json".to_string()
+            ]))
+        );
+
+        let error = decrypt_zip(&zip, b"wrong").expect_err("Wrong password should fail");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::InvalidPassword)
+        );
+
+        let decrypted = decrypt_zip(&zip, b"secret").expect("Failed to decrypt zip");
+        assert!(get_encrypted_entries(&decrypted)
+            .expect("Failed to read entries")
+            .is_empty());
+        assert_eq!(
+            get_manifest(&decrypted).expect("Failed to read manifest")["name"],
+            "Encrypted"
+        );
+        assert_eq!(
+            read_entry_to_string(&decrypted, "readme.txt").expect("Failed to read entry"),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn describes_crx_layout() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+        let layout = extension.layout();
+
+        assert_eq!(layout.get(CrxField::Magic), Some(0..4));
+        assert_eq!(layout.get(CrxField::HeaderSize), Some(8..12));
+        assert_eq!(layout.get(CrxField::PublicKey), None);
+
+        let header = layout.get(CrxField::Header).expect("Missing header");
+        let zip = layout.get(CrxField::Zip).expect("Missing zip");
+        assert_eq!(header.start, 12);
+        assert_eq!(header.end, zip.start);
+        assert_eq!(&data[zip], extension.zip.as_slice());
+
+        let mut crx2 = b"Cr24".to_vec();
+        crx2.extend(2u32.to_le_bytes());
+        crx2.extend(3u32.to_le_bytes());
+        crx2.extend(2u32.to_le_bytes());
+        crx2.extend(b"keysgPK\x05\x06");
+
+        let layout = get_crx_layout(&crx2).expect("Failed to read layout");
+        assert_eq!(layout.get(CrxField::PublicKey), Some(16..19));
+        assert_eq!(layout.get(CrxField::Signature), Some(19..21));
+        assert_eq!(layout.get(CrxField::Zip), Some(21..25));
+    }
+
+    #[test]
+    fn carves_crx_files_from_binary_data() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let crx =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let mut dump = vec![0xaa; 64];
+        dump.extend(b"Cr24\x03\x00\x00\x00garbage");
+        dump.extend(&crx);
+        dump.extend([0x00; 32]);
+        dump.extend(&crx);
+
+        let carved = find_crx_files(&dump);
+
+        assert_eq!(carved.len(), 2);
+        assert_eq!(carved[0].offset, 79);
+        assert_eq!(carved[1].offset, 79 + crx.len() + 32);
+
+        for found in &carved {
+            assert_eq!(found.version, 3);
+            assert_eq!(&dump[found.offset..found.offset + found.length], &crx[..]);
+        }
+    }
+
+    #[test]
+    fn parses_concatenated_crx_files() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let crx =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let single = parse_crx(&crx).expect("Failed to parse crx");
+
+        let mut bundle = crx.clone();
+        bundle.extend(&crx);
+
+        let extensions = parse_all_crx(&bundle).expect("Failed to parse bundle");
+        assert_eq!(extensions.len(), 2);
+
+        for extension in &extensions {
+            assert_eq!(extension.zip, single.zip);
+        }
+
+        bundle.extend(b"trailing");
+        assert!(parse_all_crx(&bundle).is_err());
+    }
+
+    #[test]
+    fn finds_crx_files_in_nested_archives() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let crx =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let zip_files = |files: &[(&str, &[u8])]| -> Vec<u8> {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+            for (name, content) in files {
+                writer
+                    .start_file(*name, FullFileOptions::default())
+                    .expect("Failed to start file");
+                writer.write_all(content).expect("Failed to write file");
+            }
+
+            writer.finish().expect("Failed to finish zip").into_inner()
+        };
+
+        let inner = zip_files(&[("sub/b.crx", &crx), ("notes.txt", b"skipped")]);
+        let bundle = zip_files(&[
+            ("a.crx", &crx),
+            ("inner.zip", &inner),
+            ("../escape.crx", &crx),
+        ]);
+
+        let found = find_nested_crx_files(&bundle).expect("Failed to read bundle");
+        let paths: Vec<_> = found.iter().map(|nested| nested.path.as_str()).collect();
+
+        assert_eq!(paths, vec!["a.crx", "inner/sub/b.crx"]);
+        assert!(found.iter().all(|nested| nested.data == crx));
+    }
+
+    #[test]
+    fn runs_batch_with_bounded_queues() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let crx = current_dir.join("src/mock/test-extension.crx");
+        let output_dir = current_dir.join("out/batch");
+
+        let mut jobs: Vec<_> = (0..8)
+            .map(|index| BatchJob {
+                input: crx.clone(),
+                output: output_dir.join(format!("{}.zip", index)),
+            })
+            .collect();
+        jobs.push(BatchJob {
+            input: current_dir.join("src/mock/missing.crx"),
+            output: output_dir.join("missing.zip"),
+        });
+
+        let options = BatchOptions {
+            read_jobs: 1,
+            parse_jobs: 2,
+            extract_jobs: 1,
+            queue_size: 1,
+            ..BatchOptions::default()
+        };
+
+        let mut outcomes = vec![];
+        run_batch(jobs, &options, |outcome| outcomes.push(outcome));
+
+        assert_eq!(outcomes.len(), 9);
+
+        let failed: Vec<_> = outcomes
+            .iter()
+            .filter(|outcome| outcome.result.is_err())
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].job.input.ends_with("missing.crx"));
+
+        let extension =
+            parse_crx(&fs::read(&crx).expect("Failed to read file")).expect("Failed to parse crx");
+        assert_eq!(
+            fs::read(output_dir.join("7.zip")).expect("Failed to read zip"),
+            extension.zip
+        );
+    }
+
+    #[test]
+    fn resumes_batch_from_state_file() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let state_file = current_dir.join("out/batch-state/state");
+
+        let job = |name: &str| BatchJob {
+            input: PathBuf::from(name),
+            output: PathBuf::from(format!("{}.zip", name)),
+        };
+
+        let mut state = BatchState::open(&state_file, false).expect("Failed to open state");
+        state.record(&job("a.crx")).expect("Failed to record job");
+        state.record(&job("b.crx")).expect("Failed to record job");
+
+        let state = BatchState::open(&state_file, true).expect("Failed to open state");
+        assert!(state.is_completed(&job("a.crx")));
+        assert!(state.is_completed(&job("b.crx")));
+        assert!(!state.is_completed(&job("c.crx")));
+
+        let state = BatchState::open(&state_file, false).expect("Failed to open state");
+        assert!(!state.is_completed(&job("a.crx")));
+        assert_eq!(fs::read(&state_file).expect("Failed to read state"), b"");
+    }
+
+    #[test]
+    fn reuses_cached_extractions() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let crx = current_dir.join("src/mock/test-extension.crx");
+        let output_dir = current_dir.join("out/batch-cache");
+        let _ = fs::remove_dir_all(&output_dir);
+
+        let options = BatchOptions {
+            cache: Some(ExtractionCache::new(output_dir.join("cache"))),
+            ..BatchOptions::default()
+        };
+
+        let extract = || {
+            let job = BatchJob {
+                input: crx.clone(),
+                output: output_dir.join("extension.zip"),
+            };
+            let mut extractions = vec![];
+
+            run_batch([job], &options, |outcome| {
+                extractions.push(outcome.result.expect("Failed to extract"))
+            });
+
+            extractions
+        };
+
+        assert_eq!(extract(), vec![Extraction::Written]);
+        assert_eq!(extract(), vec![Extraction::Cached]);
+
+        // Restored outputs are copies, editing one leaves the read-only cache entry as it was
+        fs::write(output_dir.join("extension.zip"), b"edited").expect("Failed to write zip");
+        assert_eq!(extract(), vec![Extraction::Cached]);
+
+        let extension =
+            parse_crx(&fs::read(&crx).expect("Failed to read file")).expect("Failed to parse crx");
+        assert_eq!(
+            fs::read(output_dir.join("extension.zip")).expect("Failed to read zip"),
+            extension.zip
+        );
+
+        let cache = options.cache.as_ref().expect("Missing cache");
+        let key = ExtractionCache::key(&fs::read(&crx).expect("Failed to read file"), false, false);
+        assert!(fs::metadata(cache.path(&key))
+            .expect("Failed to read metadata")
+            .permissions()
+            .readonly());
+    }
+
+    #[test]
+    fn times_out_slow_batch_items() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+
+        let job = BatchJob {
+            input: current_dir.join("src/mock/test-extension.crx"),
+            output: current_dir.join("out/batch-timeout/extension.zip"),
+        };
+
+        let options = BatchOptions {
+            timeout: Some(Duration::ZERO),
+            ..BatchOptions::default()
+        };
+
+        let mut errors = vec![];
+        run_batch([job], &options, |outcome| {
+            errors.push(outcome.result.expect_err("Extraction should time out"))
+        });
+
+        assert_eq!(
+            errors[0].downcast_ref::<UncrxError>(),
+            Some(&UncrxError::TimedOut(Duration::ZERO))
+        );
+    }
+
+    #[test]
+    fn checks_sha256_of_input() {
+        let hash = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+        assert_eq!(get_sha256(b"hello"), hash);
+        assert!(is_sha256_match(b"hello", &hash.to_uppercase()));
+        assert!(is_sha256_match(b"hello", &format!("{}\n", hash)));
+        assert!(!is_sha256_match(b"hello!", hash));
+    }
+
+    #[test]
+    fn detects_crx_paths_regardless_of_case() {
+        assert!(is_crx_path(Path::new("dir/extension.crx")));
+        assert!(is_crx_path(Path::new("EXTENSION.CRX")));
+        assert!(!is_crx_path(Path::new("extension.crx.zip")));
+        assert!(!is_crx_path(Path::new(".crx")));
+    }
+
+    #[test]
+    fn merges_extractions_into_corpus() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+
+        let root = current_dir.join("out/corpus");
+        let _ = fs::remove_dir_all(&root);
+        let other = root.join("hiebjbihmknnnjiaofclmmecnhigoooc/0.9.0");
+        create_directory_if_not_exists(&other);
+
+        let target = root.join("hiebjbihmknnnjiaofclmmecnhigoooc/1.0.0");
+
+        assert_eq!(
+            merge_into_corpus(&root, &data, &extension.zip, &UnpackOptions::default())
+                .expect("Failed to merge"),
+            CorpusMerge::Added(target.clone())
+        );
+        assert!(target.join("js/script.js").is_file());
+
+        fs::write(target.join("manifest.json"), "{}").expect("Failed to write file");
+
+        assert_eq!(
+            merge_into_corpus(&root, &data, &extension.zip, &UnpackOptions::default())
+                .expect("Failed to merge"),
+            CorpusMerge::Present(target.clone())
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("manifest.json")).expect("Failed to read file"),
+            "{}"
+        );
+        assert!(other.is_dir());
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("../escape.txt", FullFileOptions::default())
+            .expect("Failed to start file");
+        let zip = writer.finish().expect("Failed to finish zip").into_inner();
+
+        assert!(unpack_zip(&zip, &root.join("escape")).is_err());
+        assert!(!root.join("escape.txt").exists());
+
+        for version in [".", "..", "1..0", "1.0.0.0.0"] {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            writer
+                .start_file("manifest.json", FullFileOptions::default())
+                .expect("Failed to start file");
+            writer
+                .write_all(format!(r#"{{"version": "{}"}}"#, version).as_bytes())
+                .expect("Failed to write file");
+            let zip = writer.finish().expect("Failed to finish zip").into_inner();
+
+            assert!(merge_into_corpus(&root, &data, &zip, &UnpackOptions::default()).is_err());
+        }
+        assert!(!root
+            .join("hiebjbihmknnnjiaofclmmecnhigoooc/..")
+            .join("js")
+            .exists());
+    }
+
+    #[test]
+    fn writes_registered_output_formats() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let output_dir = current_dir.join("out/formats");
+        let _ = fs::remove_dir_all(&output_dir);
+        create_directory_if_not_exists(&output_dir);
+
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        let mut formats = OutputFormats::builtin(UnpackOptions::default());
+        assert_eq!(formats.names(), ["zip", "directory", "tar"]);
+
+        for name in formats.names() {
+            let format = formats.get(name).expect("Format is registered");
+            let output = format.output_path(&output_dir);
+            format.write(&zip, &output).expect("Failed to write output");
+        }
+
+        assert_eq!(
+            fs::read(output_dir.join("extension.zip")).expect("Failed to read zip"),
+            zip
+        );
+        assert!(output_dir.join("extension/js/script.js").is_file());
+
+        // Headers and contents are padded to 512 byte blocks, the first header names the first
+        // entry and carries the ustar magic
+        let tar = fs::read(output_dir.join("extension.tar")).expect("Failed to read tar");
+        assert_eq!(tar.len() % 512, 0);
+        assert!(tar.starts_with(b"index.html\0"));
+        assert_eq!(&tar[257..263], b"ustar\0");
+
+        // Registering under a taken name replaces the format
+        formats.register(Box::new(MemoryFormat::default()));
+        formats.register(Box::new(MemoryFormat::default()));
+        assert_eq!(formats.names(), ["zip", "directory", "tar", "memory"]);
+
+        let memory = MemoryFormat::default();
+        let output = memory.output_path(&output_dir);
+        memory.write(&zip, &output).expect("Failed to write output");
+        assert_eq!(memory.take(&output), Some(zip));
+        assert_eq!(memory.take(&output), None);
+    }
+
+    #[test]
+    fn unpacks_in_chunks_of_configured_size() {
+        let output_dir = env::temp_dir().join("uncrx-chunked-unpack");
+        let _ = fs::remove_dir_all(&output_dir);
+
+        let content: Vec<u8> = (0..100_000).map(|index| (index % 251) as u8).collect();
+        let zip = build_zip(
+            &[
+                ("empty.txt".to_string(), vec![]),
+                ("data/large.bin".to_string(), content.clone()),
+            ],
+            CompressionMethod::Deflated,
+        )
+        .expect("Failed to build zip");
+
+        let options = UnpackOptions {
+            chunk_size: 7,
+            drop_page_cache: true,
+        };
+        unpack_zip_with_options(&zip, &output_dir, &options).expect("Failed to unpack zip");
+
+        assert_eq!(
+            fs::read(output_dir.join("data/large.bin")).expect("Failed to read file"),
+            content
+        );
+        assert_eq!(
+            fs::metadata(output_dir.join("empty.txt"))
+                .expect("Failed to read metadata")
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn rejects_entries_shorter_than_declared() {
+        let output_dir = env::temp_dir().join("uncrx-short-entry");
+        let _ = fs::remove_dir_all(&output_dir);
+
+        let mut zip = build_zip(
+            &[("padded.bin".to_string(), b"hello".to_vec())],
+            CompressionMethod::Stored,
+        )
+        .expect("Failed to build zip");
+
+        // Declare 50 MiB in both the local header and the central directory record
+        let declared = (50u32 * 1024 * 1024).to_le_bytes();
+        zip[22..26].copy_from_slice(&declared);
+        let record = zip
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .expect("Missing central directory");
+        zip[record + 24..record + 28].copy_from_slice(&declared);
+
+        assert!(unpack_zip(&zip, &output_dir).is_err());
+        assert!(!output_dir.join("padded.bin").exists());
+    }
+
+    #[test]
+    fn removes_entries_failing_to_decompress() {
+        let output_dir = env::temp_dir().join("uncrx-corrupt-entry");
+        let _ = fs::remove_dir_all(&output_dir);
+
+        let content: Vec<u8> = (0..64 * 1024).map(|index| (index % 251) as u8).collect();
+        let mut zip = build_zip(
+            &[("corrupt.bin".to_string(), content)],
+            CompressionMethod::Deflated,
+        )
+        .expect("Failed to build zip");
+
+        // Garble the middle of the compressed data, the sizes are left as declared
+        let compressed = u32::from_le_bytes(zip[18..22].try_into().unwrap()) as usize;
+        let middle = 30 + "corrupt.bin".len() + compressed / 2;
+        zip[middle..middle + 16].fill(0xff);
+
+        assert!(unpack_zip(&zip, &output_dir).is_err());
+        assert!(!output_dir.join("corrupt.bin").exists());
+    }
+
+    #[test]
+    fn reports_parse_errors_with_offsets_and_hints() {
+        let error = parse_crx(b"PK\x03\x04rest of a zip").expect_err("A zip is not a CRX file");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::InvalidMagic(*b"PK\x03\x04"))
+        );
+        assert!(error.to_string().contains("found 'PK\\x03\\x04'"));
+        assert!(error.to_string().contains("try --force-zip"));
+
+        let error = parse_crx(b"Cr24\x03\x00\x00\x00\x10\x00").expect_err("Header is truncated");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::Truncated {
+                needed: 12,
+                found: 10
+            })
+        );
+
+        let error = parse_crx(b"Cr24\x03\x00\x00\x00\x00\x00\x00\x00junk")
+            .expect_err("Payload is not a zip");
+        assert_eq!(
+            error.to_string(),
+            "Expected a zip archive at byte 12, found 'junk'. The header lengths may be corrupted"
+        );
+    }
+
+    #[test]
+    fn recovers_entries_of_truncated_zip() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        let last_entry = zip
+            .windows(4)
+            .rposition(|window| window == b"PK\x03\x04")
+            .expect("Missing local header");
+        let truncated = &zip[..last_entry + 100];
+
+        assert!(get_entries(truncated).is_err());
+
+        let recovery = recover_zip(truncated).expect("Failed to recover zip");
+
+        assert_eq!(
+            recovery.recovered,
+            vec!["index.html", "manifest.json", "js/"]
+        );
+        assert_eq!(recovery.lost, vec!["js/script.js"]);
+        assert_eq!(
+            get_manifest(&recovery.zip).expect("Failed to read manifest")["name"],
+            "Test Extension"
+        );
+
+        let recovery = recover_zip(&zip).expect("Failed to recover zip");
+        assert_eq!(recovery.recovered.len(), 4);
+        assert!(recovery.lost.is_empty());
+    }
+
+    #[test]
+    fn flags_central_directory_mismatches() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let mut zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        assert!(check_zip_consistency(&zip)
+            .expect("Failed to check zip")
+            .is_empty());
+
+        let central_directory = zip
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .expect("Missing central directory");
+
+        // The first entry is index.html, rename it locally and change its central CRC
+        zip[30] = b'I';
+        zip[central_directory + 16] ^= 0xff;
+
+        let inconsistencies = check_zip_consistency(&zip).expect("Failed to check zip");
+        let messages: Vec<String> = inconsistencies.iter().map(|i| i.to_string()).collect();
+
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("index.html: crc32 is "));
+        assert_eq!(
+            messages[1],
+            "index.html: is named Index.html in its local header"
+        );
+    }
+
+    #[test]
+    fn detects_polyglot_files() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        assert!(find_polyglot_formats(&data)
+            .expect("Failed to scan crx")
+            .is_empty());
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in ["manifest.json", "META-INF/MANIFEST.MF"] {
+            writer
+                .start_file(name, FullFileOptions::default())
+                .expect("Failed to start file");
+            writer.write_all(b"{}").expect("Failed to write file");
+        }
+        let zip = writer.finish().expect("Failed to finish zip").into_inner();
+
+        // CRX3 header bytes are opaque to other parsers, hide a PDF and an HTML header there
+        let header = b"%PDF-1.7 <HTML><script>alert(1)</script>";
+        let mut crx = b"Cr24".to_vec();
+        crx.extend(3u32.to_le_bytes());
+        crx.extend((header.len() as u32).to_le_bytes());
+        crx.extend(header);
+        crx.extend(&zip);
+
+        let java_manifest = crx
+            .windows(4)
+            .rposition(|window| window == b"PK\x03\x04")
+            .expect("Missing local header");
+
+        let matches = find_polyglot_formats(&crx).expect("Failed to scan crx");
+        let formats: Vec<(PolyglotFormat, usize)> = matches
+            .iter()
+            .map(|polyglot| (polyglot.format, polyglot.offset))
+            .collect();
+
+        assert_eq!(
+            formats,
+            vec![
+                (PolyglotFormat::Html, 21),
+                (PolyglotFormat::Pdf, 12),
+                (PolyglotFormat::Jar, java_manifest),
+            ]
+        );
+    }
+
+    #[test]
+    fn sets_modes_of_extracted_files() {
+        assert_eq!(
+            "0644/0755".parse(),
+            Ok(FileModes {
+                file: 0o644,
+                directory: 0o755
+            })
+        );
+        assert_eq!(
+            "640".parse::<FileModes>().map(|modes| modes.to_string()),
+            Ok("0640/0750".to_string())
+        );
+        assert!("0999".parse::<FileModes>().is_err());
+
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        let output_dir = current_dir.join("out/modes");
+        let _ = fs::remove_dir_all(&output_dir);
+        unpack_zip(&zip, &output_dir).expect("Failed to unpack zip");
+
+        set_file_modes(
+            &output_dir,
+            &"0600/0700".parse().expect("Failed to parse modes"),
+        )
+        .expect("Failed to set modes");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = |path: PathBuf| {
+                fs::metadata(path)
+                    .expect("Failed to read metadata")
+                    .permissions()
+                    .mode()
+                    & 0o7777
+            };
+
+            assert_eq!(mode(output_dir.join("js")), 0o700);
+            assert_eq!(mode(output_dir.join("js/script.js")), 0o600);
+
+            // Directories without search permission are changed after their entries, which
+            // are then only reachable once the directories are opened up again
+            set_file_modes(
+                &output_dir,
+                &"0644/0600".parse().expect("Failed to parse modes"),
+            )
+            .expect("Failed to set modes");
+
+            let open_up = |path: PathBuf| {
+                fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+                    .expect("Failed to set mode")
+            };
+
+            assert_eq!(mode(output_dir.clone()), 0o600);
+            open_up(output_dir.clone());
+            assert_eq!(mode(output_dir.join("js")), 0o600);
+            open_up(output_dir.join("js"));
+            assert_eq!(mode(output_dir.join("js/script.js")), 0o644);
+
+            set_file_modes(
+                &output_dir,
+                &"0644/0755".parse().expect("Failed to parse modes"),
+            )
+            .expect("Failed to set modes");
+        }
+    }
+
+    #[test]
+    fn parses_ownership() {
+        assert_eq!(
+            "1000:100".parse(),
+            Ok(Ownership {
+                user: Some(1000),
+                group: Some(100)
+            })
+        );
+        assert_eq!(
+            ":100".parse(),
+            Ok(Ownership {
+                user: None,
+                group: Some(100)
+            })
+        );
+        assert!("".parse::<Ownership>().is_err());
+        assert!("no-such-user-uncrx".parse::<Ownership>().is_err());
+
+        #[cfg(unix)]
+        assert_eq!(
+            "root:0"
+                .parse::<Ownership>()
+                .map(|ownership| ownership.user),
+            Ok(Some(0))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_scanners_on_staged_files() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let staging = current_dir.join("src/mock");
+
+        let scanners = vec![
+            "test -d".to_string(),
+            "test -f".to_string(),
+            "ls".to_string(),
+        ];
+
+        let failures = run_scanners(&staging, &scanners).expect("Failed to run scanners");
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].scanner, "test -f");
+        assert_eq!(failures[0].status, Some(1));
+
+        assert!(run_scanners(&staging, &["no-such-scanner-uncrx".to_string()]).is_err());
+    }
+
+    #[test]
+    fn removes_staging_dir_unless_kept() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let parent = current_dir.join("out/staging-dir");
+
+        let staging =
+            StagingDir::create_in(&parent, "staging-").expect("Failed to create staging dir");
+        let path = staging.path().to_path_buf();
+        assert_eq!(path.parent(), Some(parent.as_path()));
+        fs::write(staging.path().join("file"), "").expect("Failed to write file");
+        drop(staging);
+        assert!(!path.exists());
+
+        // A panic while the directory is in use removes it too
+        let result = std::panic::catch_unwind(|| {
+            let staging =
+                StagingDir::create_in(&parent, "staging-").expect("Failed to create staging dir");
+            let path = staging.path().to_path_buf();
+            std::panic::panic_any(path);
+        });
+        let path = result
+            .expect_err("The closure panics")
+            .downcast::<PathBuf>()
+            .expect("Panicked with the path");
+        assert!(!path.exists());
+
+        let staging =
+            StagingDir::create_in(&parent, "staging-").expect("Failed to create staging dir");
+        let path = staging.keep();
+        assert!(path.is_dir());
+
+        // Names are random, a kept directory is never reused
+        let staging =
+            StagingDir::create_in(&parent, "staging-").expect("Failed to create staging dir");
+        assert_ne!(staging.path(), path);
+    }
+
+    #[test]
+    fn computes_swhids_like_git() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        // As printed by git write-tree and git hash-object for the unpacked files
+        let swhids = get_tree_swhids(&zip).expect("Failed to compute SWHIDs");
+        assert_eq!(
+            swhids.directory,
+            "swh:1:dir:3b8cb8be2c6bbbbb653a40fb415ccab4481e3f37"
+        );
+        assert_eq!(
+            swhids.files["manifest.json"],
+            "swh:1:cnt:a716514512d9c7df7327c6fe9089885b84625048"
+        );
+        assert_eq!(swhids.files.len(), 3);
+
+        assert_eq!(
+            get_content_swhid(b""),
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+    }
+
+    #[test]
+    fn finds_license_files_and_headers() {
+        let zip = build_zip(
+            &[
+                (
+                    "LICENSE.txt".to_string(),
+                    b"Permission is hereby granted, free of charge,\nto any person".to_vec(),
+                ),
+                (
+                    "vendor/COPYING".to_string(),
+                    b"GNU GENERAL PUBLIC LICENSE\n   Version 3, 29 June 2007".to_vec(),
+                ),
+                (
+                    "js/lib.min.js".to_string(),
+                    b"/*! @license MIT */ var a = 1;".to_vec(),
+                ),
+                (
+                    "js/app.js".to_string(),
+                    b"// SPDX-License-Identifier: Apache-2.0\n".to_vec(),
+                ),
+                ("js/plain.js".to_string(), b"var b = 2;".to_vec()),
+                ("LICENSE-OTHER".to_string(), b"All rights reserved".to_vec()),
+            ],
+            CompressionMethod::Deflated,
+        )
+        .expect("Failed to build zip");
+
+        let licenses = find_licenses(&zip).expect("Failed to find licenses");
+        assert_eq!(licenses.len(), 5);
+        assert_eq!(licenses[0].source, LicenseSource::File);
+        assert_eq!(licenses[2].source, LicenseSource::Header);
+
+        let summary = summarize_licenses(&licenses);
+        assert_eq!(summary["MIT"], ["LICENSE.txt", "js/lib.min.js"]);
+        assert_eq!(summary["GPL-3.0"], ["vendor/COPYING"]);
+        assert_eq!(summary["Apache-2.0"], ["js/app.js"]);
+        assert_eq!(summary["unknown"], ["LICENSE-OTHER"]);
+    }
+
+    #[test]
+    fn extracts_crx_to_dir() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+
+        let output_dir = current_dir.join("out/extract-to-dir");
+        let _ = fs::remove_dir_all(&output_dir);
+        fs::create_dir_all(&output_dir).expect("Failed to create directory");
+
+        let count = extract_to_dir(&extension, &output_dir, &ExtractOptions::default())
+            .expect("Failed to extract crx");
+        assert_eq!(count, 4);
+        assert!(output_dir.join("js/script.js").is_file());
+
+        // Nothing is written over an earlier extraction, nor left next to it
+        assert!(extract_to_dir(&extension, &output_dir, &ExtractOptions::default()).is_err());
+        assert_eq!(
+            fs::read_dir(current_dir.join("out"))
+                .expect("Failed to read directory")
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".uncrx-extract-"))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn writes_extraction_manifest() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+
+        let files = get_entry_hashes(&zip, HashAlgorithm::Sha256).expect("Failed to hash entries");
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            vec!["index.html", "js/script.js", "manifest.json"]
+        );
+
+        let manifest = ExtractionManifest::new(&data, &zip, files, HashAlgorithm::Sha256);
+        assert_eq!(manifest.source_hash, get_sha256(&data));
+        assert_eq!(
+            manifest.extension_id.as_deref(),
+            Some("hiebjbihmknnnjiaofclmmecnhigoooc")
+        );
+        assert_eq!(manifest.version.as_deref(), Some("1.0.0"));
+
+        let output_dir = current_dir.join("out/provenance");
+        fs::create_dir_all(&output_dir).expect("Failed to create directory");
+
+        write_extraction_manifest(&output_dir, &manifest).expect("Failed to write manifest");
+        assert_eq!(
+            read_extraction_manifest(&output_dir).expect("Failed to read manifest"),
+            manifest
+        );
+    }
+
+    #[test]
+    fn finds_changes_to_extracted_trees() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+        let files = get_entry_hashes(&zip, HashAlgorithm::Sha256).expect("Failed to hash entries");
+
+        let output_dir = current_dir.join("out/tree-changes");
+        let _ = fs::remove_dir_all(&output_dir);
+        unpack_zip(&zip, &output_dir).expect("Failed to unpack zip");
+        write_extraction_manifest(
+            &output_dir,
+            &ExtractionManifest::new(&data, &zip, files.clone(), HashAlgorithm::Sha256),
+        )
+        .expect("Failed to write manifest");
+
+        assert!(
+            find_tree_changes(&output_dir, &files, HashAlgorithm::Sha256)
+                .expect("Failed to check tree")
+                .is_empty()
+        );
+
+        fs::write(output_dir.join("js/script.js"), "alert(1)").expect("Failed to write file");
+        fs::write(output_dir.join("js/extra.js"), "").expect("Failed to write file");
+        fs::remove_file(output_dir.join("index.html")).expect("Failed to remove file");
+
+        let changes: Vec<(TreeChangeKind, String)> =
+            find_tree_changes(&output_dir, &files, HashAlgorithm::Sha256)
+                .expect("Failed to check tree")
+                .into_iter()
+                .map(|change| (change.kind, change.path))
+                .collect();
+
+        assert_eq!(
+            changes,
+            vec![
+                (TreeChangeKind::Missing, "index.html".to_string()),
+                (TreeChangeKind::Added, "js/extra.js".to_string()),
+                (TreeChangeKind::Modified, "js/script.js".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn downloads_crx_files_over_http() {
+        assert!(is_url("HTTPS://example.com/extension.crx"));
+        assert!(!is_url("extension.crx"));
+        assert!(is_accepted_content_type("application/x-chrome-extension"));
+        assert!(!is_accepted_content_type("text/html; charset=utf-8"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let address = listener.local_addr().expect("Failed to get address");
+
+        let server = thread::spawn(move || {
+            for content_type in ["application/x-chrome-extension", "text/html"] {
+                let (mut stream, _) = listener.accept().expect("Failed to accept connection");
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: 4\r\nConnection: close\r\n\r\nCr24",
+                    content_type
+                )
+                .expect("Failed to write response");
+            }
+        });
+
+        let url = format!("http://{}/extension.crx", address);
+
+        let options = DownloadOptions {
+            max_size: 1024,
+            rate_limit: Some(8),
+            ..DownloadOptions::default()
+        };
+
+        let start = Instant::now();
+        assert_eq!(
+            download(&url, &options).expect("Failed to download"),
+            b"Cr24"
+        );
+        assert!(start.elapsed() >= Duration::from_millis(500));
+
+        let error = download(&url, &options).expect_err("Downloaded an HTML page");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::UnexpectedContentType("text/html".to_string()))
+        );
+
+        server.join().expect("Failed to join server");
+
+        let offline = DownloadOptions {
+            offline: true,
+            ..DownloadOptions::default()
+        };
+        let error = download(&url, &offline).expect_err("Downloaded in offline mode");
+        assert_eq!(
+            error.downcast_ref::<UncrxError>(),
+            Some(&UncrxError::Offline(url))
+        );
+    }
+
+    #[test]
+    fn revalidates_cached_downloads() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let cache_dir = current_dir.join("out/http-cache");
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let address = listener.local_addr().expect("Failed to get address");
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+
+            for response in [
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 4\r\nConnection: close\r\n\r\nCr24",
+                "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().expect("Failed to accept connection");
+                let mut request = [0u8; 1024];
+                let length = stream.read(&mut request).expect("Failed to read request");
+
+                requests.push(String::from_utf8_lossy(&request[..length]).to_lowercase());
+                stream
+                    .write_all(response.as_bytes())
+                    .expect("Failed to write response");
+            }
+
+            requests
+        });
+
+        let url = format!("http://{}/extension.crx", address);
+        let options = DownloadOptions {
+            cache: Some(HttpCache::new(&cache_dir)),
+            ..DownloadOptions::default()
+        };
+
+        for _ in 0..2 {
+            assert_eq!(
+                download(&url, &options).expect("Failed to download"),
+                b"Cr24"
+            );
+        }
+
+        let requests = server.join().expect("Failed to join server");
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+
+    #[test]
+    fn hashes_with_selected_algorithm() {
+        assert_eq!("sha512".parse(), Ok(HashAlgorithm::Sha512));
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+
+        assert_eq!(HashAlgorithm::Sha256.hash(b"abc"), get_sha256(b"abc"));
+
+        let sha512 = HashAlgorithm::Sha512.hash(b"abc");
+        assert_eq!(sha512.len(), 128);
+        assert!(sha512.starts_with("ddaf35a193617aba"));
+        assert_eq!(
+            HashAlgorithm::Sha512
+                .hash_reader(&mut Cursor::new(b"abc"))
+                .expect("Failed to hash reader"),
+            sha512
+        );
+
+        // The official test vector for an empty input
+        assert_eq!("blake3".parse(), Ok(HashAlgorithm::Blake3));
+        assert_eq!(
+            HashAlgorithm::Blake3.hash(b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+
+        // Manifests written before --hash existed
+        let manifest: ExtractionManifest = serde_json::from_str(
+            r#"{"source_sha256": "00", "extension_id": null, "version": null, "tool_version": "0.2.2", "files": {}}"#,
+        )
+        .expect("Failed to parse manifest");
+        assert_eq!(manifest.hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(manifest.source_hash, "00");
+    }
+
+    #[test]
+    fn collects_corpus_stats() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        assert_eq!(
+            get_signature_algorithms(&parse_crx(&data).expect("Failed to parse crx")),
+            vec!["sha256_with_rsa"]
+        );
+
+        let key = generate_test_key().expect("Failed to generate key");
+        let zip = parse_crx(&data).expect("Failed to parse crx").zip;
+        let crx2 = build_crx2(&zip, &key).expect("Failed to build crx");
+        assert_eq!(
+            get_signature_algorithms(&parse_crx(&crx2).expect("Failed to parse crx")),
+            vec!["sha1_with_rsa"]
+        );
+
+        let root = current_dir.join("out/stats");
+        let _ = fs::remove_dir_all(&root);
+        let unpacked = root.join("unpacked");
+        create_directory_if_not_exists(&unpacked);
+
+        fs::write(root.join("extension.crx"), &data).expect("Failed to write file");
+        fs::write(root.join("broken.crx"), b"Cr24").expect("Failed to write file");
+        fs::write(
+            unpacked.join("manifest.json"),
+            r#"{"manifest_version": 2, "permissions": ["tabs", "storage", {"socket": []}]}"#,
+        )
+        .expect("Failed to write file");
+
+        let stats = collect_corpus_stats(&root).expect("Failed to collect stats");
+
+        assert_eq!(stats.extensions, 2);
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(
+            stats.manifest_versions.into_iter().collect::<Vec<_>>(),
+            vec![(2, 1), (3, 1)]
+        );
+        assert_eq!(
+            stats
+                .permissions
+                .iter()
+                .map(|permission| permission.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["storage", "tabs"]
+        );
+        assert_eq!(stats.signature_algorithms.get("sha256_with_rsa"), Some(&1));
+    }
+
+    #[test]
+    fn exports_corpus_records() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+
+        let root = current_dir.join("out/export");
+        let _ = fs::remove_dir_all(&root);
+        merge_into_corpus(&root, &data, &extension.zip, &UnpackOptions::default())
+            .expect("Failed to merge");
+        create_directory_if_not_exists(&root.join("not-an-id/1.0.0"));
+
+        let records = get_corpus_records(&root).expect("Failed to read corpus");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, "hiebjbihmknnnjiaofclmmecnhigoooc");
+        assert_eq!(records[0].manifest_version, Some(3));
+
+        let mut csv = vec![];
+        write_csv(&records, &mut csv).expect("Failed to write csv");
+        let csv = String::from_utf8(csv).expect("Invalid csv");
+        assert!(csv.starts_with("id,version,name,manifest_version,"));
+        assert!(csv.contains("\nhiebjbihmknnnjiaofclmmecnhigoooc,1.0.0,Test Extension,3,"));
+
+        assert_eq!(
+            ExportFormat::from_path(&root.join("corpus.parquet")),
+            ExportFormat::Parquet
+        );
+
+        #[cfg(feature = "parquet")]
+        {
+            use parquet::file::reader::{FileReader, SerializedFileReader};
+
+            let path = root.join("corpus.parquet");
+            crate::export::write_parquet(
+                &records,
+                fs::File::create(&path).expect("Failed to create file"),
+            )
+            .expect("Failed to write parquet");
+
+            let reader = SerializedFileReader::new(fs::File::open(&path).expect("Failed to open"))
+                .expect("Failed to read parquet");
+            assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+        }
+    }
+
+    #[test]
+    fn builds_web_store_download_url() {
+        let url = download_url(
+            "hiebjbihmknnnjiaofclmmecnhigoooc",
+            "131.0.6778.85",
+            "linux",
+            "x64",
+        )
+        .expect("Failed to build url");
+
+        assert!(
+            url.starts_with("https://clients2.google.com/service/update2/crx?response=redirect&")
+        );
+        assert!(url.contains("&os=linux&arch=x64&nacl_arch=x86-64&"));
+        assert!(url.contains("&prodversion=131.0.6778.85&acceptformat=crx2,crx3&"));
+        assert!(url.ends_with("&x=id%3Dhiebjbihmknnnjiaofclmmecnhigoooc%26uc"));
+
+        assert_eq!(
+            parse_update_request(url.split_once('?').unwrap().1)[0].app_id,
+            "hiebjbihmknnnjiaofclmmecnhigoooc"
+        );
+        assert!(download_url("not-an-id", "131", "linux", "x64").is_err());
+    }
+
+    #[test]
+    fn imports_chrome_profile_extensions() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let profile = current_dir.join("out/profile/Extensions");
+        let root = current_dir.join("out/profile/corpus");
+        let _ = fs::remove_dir_all(current_dir.join("out/profile"));
+
+        let installed = profile.join("hiebjbihmknnnjiaofclmmecnhigoooc/1.0.0_0");
+        create_directory_if_not_exists(&installed.join("js"));
+        fs::write(installed.join("manifest.json"), r#"{"version": "1.0.0"}"#)
+            .expect("Failed to write file");
+        fs::write(installed.join("js/script.js"), "").expect("Failed to write file");
+
+        // Renamed directories are identified by the key in their manifest
+        let renamed = profile.join("copied/2.1_0");
+        create_directory_if_not_exists(&renamed);
+        fs::write(
+            renamed.join("manifest.json"),
+            r#"{"version": "2.1", "key": "cHVibGljIGtleQ=="}"#,
+        )
+        .expect("Failed to write file");
+
+        let broken = profile.join("hiebjbihmknnnjiaofclmmecnhigoooc/0.9_0");
+        create_directory_if_not_exists(&broken);
+        fs::write(broken.join("manifest.json"), r#"{"version": "../0.9"}"#)
+            .expect("Failed to write file");
+
+        let extensions = find_installed_extensions(&profile).expect("Failed to read profile");
+        assert_eq!(
+            extensions
+                .iter()
+                .map(|extension| (extension.id.as_str(), extension.version.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    get_extension_id_from_public_key(b"public key").as_str(),
+                    "2.1"
+                ),
+                ("hiebjbihmknnnjiaofclmmecnhigoooc", "1.0.0"),
+            ]
+        );
+
+        let target = root.join("hiebjbihmknnnjiaofclmmecnhigoooc/1.0.0");
+        assert_eq!(
+            import_into_corpus(&root, &extensions[1]).expect("Failed to import"),
+            CorpusMerge::Added(target.clone())
+        );
+        assert!(target.join("js/script.js").is_file());
+        assert_eq!(
+            import_into_corpus(&root, &extensions[1]).expect("Failed to import"),
+            CorpusMerge::Present(target)
+        );
+    }
+
+    #[test]
+    fn repacks_installed_extensions() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let source_dir = current_dir.join("out/pack-installed");
+        create_directory_if_not_exists(&source_dir.join("_metadata"));
+
+        for (name, content) in [
+            (
+                "manifest.json",
+                r#"{"key": "cHVibGljIGtleQ==", "version": "1.0"}"#,
+            ),
+            ("_metadata/verified_contents.json", "[]"),
+        ] {
+            fs::write(source_dir.join(name), content).expect("Failed to write file");
+        }
+
+        let options = PackOptions {
+            installed: true,
+            ..Default::default()
+        };
+        let zip = zip_directory(&source_dir, &options).expect("Failed to pack directory");
+
+        assert_eq!(
+            get_entries(&zip)
+                .expect("Failed to read entries")
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["manifest.json"]
+        );
+
+        let manifest = get_manifest(&zip).expect("Failed to read manifest");
+        assert!(manifest.get("key").is_none());
+        assert_eq!(manifest["version"], "1.0");
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "smoke-test"))]
+    fn smoke_tests_packed_extensions() {
+        use crate::smoke_test::{smoke_test, SmokeTestResult};
+        use std::os::unix::fs::PermissionsExt;
+
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+        let extension = parse_crx(&data).expect("Failed to parse crx");
+
+        let browser_dir = current_dir.join("out/smoke-test");
+        create_directory_if_not_exists(&browser_dir);
+
+        let browser = browser_dir.join("chromium");
+        fs::write(
+            &browser,
+            "#!/bin/sh\necho 'ERROR: Failed to load extension from: x. Invalid manifest' >&2\nsleep 10\n",
+        )
+        .expect("Failed to write file");
+        fs::set_permissions(&browser, fs::Permissions::from_mode(0o755))
+            .expect("Failed to set permissions");
+
+        let start = Instant::now();
+
+        assert_eq!(
+            smoke_test(&extension.zip, &browser, Duration::from_secs(5)).expect("Failed to run"),
+            SmokeTestResult::Rejected(
+                "ERROR: Failed to load extension from: x. Invalid manifest".to_string()
+            )
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn tests_installs_through_webdriver() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let address = listener.local_addr().expect("Failed to get address");
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+
+            for (status, body) in [
+                (
+                    "500 Internal Server Error",
+                    r#"{"value": {"error": "session not created", "message": "cannot process extension #1"}}"#,
+                ),
+                (
+                    "200 OK",
+                    r#"{"value": {"sessionId": "42", "capabilities": {}}}"#,
+                ),
+                ("200 OK", r#"{"value": null}"#),
+            ] {
+                let (mut stream, _) = listener.accept().expect("Failed to accept connection");
+                let mut request = vec![];
+                let mut buffer = [0u8; 4096];
+
+                // The whole body is read, closing early would reset the connection
+                while !String::from_utf8_lossy(&request).contains("\r\n\r\n")
+                    || request.len() < get_request_length(&request)
+                {
+                    let length = stream.read(&mut buffer).expect("Failed to read request");
+                    request.extend_from_slice(&buffer[..length]);
+                }
+
+                requests.push(String::from_utf8_lossy(&request).into_owned());
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .expect("Failed to write response");
+            }
+
+            requests
+        });
+
+        let url = format!("http://{}/", address);
+
+        assert_eq!(
+            test_install(&url, b"Cr24").expect("Failed to reach webdriver"),
+            InstallResult::Rejected("cannot process extension #1".to_string())
+        );
+        assert_eq!(
+            test_install(&url, b"Cr24").expect("Failed to reach webdriver"),
+            InstallResult::Accepted
+        );
+
+        let requests = server.join().expect("Failed to join server");
+        assert!(requests[0].starts_with("POST /session "));
+        assert!(requests[0].contains(r#""extensions":["Q3IyNA=="]"#));
+        assert!(requests[2].starts_with("DELETE /session/42 "));
+    }
+
+    #[test]
+    fn computes_from_bytes_without_filesystem_access() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let data =
+            fs::read(current_dir.join("src/mock/test-extension.crx")).expect("Failed to read file");
+
+        let mut collector = StatsCollector::default();
+        collector.add_crx(&data).expect("Failed to add crx");
+        collector.add_manifest(&serde_json::json!({"manifest_version": 2}), 100);
+        assert!(collector.add_crx(b"Cr24").is_err());
+
+        let stats = collector.finish();
+        assert_eq!(stats.extensions, 2);
+        assert_eq!(stats.signature_algorithms.get("sha256_with_rsa"), Some(&1));
+
+        let hosted = |version: &str| {
+            let mut entry = get_update_manifest_entry(&data, "https://host/extension.crx")
+                .expect("Failed to read crx");
+            entry.version = version.to_string();
+
+            HostedExtension {
+                entry,
+                path: PathBuf::from(format!("{}.crx", version)),
+                file_name: format!("{}.crx", version),
+            }
+        };
+
+        let latest = keep_latest_versions([hosted("1.2"), hosted("1.10"), hosted("1.9")]);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].entry.version, "1.10");
+    }
+
+    #[test]
+    fn builds_fixtures_with_quirks() {
+        let quirks: Vec<FixtureQuirk> = ["zip64", "entries=3", "trailing-garbage=16"]
+            .iter()
+            .map(|quirk| quirk.parse().expect("Failed to parse quirk"))
+            .collect();
+        assert_eq!(quirks[1], FixtureQuirk::Entries(3));
+        assert!("entries".parse::<FixtureQuirk>().is_err());
+
+        let key = &get_test_keys()[0];
+        let crx = build_fixture(3, &quirks, key).expect("Failed to build fixture");
+        assert_eq!(crx[crx.len() - 16..], (0..16).collect::<Vec<u8>>());
+
+        let zip = parse_crx(&crx[..crx.len() - 16])
+            .expect("Failed to parse crx")
+            .zip;
+        assert_eq!(get_entries(&zip).expect("Failed to read entries").len(), 4);
+
+        let unsigned =
+            build_fixture(2, &[FixtureQuirk::ZeroLengthKey], key).expect("Failed to build fixture");
+        assert_eq!(unsigned[8..16], [0; 8]);
+        assert_eq!(
+            get_manifest(&parse_crx(&unsigned).expect("Failed to parse crx").zip)
+                .expect("Failed to read manifest")["name"],
+            "Fixture"
+        );
+    }
+
+    // Generating keys dominates the run time, so every case signs with one of these
+    fn get_test_keys() -> &'static [RsaPrivateKey] {
+        static KEYS: OnceLock<Vec<RsaPrivateKey>> = OnceLock::new();
+
+        KEYS.get_or_init(|| {
+            (0..2)
+                .map(|_| generate_test_key().expect("Failed to generate key"))
+                .collect()
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn round_trips_synthetic_crx_files(
+            files in prop::collection::btree_map(
+                "[a-z]{1,8}(/[a-z]{1,8})?\\.txt",
+                prop::collection::vec(any::<u8>(), 0..512),
+                1..8,
+            ),
+            crx_version in 2u32..=3,
+            key_index in 0usize..2,
+            deflate in any::<bool>(),
+        ) {
+            let key = &get_test_keys()[key_index];
+            let entries: Vec<(String, Vec<u8>)> = files.clone().into_iter().collect();
+            let compression = match deflate {
+                true => CompressionMethod::Deflated,
+                false => CompressionMethod::Stored,
+            };
+
+            let zip = build_zip(&entries, compression).expect("Failed to build zip");
+            let crx = match crx_version {
+                2 => build_crx2(&zip, key),
+                _ => build_crx3(&zip, key),
+            }
+            .expect("Failed to build crx");
+
+            let extension = parse_crx(&crx).expect("Failed to parse crx");
+            prop_assert_eq!(extension.version, crx_version);
+            prop_assert_eq!(&extension.zip, &zip);
+
+            let public_key = key
+                .to_public_key()
+                .to_public_key_der()
+                .expect("Failed to encode key");
+            prop_assert_eq!(
+                get_extension_id(&crx).expect("Failed to compute id"),
+                get_extension_id_from_public_key(public_key.as_bytes())
+            );
+
+            let output_dir = env::current_dir()
+                .expect("Failed to get current directory")
+                .join("out/proptest");
+            let _ = fs::remove_dir_all(&output_dir);
+            unpack_zip(&extension.zip, &output_dir).expect("Failed to unpack zip");
+
+            for (name, content) in &files {
+                let written = fs::read(output_dir.join(name)).expect("Failed to read file");
+                prop_assert_eq!(&written, content);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, fmt, io::Read};
+
+use super::archive::{ensure_not_encrypted, open_archive};
+
+// Only the start of a source file is searched for a license header, where bundlers and
+// minifiers keep it
+const HEADER_LENGTH: u64 = 4096;
+
+const LICENSE_FILE_NAMES: [&str; 4] = ["license", "licence", "copying", "unlicense"];
+const SOURCE_EXTENSIONS: [&str; 5] = ["js", "mjs", "css", "html", "htm"];
+
+// Phrases from the license texts, the first license whose phrases are all found wins, so
+// the more specific ones come first
+const LICENSE_PHRASES: [(&str, &[&str]); 12] = [
+    ("AGPL-3.0", &["GNU AFFERO GENERAL PUBLIC LICENSE"]),
+    (
+        "LGPL-3.0",
+        &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"],
+    ),
+    ("LGPL-2.1", &["GNU LESSER GENERAL PUBLIC LICENSE"]),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "Redistribution and use in source and binary forms",
+            "Neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["Redistribution and use in source and binary forms"],
+    ),
+    (
+        "ISC",
+        &["Permission to use, copy, modify, and/or distribute this software for any purpose"],
+    ),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    (
+        "Unlicense",
+        &["This is free and unencumbered software released into the public domain"],
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseSource {
+    File,
+    Header,
+}
+
+impl fmt::Display for LicenseSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseSource::File => write!(f, "license file"),
+            LicenseSource::Header => write!(f, "header"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedLicense {
+    pub path: String,
+    // An SPDX identifier, or "unknown" for license files matching none of the known texts
+    pub license: String,
+    pub source: LicenseSource,
+}
+
+// LICENSE and COPYING files, whatever their extension, and license headers of scripts,
+// stylesheets and pages
+pub fn find_licenses(zip: &[u8]) -> anyhow::Result<Vec<DetectedLicense>> {
+    ensure_not_encrypted(zip)?;
+
+    let mut archive = open_archive(zip)?;
+    let mut licenses = vec![];
+
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+
+        if file.is_dir() {
+            continue;
+        }
+
+        let path = file.name().to_string();
+        let name = path.rsplit('/').next().unwrap_or_default().to_lowercase();
+        let (stem, extension) = name.split_once('.').unwrap_or((name.as_str(), ""));
+
+        let source = if LICENSE_FILE_NAMES
+            .iter()
+            .any(|prefix| stem.starts_with(prefix))
+        {
+            LicenseSource::File
+        } else if SOURCE_EXTENSIONS.contains(&extension.rsplit('.').next().unwrap_or_default()) {
+            LicenseSource::Header
+        } else {
+            continue;
+        };
+
+        let mut text = vec![];
+        match source {
+            LicenseSource::File => file.take(u64::MAX).read_to_end(&mut text)?,
+            LicenseSource::Header => file.take(HEADER_LENGTH).read_to_end(&mut text)?,
+        };
+
+        let license = match (identify_license(&String::from_utf8_lossy(&text)), source) {
+            (Some(license), _) => license,
+            (None, LicenseSource::File) => "unknown".to_string(),
+            (None, LicenseSource::Header) => continue,
+        };
+
+        licenses.push(DetectedLicense {
+            path,
+            license,
+            source,
+        });
+    }
+
+    Ok(licenses)
+}
+
+// Paths by license, for reports
+pub fn summarize_licenses(licenses: &[DetectedLicense]) -> BTreeMap<String, Vec<String>> {
+    let mut summary: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for license in licenses {
+        summary
+            .entry(license.license.clone())
+            .or_default()
+            .push(license.path.clone());
+    }
+
+    summary
+}
+
+// An explicit SPDX tag or @license annotation wins over the phrases of the full texts
+pub fn identify_license(text: &str) -> Option<String> {
+    for tag in ["SPDX-License-Identifier:", "@license"] {
+        if let Some(position) = text.find(tag) {
+            let identifier: String = text[position + tag.len()..]
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '+'))
+                .collect();
+
+            if !identifier.is_empty() {
+                return Some(identifier);
+            }
+        }
+    }
+
+    // Wrapped lines are joined, license texts break their sentences anywhere
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    LICENSE_PHRASES
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(license, _)| license.to_string())
+}
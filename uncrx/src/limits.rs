@@ -0,0 +1,68 @@
+use super::{errors::UncrxError, types::CrxField};
+
+// Far above the DER encoding of a 16384 bit RSA key and its signature
+pub const DEFAULT_MAX_PUBLIC_KEY_LENGTH: u32 = 64 * 1024;
+pub const DEFAULT_MAX_SIGNATURE_LENGTH: u32 = 64 * 1024;
+// A CRX3 header holds every proof plus the signed data, Chrome itself refuses much less
+pub const DEFAULT_MAX_HEADER_SIZE: u32 = 1024 * 1024;
+
+// Checked against the lengths a header declares before anything is allocated for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderLimits {
+    pub max_public_key_length: u32,
+    pub max_signature_length: u32,
+    pub max_header_size: u32,
+}
+
+impl Default for HeaderLimits {
+    fn default() -> Self {
+        DEFAULT_HEADER_LIMITS
+    }
+}
+
+const DEFAULT_HEADER_LIMITS: HeaderLimits = HeaderLimits {
+    max_public_key_length: DEFAULT_MAX_PUBLIC_KEY_LENGTH,
+    max_signature_length: DEFAULT_MAX_SIGNATURE_LENGTH,
+    max_header_size: DEFAULT_MAX_HEADER_SIZE,
+};
+
+// The public key length field of a CRX3 file holds the size of the whole header
+pub fn check_header_lengths(
+    version: u32,
+    public_key_length: u32,
+    signature_length: u32,
+    limits: &HeaderLimits,
+) -> anyhow::Result<()> {
+    let checks = match version {
+        2 => vec![
+            (
+                CrxField::PublicKeyLength,
+                public_key_length,
+                limits.max_public_key_length,
+            ),
+            (
+                CrxField::SignatureLength,
+                signature_length,
+                limits.max_signature_length,
+            ),
+        ],
+        _ => vec![(
+            CrxField::HeaderSize,
+            public_key_length,
+            limits.max_header_size,
+        )],
+    };
+
+    for (field, length, limit) in checks {
+        if length > limit {
+            return Err(UncrxError::HeaderFieldTooLarge {
+                field,
+                length,
+                limit,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use serde_json::Value;
+
+use super::{archive::read_entry_to_string, manifest::get_manifest};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyOption {
+    pub name: String,
+    pub kind: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+pub fn get_managed_schema(zip: &[u8]) -> anyhow::Result<Option<Value>> {
+    let manifest = get_manifest(zip)?;
+
+    let Some(path) = manifest
+        .pointer("/storage/managed_schema")
+        .and_then(Value::as_str)
+    else {
+        return Ok(None);
+    };
+
+    let schema = read_entry_to_string(zip, path)?;
+
+    Ok(Some(serde_json::from_str(
+        schema.trim_start_matches('\u{feff}'),
+    )?))
+}
+
+pub fn get_policy_options(schema: &Value) -> Vec<PolicyOption> {
+    let mut options = vec![];
+    collect_policy_options(schema, "", &mut options);
+
+    options
+}
+
+fn collect_policy_options(schema: &Value, prefix: &str, options: &mut Vec<PolicyOption>) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+
+    for (name, property) in properties {
+        let name = match prefix {
+            "" => name.clone(),
+            _ => format!("{}.{}", prefix, name),
+        };
+
+        let kind = property
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("any");
+
+        let kind = match (
+            kind,
+            property.pointer("/items/type").and_then(Value::as_str),
+        ) {
+            ("array", Some(item_kind)) => format!("array<{}>", item_kind),
+            _ => kind.to_string(),
+        };
+
+        let text = |key: &str| property.get(key).and_then(Value::as_str).map(String::from);
+
+        options.push(PolicyOption {
+            name: name.clone(),
+            kind,
+            title: text("title"),
+            description: text("description"),
+        });
+
+        collect_policy_options(property, &name, options);
+
+        if let Some(items) = property.get("items") {
+            collect_policy_options(items, &format!("{}[]", name), options);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use serde_json::Value;
+
+use super::{
+    archive::read_entry_to_string,
+    helpers::{get_zip_payload_with_options, ParseOptions},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionBump {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl FromStr for VersionBump {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "major" => Ok(VersionBump::Major),
+            "minor" => Ok(VersionBump::Minor),
+            "patch" => Ok(VersionBump::Patch),
+            _ => Err(format!("expected major, minor or patch, got {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionBump::Major => write!(f, "major"),
+            VersionBump::Minor => write!(f, "minor"),
+            VersionBump::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+pub fn get_manifest(zip: &[u8]) -> anyhow::Result<Value> {
+    parse_manifest(&read_entry_to_string(zip, MANIFEST_FILE_NAME)?)
+}
+
+pub fn parse_manifest(content: &str) -> anyhow::Result<Value> {
+    // Some packers prepend a UTF-8 BOM which serde_json refuses to parse
+    Ok(serde_json::from_str(
+        content.trim_start_matches('\u{feff}'),
+    )?)
+}
+
+pub fn read_manifest(crx: &[u8]) -> anyhow::Result<Value> {
+    read_manifest_with_options(crx, &ParseOptions::default())
+}
+
+pub fn read_manifest_with_options(crx: &[u8], options: &ParseOptions) -> anyhow::Result<Value> {
+    // Only the central directory and the manifest entry are read, the payload is never copied
+    get_manifest(get_zip_payload_with_options(crx, options)?)
+}
+
+pub fn get_manifest_version(manifest: &Value) -> u64 {
+    manifest
+        .get("manifest_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(2)
+}
+
+pub fn get_declared_scripts(manifest: &Value) -> Vec<String> {
+    let mut scripts = vec![];
+
+    if let Some(background) = manifest.get("background") {
+        if let Some(service_worker) = background.get("service_worker").and_then(Value::as_str) {
+            scripts.push(service_worker.to_string());
+        }
+
+        collect_strings(background.get("scripts"), &mut scripts);
+    }
+
+    for content_script in manifest
+        .get("content_scripts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        collect_strings(content_script.get("js"), &mut scripts);
+    }
+
+    scripts.dedup();
+
+    scripts
+}
+
+fn collect_strings(value: Option<&Value>, output: &mut Vec<String>) {
+    output.extend(
+        value
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|value| value.to_string()),
+    );
+}
+
+pub fn bump_version(version: &str, bump: VersionBump) -> anyhow::Result<String> {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u16>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow::anyhow!("Invalid manifest version {}", version))?;
+
+    let index = match bump {
+        VersionBump::Major => 0,
+        VersionBump::Minor => 1,
+        VersionBump::Patch => 2,
+    };
+
+    parts.resize(parts.len().max(index + 1), 0);
+    parts[index] = parts[index]
+        .checked_add(1)
+        .ok_or_else(|| anyhow::anyhow!("Cannot bump {} past 65535", version))?;
+    parts.truncate(index + 1);
+    parts.resize(3, 0);
+
+    Ok(parts
+        .iter()
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+pub fn set_manifest_version(content: &str, version: &str) -> anyhow::Result<String> {
+    let manifest = parse_manifest(content)?;
+    let current = manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("manifest.json has no version"))?;
+
+    // Rewrite the value in place so the rest of the file keeps its formatting
+    for (index, _) in content.match_indices("\"version\"") {
+        let rest = content[index + "\"version\"".len()..].trim_start();
+
+        let Some(value) = rest.strip_prefix(':').map(str::trim_start) else {
+            continue;
+        };
+
+        let quoted = format!("\"{}\"", current);
+
+        if value.starts_with(&quoted) {
+            let start = content.len() - value.len();
+
+            return Ok(format!(
+                "{}\"{}\"{}",
+                &content[..start],
+                version,
+                &content[start + quoted.len()..]
+            ));
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "Failed to locate the version in manifest.json"
+    ))
+}
+
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u32> {
+        version
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+
+    let (mut a, mut b) = (parse(a), parse(b));
+    let length = a.len().max(b.len());
+    a.resize(length, 0);
+    b.resize(length, 0);
+
+    a.cmp(&b)
+}
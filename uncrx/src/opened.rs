@@ -0,0 +1,81 @@
+use std::io::Cursor;
+
+use serde_json::Value;
+use zip::ZipArchive;
+
+use super::{
+    archive::{get_entries_from_records, open_archive, read_entry_from_archive, EntryMetadata},
+    build_info::{read_build_info, BuildInfo},
+    helpers::{get_crx_version, get_zip_payload_with_options, ParseOptions},
+    id::get_extension_id,
+    manifest::{parse_manifest, MANIFEST_FILE_NAME},
+    zip_records::{read_central_directory, ZipRecords},
+};
+
+// Parses the header and the central directory once, for callers reading several things
+// out of the same CRX file
+pub struct OpenedCrx<'a> {
+    data: &'a [u8],
+    zip: &'a [u8],
+    records: ZipRecords,
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+}
+
+impl<'a> OpenedCrx<'a> {
+    pub fn open(data: &'a [u8]) -> anyhow::Result<Self> {
+        Self::open_with_options(data, &ParseOptions::default())
+    }
+
+    pub fn open_with_options(data: &'a [u8], options: &ParseOptions) -> anyhow::Result<Self> {
+        Self::from_zip(data, get_zip_payload_with_options(data, options)?)
+    }
+
+    // For a payload that was repaired, decrypted or stripped after leaving the CRX file
+    pub fn from_zip(data: &'a [u8], zip: &'a [u8]) -> anyhow::Result<Self> {
+        Ok(OpenedCrx {
+            data,
+            zip,
+            records: read_central_directory(zip)?,
+            archive: open_archive(zip)?,
+        })
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn zip(&self) -> &'a [u8] {
+        self.zip
+    }
+
+    pub fn crx_version(&self) -> anyhow::Result<u32> {
+        get_crx_version(self.data)
+    }
+
+    pub fn extension_id(&self) -> anyhow::Result<String> {
+        get_extension_id(self.data)
+    }
+
+    pub fn entries(&self) -> anyhow::Result<Vec<EntryMetadata>> {
+        get_entries_from_records(self.zip, &self.records)
+    }
+
+    pub fn comment(&self) -> String {
+        String::from_utf8_lossy(&self.records.end.comment).into_owned()
+    }
+
+    // Clones of the archive share its parsed central directory
+    pub fn read_entry(&self, name: &str) -> anyhow::Result<Vec<u8>> {
+        read_entry_from_archive(&mut self.archive.clone(), name)
+    }
+
+    pub fn manifest(&self) -> anyhow::Result<Value> {
+        parse_manifest(&String::from_utf8_lossy(
+            &self.read_entry(MANIFEST_FILE_NAME)?,
+        ))
+    }
+
+    pub fn build_info(&self) -> anyhow::Result<Option<BuildInfo>> {
+        read_build_info(&mut self.archive.clone())
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use zip::DateTime;
+
+use super::archive::{ensure_not_encrypted, open_archive, unpack_zip_with_options, UnpackOptions};
+
+pub const DEFAULT_OUTPUT_FORMAT: &str = "zip";
+
+const TAR_BLOCK_SIZE: usize = 512;
+
+// How the zip payload of an extension ends up in the output directory. Formats are looked up
+// by name in an OutputFormats registry, which other crates can add their own formats to
+pub trait OutputFormat: Send + Sync {
+    fn name(&self) -> &str;
+
+    // The file or directory written for an extension in the output directory
+    fn output_path(&self, output_dir: &Path) -> PathBuf;
+
+    fn write(&self, zip: &[u8], output: &Path) -> anyhow::Result<()>;
+}
+
+// The payload as it is
+pub struct ZipFormat;
+
+impl OutputFormat for ZipFormat {
+    fn name(&self) -> &str {
+        "zip"
+    }
+
+    fn output_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("extension.zip")
+    }
+
+    fn write(&self, zip: &[u8], output: &Path) -> anyhow::Result<()> {
+        Ok(fs::write(output, zip)?)
+    }
+}
+
+// The unpacked files, ready to be loaded as an unpacked extension
+#[derive(Default)]
+pub struct DirectoryFormat {
+    pub options: UnpackOptions,
+}
+
+impl OutputFormat for DirectoryFormat {
+    fn name(&self) -> &str {
+        "directory"
+    }
+
+    fn output_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("extension")
+    }
+
+    fn write(&self, zip: &[u8], output: &Path) -> anyhow::Result<()> {
+        unpack_zip_with_options(zip, output, &self.options)?;
+
+        Ok(())
+    }
+}
+
+// An uncompressed ustar archive, for tools that can't read zips
+pub struct TarFormat;
+
+impl OutputFormat for TarFormat {
+    fn name(&self) -> &str {
+        "tar"
+    }
+
+    fn output_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("extension.tar")
+    }
+
+    fn write(&self, zip: &[u8], output: &Path) -> anyhow::Result<()> {
+        let mut writer = BufWriter::new(File::create(output)?);
+        write_tar(zip, &mut writer)?;
+
+        Ok(writer.flush()?)
+    }
+}
+
+// Keeps what would have been written by output path, for library callers that don't want
+// anything on disk
+#[derive(Default)]
+pub struct MemoryFormat {
+    outputs: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemoryFormat {
+    pub fn take(&self, output: &Path) -> Option<Vec<u8>> {
+        self.outputs
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .remove(output)
+    }
+}
+
+impl OutputFormat for MemoryFormat {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn output_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("extension.zip")
+    }
+
+    fn write(&self, zip: &[u8], output: &Path) -> anyhow::Result<()> {
+        self.outputs
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(output.to_path_buf(), zip.to_vec());
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct OutputFormats {
+    formats: Vec<Box<dyn OutputFormat>>,
+}
+
+impl OutputFormats {
+    // The formats writing to disk, the directory one unpacking with the given options
+    pub fn builtin(options: UnpackOptions) -> Self {
+        let mut formats = OutputFormats::default();
+        formats.register(Box::new(ZipFormat));
+        formats.register(Box::new(DirectoryFormat { options }));
+        formats.register(Box::new(TarFormat));
+
+        formats
+    }
+
+    // Replaces the format registered under the same name, if any
+    pub fn register(&mut self, format: Box<dyn OutputFormat>) {
+        self.formats
+            .retain(|existing| existing.name() != format.name());
+        self.formats.push(format);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn OutputFormat> {
+        self.formats
+            .iter()
+            .find(|format| format.name() == name)
+            .map(|format| format.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.formats.iter().map(|format| format.name()).collect()
+    }
+}
+
+// Entries are streamed from the zip one at a time, each padded to the 512 byte blocks of
+// the format, and the archive ends with two empty blocks
+pub fn write_tar(zip: &[u8], writer: &mut impl Write) -> anyhow::Result<()> {
+    ensure_not_encrypted(zip)?;
+
+    let mut archive = open_archive(zip)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow::anyhow!("{} points outside the archive", file.name()))?;
+        let name = path.to_string_lossy().replace('\\', "/");
+
+        let header = TarHeader {
+            name: match file.is_dir() {
+                true => format!("{}/", name),
+                false => name,
+            },
+            mode: file
+                .unix_mode()
+                .map_or(if file.is_dir() { 0o755 } else { 0o644 }, |mode| {
+                    mode & 0o7777
+                }),
+            size: if file.is_dir() { 0 } else { file.size() },
+            modified: file.last_modified().map_or(0, get_unix_time),
+            directory: file.is_dir(),
+        };
+        writer.write_all(&header.encode()?)?;
+
+        if header.directory {
+            continue;
+        }
+
+        let copied = io::copy(&mut file, writer)?;
+        let padding = (TAR_BLOCK_SIZE - copied as usize % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+        writer.write_all(&[0; TAR_BLOCK_SIZE][..padding])?;
+    }
+
+    writer.write_all(&[0; TAR_BLOCK_SIZE * 2])?;
+
+    Ok(())
+}
+
+struct TarHeader {
+    name: String,
+    mode: u32,
+    size: u64,
+    modified: u64,
+    directory: bool,
+}
+
+impl TarHeader {
+    fn encode(&self) -> anyhow::Result<[u8; TAR_BLOCK_SIZE]> {
+        let mut block = [0; TAR_BLOCK_SIZE];
+        let (prefix, name) = split_tar_name(&self.name)?;
+
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        write_octal(&mut block[100..108], self.mode as u64)?;
+        write_octal(&mut block[108..116], 0)?;
+        write_octal(&mut block[116..124], 0)?;
+        write_octal(&mut block[124..136], self.size)?;
+        write_octal(&mut block[136..148], self.modified)?;
+        block[156] = if self.directory { b'5' } else { b'0' };
+        block[257..265].copy_from_slice(b"ustar\x0000");
+        block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+        // Summed with the checksum field itself filled with spaces
+        block[148..156].fill(b' ');
+        let checksum: u32 = block.iter().map(|byte| *byte as u32).sum();
+        write_octal(&mut block[148..155], checksum as u64)?;
+
+        Ok(block)
+    }
+}
+
+// Names longer than 100 bytes are split at a slash, the directories going to the 155 byte
+// prefix field
+fn split_tar_name(name: &str) -> anyhow::Result<(&str, &str)> {
+    if name.len() <= 100 {
+        return Ok(("", name));
+    }
+
+    name.trim_end_matches('/')
+        .match_indices('/')
+        .map(|(index, _)| (&name[..index], &name[index + 1..]))
+        .find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100)
+        .ok_or_else(|| anyhow::anyhow!("{} is too long for a tar archive", name))
+}
+
+// Zero padded and followed by a NUL, as most readers expect
+fn write_octal(field: &mut [u8], value: u64) -> anyhow::Result<()> {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+
+    if digits.len() >= field.len() {
+        return Err(anyhow::anyhow!("{} does not fit a tar header field", value));
+    }
+
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+
+    Ok(())
+}
+
+// Zip times carry no time zone, they are taken as UTC
+fn get_unix_time(time: DateTime) -> u64 {
+    let (year, month, day) = (time.year() as i64, time.month() as i64, time.day() as i64);
+
+    // Days since 1970-01-01 of the proleptic Gregorian calendar, years starting in March
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds =
+        days * 86400 + time.hour() as i64 * 3600 + time.minute() as i64 * 60 + time.second() as i64;
+
+    seconds.max(0) as u64
+}
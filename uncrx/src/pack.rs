@@ -0,0 +1,315 @@
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use ignore::{overrides::OverrideBuilder, Walk, WalkBuilder};
+use rsa::{
+    pkcs1::DecodeRsaPrivateKey,
+    pkcs1v15::SigningKey,
+    pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding},
+    signature::{SignatureEncoding, Signer},
+    RsaPrivateKey,
+};
+use sha2::{Digest, Sha256};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::{
+    build_info::{BuildInfo, BUILD_INFO_FILE_NAME},
+    constants::{
+        CRX3_CRX_ID_FIELD, CRX3_PUBLIC_KEY_FIELD, CRX3_SHA256_WITH_RSA_FIELD, CRX3_SIGNATURE_FIELD,
+        CRX3_SIGNED_HEADER_DATA_FIELD, CRX_ID_LENGTH, CRX_MAGIC_VALUE,
+    },
+    helpers::{get_zip_payload_with_options, ParseOptions},
+    manifest::{
+        bump_version, parse_manifest, set_manifest_version, VersionBump, MANIFEST_FILE_NAME,
+    },
+    protobuf::write_bytes_field,
+};
+
+pub const CRX_IGNORE_FILE_NAME: &str = ".crxignore";
+pub const CRX3_SIGNATURE_CONTEXT: &[u8] = b"CRX3 SignedData\x00";
+pub const RSA_KEY_BITS: usize = 2048;
+
+// Never shipped, whatever the ignore files say: VCS metadata, OS junk and signing keys
+pub const DEFAULT_EXCLUDES: [&str; 7] = [
+    ".git/",
+    ".DS_Store",
+    "Thumbs.db",
+    ".crxignore",
+    ".gitignore",
+    "*.pem",
+    "*.crx",
+];
+
+// Written by Chrome when installing, verified_contents.json is signed by the Web Store and
+// would not match a repacked extension
+pub const INSTALLED_EXCLUDES: [&str; 1] = ["_metadata/"];
+
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub build_info: Option<BuildInfo>,
+    // Packs a directory from a Chrome profile, leaving out what Chrome added when installing
+    pub installed: bool,
+    // Packed in place of the manifest.json of the directory, e.g. one with a bumped version
+    // that is only written once the pack succeeded
+    pub manifest: Option<String>,
+    // Parses the reference CRX file of verify_reproducible
+    pub parse: ParseOptions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackEntry {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+pub struct PackEntries {
+    root: PathBuf,
+    walk: Walk,
+}
+
+impl Iterator for PackEntries {
+    type Item = anyhow::Result<PackEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.walk.next()? {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error.into())),
+            };
+
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+
+            let name = entry
+                .path()
+                .strip_prefix(&self.root)
+                .map(|relative| {
+                    relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                })
+                .map_err(anyhow::Error::from);
+
+            return Some(name.map(|name| PackEntry {
+                path: entry.into_path(),
+                name,
+            }));
+        }
+    }
+}
+
+pub fn pack_entries(directory: &Path, options: &PackOptions) -> anyhow::Result<PackEntries> {
+    let mut overrides = OverrideBuilder::new(directory);
+
+    for pattern in &options.include {
+        overrides.add(pattern)?;
+    }
+
+    let installed: &[&str] = match options.installed {
+        true => &INSTALLED_EXCLUDES,
+        false => &[],
+    };
+
+    for pattern in DEFAULT_EXCLUDES
+        .iter()
+        .chain(installed)
+        .copied()
+        .map(String::from)
+        .chain(options.exclude.clone())
+    {
+        overrides.add(&format!("!{}", pattern))?;
+    }
+
+    let walk = WalkBuilder::new(directory)
+        .hidden(false)
+        .parents(false)
+        .git_global(false)
+        .git_exclude(false)
+        .require_git(false)
+        .add_custom_ignore_filename(CRX_IGNORE_FILE_NAME)
+        .overrides(overrides.build()?)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    Ok(PackEntries {
+        root: directory.to_path_buf(),
+        walk,
+    })
+}
+
+pub fn zip_directory(directory: &Path, options: &PackOptions) -> anyhow::Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    // Fixed timestamps and permissions keep the payload byte-identical across builds
+    let file_options = SimpleFileOptions::DEFAULT
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+
+    for entry in pack_entries(directory, options)? {
+        let entry = entry?;
+
+        let mut content = match &options.manifest {
+            Some(manifest) if entry.name == MANIFEST_FILE_NAME => manifest.clone().into_bytes(),
+            _ => fs::read(&entry.path)?,
+        };
+
+        if options.installed && entry.name == MANIFEST_FILE_NAME {
+            content = remove_manifest_key(&String::from_utf8(content)?)?.into_bytes();
+        }
+
+        writer.start_file(entry.name.as_str(), file_options)?;
+        writer.write_all(&content)?;
+    }
+
+    if let Some(build_info) = &options.build_info {
+        writer.start_file(BUILD_INFO_FILE_NAME, file_options)?;
+        serde_json::to_writer_pretty(&mut writer, build_info)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+// Chrome adds the public key of the installed CRX to the manifest, which would pin the
+// extension id to a key the repacked CRX is not signed with
+pub fn remove_manifest_key(content: &str) -> anyhow::Result<String> {
+    let mut manifest = parse_manifest(content)?;
+
+    match manifest
+        .as_object_mut()
+        .and_then(|object| object.remove("key"))
+    {
+        Some(_) => Ok(serde_json::to_string_pretty(&manifest)? + "\n"),
+        None => Ok(content.to_string()),
+    }
+}
+
+pub fn verify_reproducible(
+    directory: &Path,
+    options: &PackOptions,
+    reference: Option<&[u8]>,
+) -> anyhow::Result<Vec<u8>> {
+    let zip = zip_directory(directory, options)?;
+
+    let (expected, source) = match reference {
+        Some(crx) => (
+            get_zip_payload_with_options(crx, &options.parse)?.to_vec(),
+            "the reference CRX",
+        ),
+        None => (zip_directory(directory, options)?, "a second build"),
+    };
+
+    if let Some(offset) = find_first_difference(&zip, &expected) {
+        return Err(anyhow::anyhow!(
+            "Zip payload differs from {} at byte {} ({} vs {} bytes)",
+            source,
+            offset,
+            zip.len(),
+            expected.len()
+        ));
+    }
+
+    Ok(zip)
+}
+
+fn find_first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+pub fn generate_private_key() -> anyhow::Result<RsaPrivateKey> {
+    Ok(RsaPrivateKey::new(&mut rand::thread_rng(), RSA_KEY_BITS)?)
+}
+
+pub fn read_private_key(pem: &str) -> anyhow::Result<RsaPrivateKey> {
+    RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+        .map_err(|_| anyhow::anyhow!("Unsupported private key, expected an RSA key in PEM format"))
+}
+
+pub fn encode_private_key(key: &RsaPrivateKey) -> anyhow::Result<String> {
+    Ok(key.to_pkcs8_pem(LineEnding::LF)?.to_string())
+}
+
+pub fn build_crx3(zip: &[u8], key: &RsaPrivateKey) -> anyhow::Result<Vec<u8>> {
+    let public_key = key.to_public_key().to_public_key_der()?.into_vec();
+
+    let crx_id = &Sha256::digest(&public_key)[..CRX_ID_LENGTH];
+
+    let mut signed_data = vec![];
+    write_bytes_field(&mut signed_data, CRX3_CRX_ID_FIELD, crx_id);
+
+    let mut message = CRX3_SIGNATURE_CONTEXT.to_vec();
+    message.extend_from_slice(&(signed_data.len() as u32).to_le_bytes());
+    message.extend_from_slice(&signed_data);
+    message.extend_from_slice(zip);
+
+    let signature = SigningKey::<Sha256>::new(key.clone())
+        .try_sign(&message)?
+        .to_vec();
+
+    let mut proof = vec![];
+    write_bytes_field(&mut proof, CRX3_PUBLIC_KEY_FIELD, &public_key);
+    write_bytes_field(&mut proof, CRX3_SIGNATURE_FIELD, &signature);
+
+    let mut header = vec![];
+    write_bytes_field(&mut header, CRX3_SHA256_WITH_RSA_FIELD, &proof);
+    write_bytes_field(&mut header, CRX3_SIGNED_HEADER_DATA_FIELD, &signed_data);
+
+    let mut crx = Vec::with_capacity(12 + header.len() + zip.len());
+    crx.extend_from_slice(&CRX_MAGIC_VALUE);
+    crx.extend_from_slice(&3u32.to_le_bytes());
+    crx.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    crx.extend_from_slice(&header);
+    crx.extend_from_slice(zip);
+
+    Ok(crx)
+}
+
+pub fn pack_directory(
+    directory: &Path,
+    key: &RsaPrivateKey,
+    options: &PackOptions,
+) -> anyhow::Result<Vec<u8>> {
+    let zip = zip_directory(directory, options)?;
+
+    build_crx3(&zip, key)
+}
+
+pub fn bump_manifest_version(directory: &Path, bump: VersionBump) -> anyhow::Result<String> {
+    let (version, content) = get_bumped_manifest(directory, bump)?;
+    fs::write(directory.join(MANIFEST_FILE_NAME), content)?;
+
+    Ok(version)
+}
+
+// The new version and the manifest.json content holding it, nothing is written
+pub fn get_bumped_manifest(
+    directory: &Path,
+    bump: VersionBump,
+) -> anyhow::Result<(String, String)> {
+    let content = fs::read_to_string(directory.join(MANIFEST_FILE_NAME))?;
+
+    let manifest = parse_manifest(&content)?;
+    let version = manifest
+        .get("version")
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("manifest.json has no version"))?;
+
+    let version = bump_version(version, bump)?;
+    let content = set_manifest_version(&content, &version)?;
+
+    Ok((version, content))
+}
@@ -0,0 +1,230 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileModes {
+    pub file: u32,
+    pub directory: u32,
+}
+
+impl FileModes {
+    // Directories get the execute bit wherever the file mode grants read, as chmod's X does
+    pub fn from_file_mode(file: u32) -> Self {
+        let searchable = (file & 0o444) >> 2;
+
+        FileModes {
+            file,
+            directory: file | searchable,
+        }
+    }
+}
+
+impl FromStr for FileModes {
+    type Err = String;
+
+    // Either <FILE>/<DIRECTORY>, e.g. 0644/0755, or a single file mode
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse = |mode: &str| {
+            u32::from_str_radix(mode, 8)
+                .ok()
+                .filter(|mode| *mode <= 0o7777)
+                .ok_or_else(|| format!("{} is not an octal file mode", mode))
+        };
+
+        match value.split_once('/') {
+            Some((file, directory)) => Ok(FileModes {
+                file: parse(file)?,
+                directory: parse(directory)?,
+            }),
+            None => Ok(FileModes::from_file_mode(parse(value)?)),
+        }
+    }
+}
+
+impl fmt::Display for FileModes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04o}/{:04o}", self.file, self.directory)
+    }
+}
+
+// Applies the modes to a file, or to a directory and everything below it
+pub fn set_file_modes(path: &Path, modes: &FileModes) -> anyhow::Result<()> {
+    walk(path, &mut |path, is_dir| match is_dir {
+        true => set_mode(path, modes.directory),
+        false => set_mode(path, modes.file),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ownership {
+    pub user: Option<u32>,
+    pub group: Option<u32>,
+}
+
+impl FromStr for Ownership {
+    type Err = String;
+
+    // USER, USER:GROUP or :GROUP, each a name or a numeric id
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (user, group) = match value.split_once(':') {
+            Some((user, group)) => (user, group),
+            None => (value, ""),
+        };
+
+        let user = match user {
+            "" => None,
+            user => Some(
+                user.parse()
+                    .ok()
+                    .or_else(|| get_user_id(user))
+                    .ok_or_else(|| format!("Unknown user {}", user))?,
+            ),
+        };
+
+        let group = match group {
+            "" => None,
+            group => Some(
+                group
+                    .parse()
+                    .ok()
+                    .or_else(|| get_group_id(group))
+                    .ok_or_else(|| format!("Unknown group {}", group))?,
+            ),
+        };
+
+        if user.is_none() && group.is_none() {
+            return Err("Expected USER, USER:GROUP or :GROUP".to_string());
+        }
+
+        Ok(Ownership { user, group })
+    }
+}
+
+// Only root may give files away, other users can at most pick one of their own groups
+pub fn set_ownership(path: &Path, ownership: &Ownership) -> anyhow::Result<()> {
+    walk(path, &mut |path, _| set_owner(path, ownership))
+}
+
+// Changing the owner may clear the setuid and setgid bits, so modes are applied last
+pub fn apply_permissions(
+    path: &Path,
+    modes: Option<&FileModes>,
+    ownership: Option<&Ownership>,
+) -> anyhow::Result<()> {
+    if let Some(ownership) = ownership {
+        set_ownership(path, ownership)?;
+    }
+
+    if let Some(modes) = modes {
+        set_file_modes(path, modes)?;
+    }
+
+    Ok(())
+}
+
+// Symbolic links are skipped, their target may well lie outside the tree. A directory is
+// only changed once its entries are, so a mode without read or search permission doesn't
+// lock the walk out of it
+fn walk(
+    path: &Path,
+    apply: &mut dyn FnMut(&Path, bool) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+
+    if metadata.is_symlink() {
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            walk(&entry?.path(), apply)?;
+        }
+    }
+
+    apply(path, metadata.is_dir())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+
+    Ok(())
+}
+
+// Only the read-only flag exists elsewhere, derived from the owner write bit
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> anyhow::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+    fs::set_permissions(path, permissions)?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_owner(path: &Path, ownership: &Ownership) -> anyhow::Result<()> {
+    std::os::unix::fs::lchown(path, ownership.user, ownership.group)
+        .map_err(|error| anyhow::anyhow!("Failed to change owner of {}: {}", path.display(), error))
+}
+
+#[cfg(not(unix))]
+fn set_owner(_path: &Path, _ownership: &Ownership) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "Changing ownership is only supported on Unix"
+    ))
+}
+
+// Large enough for any passwd or group entry outside of huge group member lists
+#[cfg(unix)]
+const LOOKUP_BUFFER_SIZE: usize = 64 * 1024;
+
+#[cfg(unix)]
+fn get_user_id(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+
+    let code = unsafe {
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut entry,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+
+    (code == 0 && !result.is_null()).then_some(entry.pw_uid)
+}
+
+#[cfg(unix)]
+fn get_group_id(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    let mut buffer = vec![0 as libc::c_char; LOOKUP_BUFFER_SIZE];
+    let mut entry: libc::group = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+
+    let code = unsafe {
+        libc::getgrnam_r(
+            name.as_ptr(),
+            &mut entry,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+
+    (code == 0 && !result.is_null()).then_some(entry.gr_gid)
+}
+
+#[cfg(not(unix))]
+fn get_user_id(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(not(unix))]
+fn get_group_id(_name: &str) -> Option<u32> {
+    None
+}
@@ -0,0 +1,92 @@
+use std::{fmt, str::FromStr};
+
+use super::update::escape_xml;
+
+pub const WEB_STORE_UPDATE_URL: &str = "https://clients2.google.com/service/update2/crx";
+pub const FORCE_INSTALL_POLICY: &str = "ExtensionInstallForcelist";
+pub const REGISTRY_POLICY_KEY: &str = r"HKEY_LOCAL_MACHINE\SOFTWARE\Policies\Google\Chrome";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyFormat {
+    Json,
+    Registry,
+    Plist,
+}
+
+impl FromStr for PolicyFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(PolicyFormat::Json),
+            "registry" | "reg" => Ok(PolicyFormat::Registry),
+            "plist" => Ok(PolicyFormat::Plist),
+            _ => Err(format!("expected json, registry or plist, got {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for PolicyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyFormat::Json => write!(f, "json"),
+            PolicyFormat::Registry => write!(f, "registry"),
+            PolicyFormat::Plist => write!(f, "plist"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForceInstallEntry {
+    pub id: String,
+    pub update_url: String,
+}
+
+impl fmt::Display for ForceInstallEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{};{}", self.id, self.update_url)
+    }
+}
+
+pub fn generate_force_install_policy(
+    entries: &[ForceInstallEntry],
+    format: PolicyFormat,
+) -> String {
+    let values: Vec<String> = entries.iter().map(|entry| entry.to_string()).collect();
+
+    match format {
+        PolicyFormat::Json => {
+            let policy = serde_json::json!({ FORCE_INSTALL_POLICY: values });
+            format!("{:#}\n", policy)
+        }
+        PolicyFormat::Registry => {
+            let mut registry = format!(
+                "Windows Registry Editor Version 5.00\r\n\r\n[{}\\{}]\r\n",
+                REGISTRY_POLICY_KEY, FORCE_INSTALL_POLICY
+            );
+
+            for (index, value) in values.iter().enumerate() {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                registry.push_str(&format!("\"{}\"=\"{}\"\r\n", index + 1, value));
+            }
+
+            registry
+        }
+        PolicyFormat::Plist => {
+            let mut plist = format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+                 <plist version=\"1.0\">\n<dict>\n  <key>{}</key>\n  <array>\n",
+                FORCE_INSTALL_POLICY
+            );
+
+            for value in &values {
+                plist.push_str(&format!("    <string>{}</string>\n", escape_xml(value)));
+            }
+
+            plist.push_str("  </array>\n</dict>\n</plist>\n");
+
+            plist
+        }
+    }
+}
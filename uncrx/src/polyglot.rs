@@ -0,0 +1,110 @@
+use std::fmt;
+
+use super::{
+    helpers::{get_zip_start_offset_with_options, ParseOptions},
+    zip_records::read_central_directory,
+};
+
+// Browsers sniff markup in the first 512 bytes and PDF readers accept a header anywhere
+// in the first kilobyte, both windows fall within the CRX header
+const HTML_SNIFF_WINDOW: usize = 512;
+const PDF_SEARCH_WINDOW: usize = 1024;
+
+const HTML_SIGNATURES: [&[u8]; 6] = [
+    b"<!doctype html",
+    b"<html",
+    b"<head",
+    b"<body",
+    b"<script",
+    b"<iframe",
+];
+const PDF_SIGNATURE: &[u8] = b"%PDF-";
+const JAR_MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyglotFormat {
+    Html,
+    Pdf,
+    Jar,
+}
+
+impl fmt::Display for PolyglotFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolyglotFormat::Html => write!(f, "HTML"),
+            PolyglotFormat::Pdf => write!(f, "PDF"),
+            PolyglotFormat::Jar => write!(f, "JAR"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyglotMatch {
+    pub format: PolyglotFormat,
+    pub offset: usize,
+}
+
+impl fmt::Display for PolyglotMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file is also valid {}, signature at byte {}",
+            self.format, self.offset
+        )
+    }
+}
+
+pub fn find_polyglot_formats(data: &[u8]) -> anyhow::Result<Vec<PolyglotMatch>> {
+    find_polyglot_formats_with_options(data, &ParseOptions::default())
+}
+
+pub fn find_polyglot_formats_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> anyhow::Result<Vec<PolyglotMatch>> {
+    let mut matches = vec![];
+
+    let window = &data[..data.len().min(HTML_SNIFF_WINDOW)];
+    let html = HTML_SIGNATURES
+        .iter()
+        .filter_map(|signature| find_ignore_case(window, signature))
+        .min();
+
+    if let Some(offset) = html {
+        matches.push(PolyglotMatch {
+            format: PolyglotFormat::Html,
+            offset,
+        });
+    }
+
+    let window = &data[..data.len().min(PDF_SEARCH_WINDOW)];
+
+    if let Some(offset) = find_ignore_case(window, PDF_SIGNATURE) {
+        matches.push(PolyglotMatch {
+            format: PolyglotFormat::Pdf,
+            offset,
+        });
+    }
+
+    // Every CRX is a zip, a Java manifest is what makes the JVM run it as a JAR
+    let zip_start_offset = get_zip_start_offset_with_options(data, options)?;
+    let records = read_central_directory(&data[zip_start_offset..])?;
+
+    if let Some(record) = records
+        .entries
+        .iter()
+        .find(|record| record.name() == JAR_MANIFEST)
+    {
+        matches.push(PolyglotMatch {
+            format: PolyglotFormat::Jar,
+            offset: zip_start_offset + records.local_header_offset(record),
+        });
+    }
+
+    Ok(matches)
+}
+
+fn find_ignore_case(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
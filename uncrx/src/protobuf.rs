@@ -0,0 +1,111 @@
+pub const WIRE_TYPE_VARINT: u8 = 0;
+pub const WIRE_TYPE_LENGTH_DELIMITED: u8 = 2;
+
+pub fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+
+    output.push(value as u8);
+}
+
+pub fn write_bytes_field(output: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_varint(
+        output,
+        ((field as u64) << 3) | WIRE_TYPE_LENGTH_DELIMITED as u64,
+    );
+    write_varint(output, bytes.len() as u64);
+    output.extend_from_slice(bytes);
+}
+
+pub const WIRE_TYPE_FIXED64: u8 = 1;
+pub const WIRE_TYPE_FIXED32: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(&'a [u8]),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<'a> {
+    pub number: u32,
+    pub value: FieldValue<'a>,
+}
+
+pub fn read_varint(data: &[u8], position: &mut usize) -> anyhow::Result<u64> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let byte = *data
+            .get(*position)
+            .ok_or_else(|| anyhow::anyhow!("Truncated protobuf varint at byte {}", position))?;
+        *position += 1;
+
+        value |= ((byte & 0x7f) as u64) << shift;
+
+        if byte < 0x80 {
+            return Ok(value);
+        }
+    }
+
+    Err(anyhow::anyhow!("Protobuf varint is too long"))
+}
+
+fn read_slice<'a>(data: &'a [u8], position: &mut usize, length: usize) -> anyhow::Result<&'a [u8]> {
+    let end = position
+        .checked_add(length)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| anyhow::anyhow!("Truncated protobuf field at byte {}", position))?;
+
+    let slice = &data[*position..end];
+    *position = end;
+
+    Ok(slice)
+}
+
+pub fn read_fields(data: &[u8]) -> anyhow::Result<Vec<Field<'_>>> {
+    let mut fields = vec![];
+    let mut position = 0;
+
+    while position < data.len() {
+        let key = read_varint(data, &mut position)?;
+        let number = (key >> 3) as u32;
+
+        let value = match (key & 0x7) as u8 {
+            WIRE_TYPE_VARINT => FieldValue::Varint(read_varint(data, &mut position)?),
+            WIRE_TYPE_FIXED64 => {
+                let bytes = read_slice(data, &mut position, 8)?;
+                FieldValue::Fixed64(u64::from_le_bytes(bytes.try_into()?))
+            }
+            WIRE_TYPE_LENGTH_DELIMITED => {
+                let length = read_varint(data, &mut position)? as usize;
+                FieldValue::Bytes(read_slice(data, &mut position, length)?)
+            }
+            WIRE_TYPE_FIXED32 => {
+                let bytes = read_slice(data, &mut position, 4)?;
+                FieldValue::Fixed32(u32::from_le_bytes(bytes.try_into()?))
+            }
+            wire_type => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported protobuf wire type {}",
+                    wire_type
+                ))
+            }
+        };
+
+        fields.push(Field { number, value });
+    }
+
+    Ok(fields)
+}
+
+pub fn find_bytes_field<'a>(fields: &[Field<'a>], number: u32) -> Option<&'a [u8]> {
+    fields.iter().find_map(|field| match field.value {
+        FieldValue::Bytes(bytes) if field.number == number => Some(bytes),
+        _ => None,
+    })
+}
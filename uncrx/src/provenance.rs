@@ -0,0 +1,183 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    archive::{ensure_not_encrypted, open_archive},
+    hash::HashAlgorithm,
+    id::get_extension_id,
+    manifest::get_manifest,
+};
+
+pub const EXTRACTION_MANIFEST_FILE_NAME: &str = ".uncrx-manifest.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractionManifest {
+    // Manifests written before the algorithm was configurable all used SHA-256
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    #[serde(alias = "source_sha256")]
+    pub source_hash: String,
+    pub extension_id: Option<String>,
+    pub version: Option<String>,
+    pub tool_version: String,
+    // Hash of every written file, keyed by its path relative to the manifest
+    pub files: BTreeMap<String, String>,
+}
+
+impl ExtractionManifest {
+    pub fn new(
+        source: &[u8],
+        zip: &[u8],
+        files: BTreeMap<String, String>,
+        hash_algorithm: HashAlgorithm,
+    ) -> Self {
+        let version = get_manifest(zip)
+            .ok()
+            .and_then(|manifest| manifest["version"].as_str().map(str::to_string));
+
+        ExtractionManifest {
+            hash_algorithm,
+            source_hash: hash_algorithm.hash(source),
+            extension_id: get_extension_id(source).ok(),
+            version,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            files,
+        }
+    }
+}
+
+// Hashes of the files an unpacked zip consists of, directories have none
+pub fn get_entry_hashes(
+    zip: &[u8],
+    hash_algorithm: HashAlgorithm,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    ensure_not_encrypted(zip)?;
+
+    let mut archive = open_archive(zip)?;
+    let mut hashes = BTreeMap::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+
+        if file.is_dir() {
+            continue;
+        }
+
+        let name = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow::anyhow!("{} points outside the archive", file.name()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        hashes.insert(name, hash_algorithm.hash_reader(&mut file)?);
+    }
+
+    Ok(hashes)
+}
+
+pub fn write_extraction_manifest(
+    directory: &Path,
+    manifest: &ExtractionManifest,
+) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+
+    fs::write(directory.join(EXTRACTION_MANIFEST_FILE_NAME), json + "\n")?;
+
+    Ok(())
+}
+
+pub fn read_extraction_manifest(directory: &Path) -> anyhow::Result<ExtractionManifest> {
+    let json = fs::read_to_string(directory.join(EXTRACTION_MANIFEST_FILE_NAME))?;
+
+    Ok(serde_json::from_str(&json)?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeChangeKind {
+    Added,
+    Modified,
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeChange {
+    pub kind: TreeChangeKind,
+    pub path: String,
+}
+
+impl fmt::Display for TreeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            TreeChangeKind::Added => "added",
+            TreeChangeKind::Modified => "modified",
+            TreeChangeKind::Missing => "missing",
+        };
+
+        write!(f, "{} {}", kind, self.path)
+    }
+}
+
+// Compares the files on disk with the hashes recorded at extraction time, the extraction
+// manifest itself is not part of the tree
+pub fn find_tree_changes(
+    directory: &Path,
+    files: &BTreeMap<String, String>,
+    hash_algorithm: HashAlgorithm,
+) -> anyhow::Result<Vec<TreeChange>> {
+    let mut on_disk = BTreeMap::new();
+    collect_file_hashes(directory, "", hash_algorithm, &mut on_disk)?;
+    on_disk.remove(EXTRACTION_MANIFEST_FILE_NAME);
+
+    let mut changes = vec![];
+
+    for (path, hash) in files {
+        let kind = match on_disk.get(path) {
+            None => TreeChangeKind::Missing,
+            Some(found) if found != hash => TreeChangeKind::Modified,
+            Some(_) => continue,
+        };
+
+        changes.push(TreeChange {
+            kind,
+            path: path.clone(),
+        });
+    }
+
+    for path in on_disk.keys().filter(|path| !files.contains_key(*path)) {
+        changes.push(TreeChange {
+            kind: TreeChangeKind::Added,
+            path: path.clone(),
+        });
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(changes)
+}
+
+fn collect_file_hashes(
+    directory: &Path,
+    prefix: &str,
+    hash_algorithm: HashAlgorithm,
+    hashes: &mut BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+
+        if entry.file_type()?.is_dir() {
+            collect_file_hashes(&entry.path(), &format!("{}/", name), hash_algorithm, hashes)?;
+        } else {
+            let hash = hash_algorithm.hash_reader(&mut File::open(entry.path())?)?;
+            hashes.insert(name, hash);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    archive::unpack_zip,
+    staging::{get_temp_dir, StagingDir},
+};
+
+pub const DEFAULT_SMOKE_TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Searched in PATH when no browser is given, branded Chrome ignores --load-extension since
+// version 137 so Chromium and Chrome for Testing come first
+pub const BROWSER_NAMES: [&str; 5] = [
+    "chromium",
+    "chromium-browser",
+    "chrome",
+    "google-chrome",
+    "google-chrome-stable",
+];
+
+// Logged by Chrome when an unpacked extension is refused, followed by the reason
+const LOAD_ERRORS: [&str; 2] = ["Failed to load extension", "Could not load extension"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmokeTestResult {
+    Loaded,
+    Rejected(String),
+}
+
+pub fn find_browser() -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+
+    env::split_paths(&path).find_map(|directory| {
+        BROWSER_NAMES
+            .iter()
+            .map(|name| directory.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+// Loads the unpacked payload into a headless browser with a throwaway profile, the extension
+// counts as loaded when no error was logged before the timeout
+pub fn smoke_test(
+    zip: &[u8],
+    browser: &Path,
+    timeout: Duration,
+) -> anyhow::Result<SmokeTestResult> {
+    let directory = StagingDir::create_in(&get_temp_dir(&env::temp_dir()), "uncrx-smoke-test-")?;
+    let extension_dir = directory.path().join("extension");
+
+    unpack_zip(zip, &extension_dir)?;
+
+    run_browser(
+        browser,
+        &extension_dir,
+        &directory.path().join("profile"),
+        timeout,
+    )
+}
+
+fn run_browser(
+    browser: &Path,
+    extension_dir: &Path,
+    profile_dir: &Path,
+    timeout: Duration,
+) -> anyhow::Result<SmokeTestResult> {
+    let mut child = Command::new(browser)
+        .arg("--headless=new")
+        .arg("--no-first-run")
+        .arg("--disable-gpu")
+        .arg("--enable-logging=stderr")
+        .arg(format!("--user-data-dir={}", profile_dir.display()))
+        .arg(format!(
+            "--disable-extensions-except={}",
+            extension_dir.display()
+        ))
+        .arg(format!("--load-extension={}", extension_dir.display()))
+        .arg("about:blank")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| anyhow::anyhow!("Failed to run {}: {}", browser.display(), error))?;
+
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let (sender, receiver) = mpsc::channel();
+
+    // Not joined, helper processes may keep the pipe open after the browser is stopped
+    thread::spawn(move || {
+        let error = BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+            .find(|line| LOAD_ERRORS.iter().any(|error| line.contains(error)));
+
+        if let Some(error) = error {
+            let _ = sender.send(error);
+        }
+    });
+
+    let start = Instant::now();
+
+    // The browser keeps running whatever happens to the extension
+    let status = loop {
+        if let Ok(error) = receiver.try_recv() {
+            child.kill()?;
+            child.wait()?;
+            return Ok(SmokeTestResult::Rejected(error));
+        }
+
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(SmokeTestResult::Loaded);
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    // Errors logged right before exiting may still be in the pipe
+    if let Ok(error) = receiver.recv_timeout(Duration::from_millis(500)) {
+        return Ok(SmokeTestResult::Rejected(error));
+    }
+
+    match status.success() {
+        true => Ok(SmokeTestResult::Loaded),
+        false => Err(anyhow::anyhow!(
+            "{} exited with {} before loading the extension",
+            browser.display(),
+            status
+        )),
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::RwLock,
+};
+
+// Set from --tmpdir, for hosts whose system temporary directory is too small
+static TEMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanFailure {
+    pub scanner: String,
+    pub status: Option<i32>,
+    pub output: String,
+}
+
+pub fn set_temp_dir(directory: Option<PathBuf>) {
+    *TEMP_DIR.write().unwrap_or_else(|error| error.into_inner()) = directory;
+}
+
+// The directory set with set_temp_dir, then TMPDIR, then the default of the caller
+pub fn get_temp_dir(default: &Path) -> PathBuf {
+    let directory = TEMP_DIR
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+
+    directory
+        .or_else(|| {
+            env::var_os("TMPDIR")
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        })
+        .unwrap_or_else(|| default.to_path_buf())
+}
+
+// Created next to the destination unless a temporary directory is configured
+pub fn create_staging_dir(parent: &Path) -> anyhow::Result<StagingDir> {
+    StagingDir::create_in(&get_temp_dir(parent), ".uncrx-staging-")
+}
+
+// Removed with everything in it when dropped, panics included, unless kept
+pub struct StagingDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl StagingDir {
+    // Created atomically under a random name, so other users of a shared directory like /tmp
+    // can neither predict it nor plant a link in its place. Only the owner can enter it
+    pub fn create_in(directory: &Path, prefix: &str) -> anyhow::Result<Self> {
+        fs::create_dir_all(directory)?;
+
+        let path = tempfile::Builder::new()
+            .prefix(prefix)
+            .tempdir_in(directory)?
+            .keep();
+
+        Ok(StagingDir { path, keep: false })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+// Scanners are command lines run with the staged directory as their last argument, a
+// non-zero exit status rejects the extraction
+pub fn run_scanners(directory: &Path, scanners: &[String]) -> anyhow::Result<Vec<ScanFailure>> {
+    let mut failures = vec![];
+
+    for scanner in scanners {
+        let mut words = scanner.split_whitespace();
+
+        let program = words
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty scanner command"))?;
+
+        let output = Command::new(program)
+            .args(words)
+            .arg(directory)
+            .output()
+            .map_err(|error| anyhow::anyhow!("Failed to run {}: {}", program, error))?;
+
+        if !output.status.success() {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+            failures.push(ScanFailure {
+                scanner: scanner.clone(),
+                status: output.status.code(),
+                output: text.trim().to_string(),
+            });
+        }
+    }
+
+    Ok(failures)
+}
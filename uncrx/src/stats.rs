@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{
+    archive::get_entries,
+    helpers::{is_crx_path, parse_crx_with_options, ParseOptions},
+    manifest::{get_manifest, parse_manifest, MANIFEST_FILE_NAME},
+    types::CrxExtension,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CorpusStats {
+    pub extensions: usize,
+    pub manifest_versions: BTreeMap<u64, usize>,
+    // Most requested first
+    pub permissions: Vec<Count>,
+    pub average_size: u64,
+    // Only known for CRX files, unpacked extensions carry no signature
+    pub signature_algorithms: BTreeMap<String, usize>,
+    // CRX files or manifests that failed to parse
+    pub skipped: usize,
+}
+
+// Accumulates extensions handed over as bytes or parsed manifests, without touching the
+// filesystem, collect_corpus_stats feeds it from a directory
+#[derive(Debug, Clone, Default)]
+pub struct StatsCollector {
+    extensions: usize,
+    manifest_versions: BTreeMap<u64, usize>,
+    permissions: BTreeMap<String, usize>,
+    size: u64,
+    signature_algorithms: BTreeMap<String, usize>,
+    skipped: usize,
+    parse: ParseOptions,
+}
+
+impl StatsCollector {
+    pub fn with_options(options: ParseOptions) -> Self {
+        StatsCollector {
+            parse: options,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_manifest(&mut self, manifest: &Value, size: u64) {
+        self.extensions += 1;
+        self.size += size;
+
+        if let Some(version) = manifest["manifest_version"].as_u64() {
+            *self.manifest_versions.entry(version).or_default() += 1;
+        }
+
+        let permissions = manifest["permissions"].as_array().into_iter().flatten();
+
+        for permission in permissions.filter_map(Value::as_str) {
+            *self.permissions.entry(permission.to_string()).or_default() += 1;
+        }
+    }
+
+    // The size of a CRX file is the uncompressed size of its entries
+    pub fn add_crx(&mut self, data: &[u8]) -> anyhow::Result<()> {
+        let extension = parse_crx_with_options(data, &self.parse)?;
+
+        let manifest = get_manifest(&extension.zip)?;
+        let size = get_entries(&extension.zip)?
+            .iter()
+            .map(|entry| entry.size)
+            .sum();
+        let algorithms = get_signature_algorithms(&extension);
+
+        self.add_manifest(&manifest, size);
+
+        for algorithm in algorithms {
+            *self
+                .signature_algorithms
+                .entry(algorithm.to_string())
+                .or_default() += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn skip(&mut self) {
+        self.skipped += 1;
+    }
+
+    pub fn finish(self) -> CorpusStats {
+        let mut permissions: Vec<Count> = self
+            .permissions
+            .into_iter()
+            .map(|(name, count)| Count { name, count })
+            .collect();
+        permissions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+
+        CorpusStats {
+            extensions: self.extensions,
+            manifest_versions: self.manifest_versions,
+            permissions,
+            average_size: self.size.checked_div(self.extensions as u64).unwrap_or(0),
+            signature_algorithms: self.signature_algorithms,
+            skipped: self.skipped,
+        }
+    }
+}
+
+// The algorithms an extension is signed with, CRX3 files may carry several proofs
+pub fn get_signature_algorithms(extension: &CrxExtension) -> Vec<&'static str> {
+    let Some(header) = &extension.header else {
+        return vec!["sha1_with_rsa"];
+    };
+
+    let mut algorithms = vec![];
+
+    if !header.sha256_with_rsa.is_empty() {
+        algorithms.push("sha256_with_rsa");
+    }
+
+    if !header.sha256_with_ecdsa.is_empty() {
+        algorithms.push("sha256_with_ecdsa");
+    }
+
+    algorithms
+}
+
+// Walks CRX files and unpacked extensions, any directory holding a manifest.json, below root
+pub fn collect_corpus_stats(root: &Path) -> anyhow::Result<CorpusStats> {
+    collect_corpus_stats_with_options(root, &ParseOptions::default())
+}
+
+pub fn collect_corpus_stats_with_options(
+    root: &Path,
+    options: &ParseOptions,
+) -> anyhow::Result<CorpusStats> {
+    let mut collector = StatsCollector::with_options(*options);
+
+    collect_directory(root, &mut collector)?;
+
+    Ok(collector.finish())
+}
+
+fn collect_directory(directory: &Path, collector: &mut StatsCollector) -> anyhow::Result<()> {
+    let manifest_file = directory.join(MANIFEST_FILE_NAME);
+
+    if manifest_file.is_file() {
+        match parse_manifest(&fs::read_to_string(manifest_file)?) {
+            Ok(manifest) => collector.add_manifest(&manifest, get_directory_size(directory)?),
+            Err(_) => collector.skip(),
+        }
+
+        return Ok(());
+    }
+
+    let mut entries: Vec<_> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_directory(&path, collector)?;
+        } else if is_crx_path(&path) && collector.add_crx(&fs::read(&path)?).is_err() {
+            collector.skip();
+        }
+    }
+
+    Ok(())
+}
+
+pub fn get_directory_size(directory: &Path) -> anyhow::Result<u64> {
+    let mut size = 0;
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            size += get_directory_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+
+    Ok(size)
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Chain, Cursor, Read, Seek, SeekFrom, Write};
+
+use super::{
+    archive::{get_entry_metadata, EntryMetadata},
+    helpers::{get_header_length_with_options, get_zip_start_offset_with_options, ParseOptions},
+    zip_records::{
+        find_end_of_central_directory, get_directory_prefix, read_directory_records,
+        read_local_header, EndOfCentralDirectory, ZipRecords, END_OF_CENTRAL_DIRECTORY_SIZE,
+        LOCAL_FILE_HEADER_SIZE, MAX_COMMENT_LENGTH,
+    },
+};
+
+// Magic, version and both length fields of CRX2. A CRX3 file is at least this long too,
+// as its 12 byte prefix is followed by the header or the zip signature
+const FIXED_HEADER_LENGTH: usize = 16;
+
+// The bytes read past the header, then the rest of the reader
+pub type PayloadReader<R> = Chain<Cursor<Vec<u8>>, R>;
+
+// Reads the CRX header, everything before the zip payload, and returns it along with a
+// reader positioned at the start of the payload
+pub fn read_crx_header<R: Read>(reader: R) -> anyhow::Result<(Vec<u8>, PayloadReader<R>)> {
+    read_crx_header_with_options(reader, &ParseOptions::default())
+}
+
+pub fn read_crx_header_with_options<R: Read>(
+    mut reader: R,
+    options: &ParseOptions,
+) -> anyhow::Result<(Vec<u8>, PayloadReader<R>)> {
+    let mut data = vec![];
+    read_up_to(&mut reader, &mut data, FIXED_HEADER_LENGTH)?;
+
+    // Followed by the zip signature, which is checked before anything else is read
+    let header_length = get_header_length_with_options(&data, options)?;
+    read_up_to(&mut reader, &mut data, header_length + 4)?;
+    let zip_start_offset = get_zip_start_offset_with_options(&data, options)?;
+
+    let payload_start = data.split_off(zip_start_offset);
+
+    Ok((data, Cursor::new(payload_start).chain(reader)))
+}
+
+// Writes the zip payload of a CRX file read from the reader without ever holding it in
+// memory, only the header is buffered and returned, e.g. to compute the extension id
+pub fn copy_zip_payload(reader: impl Read, writer: impl Write) -> anyhow::Result<Vec<u8>> {
+    copy_zip_payload_with_options(reader, writer, &ParseOptions::default())
+}
+
+pub fn copy_zip_payload_with_options(
+    reader: impl Read,
+    mut writer: impl Write,
+    options: &ParseOptions,
+) -> anyhow::Result<Vec<u8>> {
+    let (header, mut payload) = read_crx_header_with_options(reader, options)?;
+
+    io::copy(&mut payload, &mut writer)?;
+    writer.flush()?;
+
+    Ok(header)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipListing {
+    pub entries: Vec<EntryMetadata>,
+    pub comment: String,
+}
+
+// Lists the entries of a CRX file from the end of its zip: only the end record, the central
+// directory and each local header are read, whatever the size of the payload
+pub fn read_zip_listing<R: Read + Seek>(reader: R) -> anyhow::Result<ZipListing> {
+    read_zip_listing_with_options(reader, &ParseOptions::default())
+}
+
+pub fn read_zip_listing_with_options<R: Read + Seek>(
+    mut reader: R,
+    options: &ParseOptions,
+) -> anyhow::Result<ZipListing> {
+    let (header, _) = read_crx_header_with_options(&mut reader, options)?;
+    let zip_start = header.len() as u64;
+    let zip_length = reader.seek(SeekFrom::End(0))?.saturating_sub(zip_start);
+
+    // The end record is followed by a comment of at most 64 KiB
+    let tail_length = zip_length.min((END_OF_CENTRAL_DIRECTORY_SIZE + MAX_COMMENT_LENGTH) as u64);
+    let tail_start = zip_length - tail_length;
+    let tail = read_at(&mut reader, zip_start + tail_start, tail_length as usize)?;
+
+    let end = find_end_of_central_directory(&tail)?;
+    let end = EndOfCentralDirectory {
+        offset: tail_start as usize + end.offset,
+        ..end
+    };
+
+    let prefix = get_directory_prefix(&end)?;
+    let directory = read_at(
+        &mut reader,
+        zip_start + (prefix + end.directory_offset as usize) as u64,
+        end.directory_size as usize,
+    )?;
+
+    let records = ZipRecords {
+        entries: read_directory_records(&directory, 0, end.entries)?,
+        end,
+        prefix,
+    };
+
+    let entries = records
+        .entries
+        .iter()
+        .map(|record| {
+            let offset = zip_start + records.local_header_offset(record) as u64;
+            let mut local = read_at(&mut reader, offset, LOCAL_FILE_HEADER_SIZE)?;
+
+            // Name and extra field lengths close the fixed part of the header
+            let name_length = u16::from_le_bytes([local[26], local[27]]) as usize;
+            let extra_length = u16::from_le_bytes([local[28], local[29]]) as usize;
+            read_up_to(
+                &mut reader,
+                &mut local,
+                LOCAL_FILE_HEADER_SIZE + name_length + extra_length,
+            )?;
+
+            Ok(get_entry_metadata(record, read_local_header(&local, 0)?))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok(ZipListing {
+        entries,
+        comment: String::from_utf8_lossy(&records.end.comment).into_owned(),
+    })
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, length: usize) -> anyhow::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset))?;
+
+    let mut data = vec![];
+    read_up_to(reader, &mut data, length)?;
+
+    if data.len() < length {
+        return Err(anyhow::anyhow!(
+            "Expected {} bytes at byte {}, found {}",
+            length,
+            offset,
+            data.len()
+        ));
+    }
+
+    Ok(data)
+}
+
+// Grows with the data actually read, so a header declaring absurd lengths can't make it
+// allocate more than the file holds
+fn read_up_to(reader: &mut impl Read, data: &mut Vec<u8>, length: usize) -> io::Result<()> {
+    let missing = length.saturating_sub(data.len()) as u64;
+
+    reader.take(missing).read_to_end(data)?;
+
+    Ok(())
+}
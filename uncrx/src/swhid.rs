@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, io::Read};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use super::archive::{ensure_not_encrypted, open_archive};
+
+// Software Heritage identifiers of an extracted tree, the directory one being the SWHID of
+// its root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeSwhids {
+    pub directory: String,
+    // Keyed by path relative to the root, like the hashes of an extraction manifest
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Default)]
+struct Directory {
+    files: BTreeMap<String, [u8; 20]>,
+    directories: BTreeMap<String, Directory>,
+}
+
+// swh:1:cnt, the SHA-1 git gives the same content as a blob
+pub fn get_content_swhid(data: &[u8]) -> String {
+    format!("swh:1:cnt:{}", encode_hex(&get_blob_hash(data)))
+}
+
+// Computed from the zip payload, which holds the same tree the CRX file unpacks to
+pub fn get_tree_swhids(zip: &[u8]) -> anyhow::Result<TreeSwhids> {
+    ensure_not_encrypted(zip)?;
+
+    let mut archive = open_archive(zip)?;
+    let mut root = Directory::default();
+    let mut files = BTreeMap::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow::anyhow!("{} points outside the archive", file.name()))?
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let mut components: Vec<&str> = path.split('/').filter(|name| !name.is_empty()).collect();
+
+        if file.is_dir() {
+            components.iter().fold(&mut root, |directory, name| {
+                directory.directories.entry(name.to_string()).or_default()
+            });
+            continue;
+        }
+
+        let Some(name) = components.pop() else {
+            continue;
+        };
+
+        // Not preallocated, the size an entry declares is never trusted
+        let mut content = vec![];
+        file.read_to_end(&mut content)?;
+
+        let hash = get_blob_hash(&content);
+        files.insert(path.clone(), format!("swh:1:cnt:{}", encode_hex(&hash)));
+
+        components
+            .iter()
+            .fold(&mut root, |directory, name| {
+                directory.directories.entry(name.to_string()).or_default()
+            })
+            .files
+            .insert(name.to_string(), hash);
+    }
+
+    Ok(TreeSwhids {
+        directory: format!("swh:1:dir:{}", encode_hex(&get_tree_hash(&root))),
+        files,
+    })
+}
+
+fn get_blob_hash(data: &[u8]) -> [u8; 20] {
+    get_object_hash("blob", data)
+}
+
+// Entries are sorted like git does, directories as if their name ended with a slash.
+// Unpacked files are never executable, so every file gets the regular mode
+fn get_tree_hash(directory: &Directory) -> [u8; 20] {
+    let mut entries: Vec<(String, &str, [u8; 20])> = directory
+        .files
+        .iter()
+        .map(|(name, hash)| (name.clone(), "100644", *hash))
+        .chain(
+            directory
+                .directories
+                .iter()
+                .map(|(name, directory)| (format!("{}/", name), "40000", get_tree_hash(directory))),
+        )
+        .collect();
+    entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+    let mut tree = vec![];
+
+    for (name, mode, hash) in entries {
+        tree.extend_from_slice(mode.as_bytes());
+        tree.push(b' ');
+        tree.extend_from_slice(name.trim_end_matches('/').as_bytes());
+        tree.push(0);
+        tree.extend_from_slice(&hash);
+    }
+
+    get_object_hash("tree", &tree)
+}
+
+fn get_object_hash(kind: &str, data: &[u8]) -> [u8; 20] {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{} {}\0", kind, data.len()));
+    hasher.update(data);
+
+    hasher.finalize().into()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
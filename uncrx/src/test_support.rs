@@ -0,0 +1,167 @@
+use std::{
+    fmt,
+    io::{Cursor, Write},
+    str::FromStr,
+};
+
+use rsa::{
+    pkcs1v15::SigningKey,
+    pkcs8::EncodePublicKey,
+    signature::{SignatureEncoding, Signer},
+    RsaPrivateKey,
+};
+use sha1::Sha1;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::{constants::CRX_MAGIC_VALUE, pack::build_crx3};
+
+// Small keys keep generating fixtures fast, they are not meant to sign real releases
+pub const TEST_KEY_BITS: usize = 1024;
+
+pub fn generate_test_key() -> anyhow::Result<RsaPrivateKey> {
+    Ok(RsaPrivateKey::new(&mut rand::thread_rng(), TEST_KEY_BITS)?)
+}
+
+// Entries are written in the given order, names ending with a slash become directories
+pub fn build_zip(
+    entries: &[(String, Vec<u8>)],
+    compression: CompressionMethod,
+) -> anyhow::Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::DEFAULT.compression_method(compression);
+
+    for (name, content) in entries {
+        if name.ends_with('/') {
+            writer.add_directory(name.as_str(), options)?;
+            continue;
+        }
+
+        writer.start_file(name.as_str(), options)?;
+        writer.write_all(content)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+// The CRX2 layout Chrome dropped in version 68: magic, version, key and signature lengths,
+// the DER public key, then a SHA-1 signature of the zip
+pub fn build_crx2(zip: &[u8], key: &RsaPrivateKey) -> anyhow::Result<Vec<u8>> {
+    let public_key = key.to_public_key().to_public_key_der()?.into_vec();
+    let signature = SigningKey::<Sha1>::new(key.clone()).sign(zip).to_vec();
+
+    let mut crx = CRX_MAGIC_VALUE.to_vec();
+    crx.extend_from_slice(&2u32.to_le_bytes());
+    crx.extend_from_slice(&(public_key.len() as u32).to_le_bytes());
+    crx.extend_from_slice(&(signature.len() as u32).to_le_bytes());
+    crx.extend_from_slice(&public_key);
+    crx.extend_from_slice(&signature);
+    crx.extend_from_slice(zip);
+
+    Ok(crx)
+}
+
+// Oddities seen in CRX files from the wild, used to reproduce parser bugs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureQuirk {
+    // No public key and no signature, the header lengths are zero
+    ZeroLengthKey,
+    // Pads the zip with this many more files
+    Entries(usize),
+    // Appends this many bytes after the zip
+    TrailingGarbage(usize),
+    // Writes ZIP64 records even though the entries are small
+    Zip64,
+}
+
+impl FromStr for FixtureQuirk {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, count) = match value.split_once('=') {
+            Some((name, count)) => (
+                name,
+                Some(
+                    count
+                        .parse::<usize>()
+                        .map_err(|_| format!("expected a number, got {}", count))?,
+                ),
+            ),
+            None => (value, None),
+        };
+
+        match (name, count) {
+            ("zero-length-key", None) => Ok(FixtureQuirk::ZeroLengthKey),
+            ("entries", Some(count)) => Ok(FixtureQuirk::Entries(count)),
+            ("trailing-garbage", Some(count)) => Ok(FixtureQuirk::TrailingGarbage(count)),
+            ("zip64", None) => Ok(FixtureQuirk::Zip64),
+            _ => Err(format!(
+                "expected zero-length-key, entries=N, trailing-garbage=N or zip64, got {}",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FixtureQuirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureQuirk::ZeroLengthKey => write!(f, "zero-length-key"),
+            FixtureQuirk::Entries(count) => write!(f, "entries={}", count),
+            FixtureQuirk::TrailingGarbage(count) => write!(f, "trailing-garbage={}", count),
+            FixtureQuirk::Zip64 => write!(f, "zip64"),
+        }
+    }
+}
+
+// A minimal MV3 extension wrapped in a CRX2 or CRX3 file, signed with the key unless the
+// zero-length-key quirk is set
+pub fn build_fixture(
+    crx_version: u32,
+    quirks: &[FixtureQuirk],
+    key: &RsaPrivateKey,
+) -> anyhow::Result<Vec<u8>> {
+    let options = SimpleFileOptions::DEFAULT
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(quirks.contains(&FixtureQuirk::Zip64));
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer.start_file("manifest.json", options)?;
+    writer.write_all(br#"{"manifest_version": 3, "name": "Fixture", "version": "1.0.0"}"#)?;
+
+    for quirk in quirks {
+        if let FixtureQuirk::Entries(count) = quirk {
+            for index in 0..*count {
+                writer.start_file(format!("files/{}.txt", index), options)?;
+                writer.write_all(index.to_string().as_bytes())?;
+            }
+        }
+    }
+
+    let zip = writer.finish()?.into_inner();
+
+    let mut crx = match (crx_version, quirks.contains(&FixtureQuirk::ZeroLengthKey)) {
+        (2, false) => build_crx2(&zip, key)?,
+        (3, false) => build_crx3(&zip, key)?,
+        (2 | 3, true) => {
+            let mut crx = CRX_MAGIC_VALUE.to_vec();
+            crx.extend_from_slice(&crx_version.to_le_bytes());
+            crx.extend_from_slice(&0u32.to_le_bytes());
+
+            if crx_version == 2 {
+                crx.extend_from_slice(&0u32.to_le_bytes());
+            }
+
+            crx.extend_from_slice(&zip);
+            crx
+        }
+        _ => return Err(anyhow::anyhow!("Unsupported CRX version {}", crx_version)),
+    };
+
+    for quirk in quirks {
+        if let FixtureQuirk::TrailingGarbage(count) = quirk {
+            crx.extend((0..*count).map(|index| (index % 251) as u8));
+        }
+    }
+
+    Ok(crx)
+}
@@ -0,0 +1,95 @@
+use std::{fmt, ops::Range};
+
+use super::id::get_extension_id_from_parts;
+
+#[derive(Debug)]
+pub struct CrxExtension {
+    pub version: u32,
+    pub public_key: Vec<u8>,
+    pub signature: Option<Vec<u8>>,
+    pub zip: Vec<u8>,
+    // Only CRX3 files have one, CRX2 keeps its single key and signature in the fields above
+    pub header: Option<Crx3Header>,
+    pub(super) layout: CrxLayout,
+}
+
+impl CrxExtension {
+    // Byte ranges within the original CRX file, not within the owned buffers above
+    pub fn layout(&self) -> &CrxLayout {
+        &self.layout
+    }
+
+    // The 32 letter id Chrome and the Web Store know the extension by, the same one
+    // get_extension_id reads from the raw file
+    pub fn extension_id(&self) -> anyhow::Result<String> {
+        get_extension_id_from_parts(&self.public_key, self.header.as_ref())
+    }
+}
+
+// The CrxFileHeader protobuf of a CRX3 file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Crx3Header {
+    pub sha256_with_rsa: Vec<Crx3Proof>,
+    pub sha256_with_ecdsa: Vec<Crx3Proof>,
+    // The bytes covered by every signature, kept as they are so proofs can be checked
+    pub signed_header_data: Vec<u8>,
+    // As declared in the signed header data, Chrome only accepts 16 bytes
+    pub crx_id: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Crx3Proof {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrxField {
+    Magic,
+    Version,
+    PublicKeyLength,
+    SignatureLength,
+    PublicKey,
+    Signature,
+    HeaderSize,
+    Header,
+    Zip,
+}
+
+impl fmt::Display for CrxField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CrxField::Magic => "magic",
+            CrxField::Version => "version",
+            CrxField::PublicKeyLength => "public_key_length",
+            CrxField::SignatureLength => "signature_length",
+            CrxField::PublicKey => "public_key",
+            CrxField::Signature => "signature",
+            CrxField::HeaderSize => "header_size",
+            CrxField::Header => "header",
+            CrxField::Zip => "zip",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrxRegion {
+    pub field: CrxField,
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrxLayout {
+    pub regions: Vec<CrxRegion>,
+}
+
+impl CrxLayout {
+    pub fn get(&self, field: CrxField) -> Option<Range<usize>> {
+        self.regions
+            .iter()
+            .find(|region| region.field == field)
+            .map(|region| region.range.clone())
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use super::{
+    helpers::{is_crx_path, ParseOptions},
+    id::get_extension_id,
+    manifest::{compare_versions, read_manifest_with_options},
+};
+
+pub const UPDATE_PROTOCOL_NAMESPACE: &str = "http://www.google.com/update2/response";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateManifestEntry {
+    pub app_id: String,
+    pub version: String,
+    pub codebase: String,
+    pub minimum_chrome_version: Option<String>,
+}
+
+pub fn get_update_manifest_entry(
+    crx: &[u8],
+    codebase: &str,
+) -> anyhow::Result<UpdateManifestEntry> {
+    get_update_manifest_entry_with_options(crx, codebase, &ParseOptions::default())
+}
+
+pub fn get_update_manifest_entry_with_options(
+    crx: &[u8],
+    codebase: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<UpdateManifestEntry> {
+    let manifest = read_manifest_with_options(crx, options)?;
+
+    let version = manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("manifest.json has no version"))?;
+
+    Ok(UpdateManifestEntry {
+        app_id: get_extension_id(crx)?,
+        version: version.to_string(),
+        codebase: codebase.to_string(),
+        minimum_chrome_version: manifest
+            .get("minimum_chrome_version")
+            .and_then(Value::as_str)
+            .map(String::from),
+    })
+}
+
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateCheck {
+    pub app_id: String,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostedExtension {
+    pub entry: UpdateManifestEntry,
+    pub path: PathBuf,
+    pub file_name: String,
+}
+
+fn write_app(xml: &mut String, entry: &UpdateManifestEntry) {
+    let minimum_chrome_version = entry
+        .minimum_chrome_version
+        .as_ref()
+        .map(|version| format!(" prodversionmin='{}'", escape_xml(version)))
+        .unwrap_or_default();
+
+    xml.push_str(&format!(
+        "  <app appid='{}'>\n    <updatecheck codebase='{}' version='{}'{} />\n  </app>\n",
+        escape_xml(&entry.app_id),
+        escape_xml(&entry.codebase),
+        escape_xml(&entry.version),
+        minimum_chrome_version
+    ));
+}
+
+fn write_document(apps: impl FnOnce(&mut String)) -> String {
+    let mut xml = format!(
+        "<?xml version='1.0' encoding='UTF-8'?>\n<gupdate xmlns='{}' protocol='2.0'>\n",
+        UPDATE_PROTOCOL_NAMESPACE
+    );
+
+    apps(&mut xml);
+    xml.push_str("</gupdate>\n");
+
+    xml
+}
+
+pub fn generate_update_manifest(entries: &[UpdateManifestEntry]) -> String {
+    write_document(|xml| entries.iter().for_each(|entry| write_app(xml, entry)))
+}
+
+pub fn answer_update_checks(checks: &[UpdateCheck], entries: &[UpdateManifestEntry]) -> String {
+    if checks.is_empty() {
+        return generate_update_manifest(entries);
+    }
+
+    write_document(|xml| {
+        for check in checks {
+            match entries.iter().find(|entry| entry.app_id == check.app_id) {
+                Some(entry) => write_app(xml, entry),
+                None => xml.push_str(&format!(
+                    "  <app appid='{}' status='error-unknownApplication' />\n",
+                    escape_xml(&check.app_id)
+                )),
+            }
+        }
+    })
+}
+
+// Keeps unreserved characters and commas only, fit for a query value or a path segment
+pub fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b',' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_query(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+}
+
+pub fn parse_update_request(query: &str) -> Vec<UpdateCheck> {
+    // Chrome sends one x=id%3D<id>%26v%3D<version>%26uc parameter per extension
+    parse_query(query)
+        .filter(|(key, _)| key == "x")
+        .filter_map(|(_, value)| {
+            let mut app_id = None;
+            let mut version = None;
+
+            for (key, value) in parse_query(&value) {
+                match key.as_str() {
+                    "id" => app_id = Some(value),
+                    "v" => version = Some(value),
+                    _ => {}
+                }
+            }
+
+            Some(UpdateCheck {
+                app_id: app_id?,
+                version,
+            })
+        })
+        .collect()
+}
+
+pub fn scan_update_directory(
+    directory: &Path,
+    base_url: &str,
+) -> anyhow::Result<Vec<HostedExtension>> {
+    scan_update_directory_with_options(directory, base_url, &ParseOptions::default())
+}
+
+pub fn scan_update_directory_with_options(
+    directory: &Path,
+    base_url: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<Vec<HostedExtension>> {
+    let mut hosted = vec![];
+
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if !is_crx_path(&path) {
+            continue;
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let codebase = format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            percent_encode(&file_name)
+        );
+
+        // Unreadable files are skipped so one bad upload doesn't take the service down
+        let Ok(entry) = fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|crx| get_update_manifest_entry_with_options(&crx, &codebase, options))
+        else {
+            continue;
+        };
+
+        hosted.push(HostedExtension {
+            entry,
+            path,
+            file_name,
+        });
+    }
+
+    Ok(keep_latest_versions(hosted))
+}
+
+// Only the highest version of every extension is served, ordered by extension id
+pub fn keep_latest_versions(
+    hosted: impl IntoIterator<Item = HostedExtension>,
+) -> Vec<HostedExtension> {
+    let mut latest: BTreeMap<String, HostedExtension> = BTreeMap::new();
+
+    for extension in hosted {
+        let is_newer = latest.get(&extension.entry.app_id).is_none_or(|current| {
+            compare_versions(&extension.entry.version, &current.entry.version) == Ordering::Greater
+        });
+
+        if is_newer {
+            latest.insert(extension.entry.app_id.clone(), extension);
+        }
+    }
+
+    latest.into_values().collect()
+}
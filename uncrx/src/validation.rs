@@ -0,0 +1,352 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use super::manifest::get_manifest_version;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    String,
+    Integer,
+    Boolean,
+    Object,
+    StringArray,
+    ObjectArray,
+    Any,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::String => write!(f, "a string"),
+            Kind::Integer => write!(f, "an integer"),
+            Kind::Boolean => write!(f, "a boolean"),
+            Kind::Object => write!(f, "an object"),
+            Kind::StringArray => write!(f, "an array of strings"),
+            Kind::ObjectArray => write!(f, "an array of objects"),
+            Kind::Any => write!(f, "any value"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+// (key, kind, allowed in manifest v2, allowed in manifest v3)
+const MANIFEST_KEYS: &[(&str, Kind, bool, bool)] = &[
+    ("name", Kind::String, true, true),
+    ("version", Kind::String, true, true),
+    ("manifest_version", Kind::Integer, true, true),
+    ("description", Kind::String, true, true),
+    ("short_name", Kind::String, true, true),
+    ("version_name", Kind::String, true, true),
+    ("default_locale", Kind::String, true, true),
+    ("icons", Kind::Object, true, true),
+    ("homepage_url", Kind::String, true, true),
+    ("permissions", Kind::StringArray, true, true),
+    ("optional_permissions", Kind::StringArray, true, true),
+    ("host_permissions", Kind::StringArray, false, true),
+    ("optional_host_permissions", Kind::StringArray, false, true),
+    ("content_scripts", Kind::ObjectArray, true, true),
+    ("background", Kind::Object, true, true),
+    ("action", Kind::Object, false, true),
+    ("browser_action", Kind::Object, true, false),
+    ("page_action", Kind::Object, true, false),
+    ("options_page", Kind::String, true, true),
+    ("options_ui", Kind::Object, true, true),
+    ("devtools_page", Kind::String, true, true),
+    ("commands", Kind::Object, true, true),
+    ("omnibox", Kind::Object, true, true),
+    ("chrome_url_overrides", Kind::Object, true, true),
+    ("externally_connectable", Kind::Object, true, true),
+    ("storage", Kind::Object, true, true),
+    ("sandbox", Kind::Object, true, true),
+    ("side_panel", Kind::Object, false, true),
+    ("declarative_net_request", Kind::Object, true, true),
+    ("oauth2", Kind::Object, true, true),
+    ("minimum_chrome_version", Kind::String, true, true),
+    ("update_url", Kind::String, true, true),
+    ("key", Kind::String, true, true),
+    ("incognito", Kind::String, true, true),
+    ("offline_enabled", Kind::Boolean, true, true),
+    ("web_accessible_resources", Kind::Any, true, true),
+    ("content_security_policy", Kind::Any, true, true),
+    ("author", Kind::Any, true, true),
+];
+
+const REQUIRED_KEYS: [&str; 3] = ["manifest_version", "name", "version"];
+
+const CONTENT_SCRIPT_KEYS: &[(&str, Kind)] = &[
+    ("matches", Kind::StringArray),
+    ("exclude_matches", Kind::StringArray),
+    ("include_globs", Kind::StringArray),
+    ("exclude_globs", Kind::StringArray),
+    ("js", Kind::StringArray),
+    ("css", Kind::StringArray),
+    ("run_at", Kind::String),
+    ("all_frames", Kind::Boolean),
+    ("match_about_blank", Kind::Boolean),
+    ("match_origin_as_fallback", Kind::Boolean),
+    ("world", Kind::String),
+];
+
+const MAX_NAME_LENGTH: usize = 75;
+const MAX_SHORT_NAME_LENGTH: usize = 12;
+const MAX_DESCRIPTION_LENGTH: usize = 132;
+
+struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn check_kind(&mut self, path: &str, value: &Value, kind: Kind) -> bool {
+        let matches = match kind {
+            Kind::String => value.is_string(),
+            Kind::Integer => value.is_u64() || value.is_i64(),
+            Kind::Boolean => value.is_boolean(),
+            Kind::Object => value.is_object(),
+            Kind::StringArray | Kind::ObjectArray => {
+                let Some(items) = value.as_array() else {
+                    self.error(path, format!("expected {}", kind));
+                    return false;
+                };
+
+                let item_kind = match kind {
+                    Kind::StringArray => Kind::String,
+                    _ => Kind::Object,
+                };
+
+                return items.iter().enumerate().fold(true, |valid, (index, item)| {
+                    self.check_kind(&format!("{}/{}", path, index), item, item_kind) && valid
+                });
+            }
+            Kind::Any => true,
+        };
+
+        if !matches {
+            self.error(path, format!("expected {}", kind));
+        }
+
+        matches
+    }
+
+    fn check_length(&mut self, manifest: &Map<String, Value>, key: &str, max: usize) {
+        if let Some(Value::String(value)) = manifest.get(key) {
+            if value.chars().count() > max {
+                self.error(
+                    &format!("/{}", key),
+                    format!("must be at most {} characters long", max),
+                );
+            }
+        }
+    }
+
+    fn check_version(&mut self, path: &str, version: &str) {
+        if !is_valid_version(version) {
+            self.error(
+                path,
+                "must be 1 to 4 dot-separated integers between 0 and 65535",
+            );
+        }
+    }
+
+    fn check_background(&mut self, background: &Map<String, Value>, manifest_version: u64) {
+        let forbidden: &[&str] = match manifest_version {
+            2 => &["service_worker", "type"],
+            _ => &["scripts", "page", "persistent"],
+        };
+
+        for key in forbidden {
+            if background.contains_key(*key) {
+                self.error(
+                    &format!("/background/{}", key),
+                    format!("is not supported in manifest v{}", manifest_version),
+                );
+            }
+        }
+
+        if let Some(service_worker) = background.get("service_worker") {
+            self.check_kind("/background/service_worker", service_worker, Kind::String);
+        }
+
+        if let Some(scripts) = background.get("scripts") {
+            self.check_kind("/background/scripts", scripts, Kind::StringArray);
+        }
+    }
+
+    fn check_content_scripts(&mut self, content_scripts: &[Value]) {
+        for (index, script) in content_scripts.iter().enumerate() {
+            let Some(script) = script.as_object() else {
+                continue;
+            };
+            let path = format!("/content_scripts/{}", index);
+
+            if !script.contains_key("matches") {
+                self.error(&path, "missing required key matches");
+            }
+
+            for (key, kind) in CONTENT_SCRIPT_KEYS {
+                if let Some(value) = script.get(*key) {
+                    self.check_kind(&format!("{}/{}", path, key), value, *kind);
+                }
+            }
+
+            if let Some(Value::String(run_at)) = script.get("run_at") {
+                if !["document_start", "document_end", "document_idle"].contains(&run_at.as_str()) {
+                    self.error(
+                        &format!("{}/run_at", path),
+                        "must be one of document_start, document_end, document_idle",
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_web_accessible_resources(&mut self, resources: &Value, manifest_version: u64) {
+        let path = "/web_accessible_resources";
+
+        if manifest_version == 2 {
+            self.check_kind(path, resources, Kind::StringArray);
+            return;
+        }
+
+        if !self.check_kind(path, resources, Kind::ObjectArray) {
+            return;
+        }
+
+        for (index, entry) in resources.as_array().into_iter().flatten().enumerate() {
+            let path = format!("{}/{}", path, index);
+
+            match entry.get("resources") {
+                Some(value) => {
+                    self.check_kind(&format!("{}/resources", path), value, Kind::StringArray);
+                }
+                None => self.error(&path, "missing required key resources"),
+            }
+
+            for key in ["matches", "extension_ids"] {
+                if let Some(value) = entry.get(key) {
+                    self.check_kind(&format!("{}/{}", path, key), value, Kind::StringArray);
+                }
+            }
+        }
+    }
+}
+
+pub fn validate_manifest(manifest: &Value) -> Vec<ValidationError> {
+    let mut validator = Validator { errors: vec![] };
+
+    let Some(object) = manifest.as_object() else {
+        validator.error("", "manifest must be a JSON object");
+        return validator.errors;
+    };
+
+    for key in REQUIRED_KEYS {
+        if !object.contains_key(key) {
+            validator.error("", format!("missing required key {}", key));
+        }
+    }
+
+    let manifest_version = get_manifest_version(manifest);
+
+    if let Some(value) = object.get("manifest_version") {
+        if !value.as_u64().is_some_and(|v| v == 2 || v == 3) {
+            validator.error("/manifest_version", "must be 2 or 3");
+        }
+    }
+
+    for (key, kind, allowed_in_v2, allowed_in_v3) in MANIFEST_KEYS {
+        let Some(value) = object.get(*key) else {
+            continue;
+        };
+        let path = format!("/{}", key);
+
+        let allowed = match manifest_version {
+            2 => *allowed_in_v2,
+            _ => *allowed_in_v3,
+        };
+
+        if !allowed {
+            validator.error(
+                &path,
+                format!("is not supported in manifest v{}", manifest_version),
+            );
+            continue;
+        }
+
+        validator.check_kind(&path, value, *kind);
+    }
+
+    if let Some(Value::String(version)) = object.get("version") {
+        validator.check_version("/version", version);
+    }
+
+    validator.check_length(object, "name", MAX_NAME_LENGTH);
+    validator.check_length(object, "short_name", MAX_SHORT_NAME_LENGTH);
+    validator.check_length(object, "description", MAX_DESCRIPTION_LENGTH);
+
+    if let Some(Value::String(incognito)) = object.get("incognito") {
+        if !["spanning", "split", "not_allowed"].contains(&incognito.as_str()) {
+            validator.error("/incognito", "must be one of spanning, split, not_allowed");
+        }
+    }
+
+    if let Some(Value::Object(background)) = object.get("background") {
+        validator.check_background(background, manifest_version);
+    }
+
+    if let Some(Value::Array(content_scripts)) = object.get("content_scripts") {
+        validator.check_content_scripts(content_scripts);
+    }
+
+    if let Some(resources) = object.get("web_accessible_resources") {
+        validator.check_web_accessible_resources(resources, manifest_version);
+    }
+
+    if manifest_version >= 3 {
+        if let Some(Value::Array(permissions)) = object.get("permissions") {
+            for (index, permission) in permissions.iter().enumerate() {
+                if permission.as_str().is_some_and(is_host_permission) {
+                    validator.error(
+                        &format!("/permissions/{}", index),
+                        "host permissions belong in host_permissions in manifest v3",
+                    );
+                }
+            }
+        }
+    }
+
+    validator.errors
+}
+
+pub fn is_valid_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+
+    parts.len() <= 4
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.chars().all(|c| c.is_ascii_digit())
+                && (part.len() == 1 || !part.starts_with('0'))
+                && part.parse::<u32>().is_ok_and(|n| n <= 65535)
+        })
+}
+
+fn is_host_permission(permission: &str) -> bool {
+    permission == "<all_urls>" || permission.contains("://")
+}
@@ -0,0 +1,276 @@
+use std::{
+    fmt,
+    io::{ErrorKind, Read},
+};
+
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+use super::{
+    constants::{
+        CRX_ID_LENGTH, EC_P256_PUBLIC_KEY_PREFIX, EC_P384_PUBLIC_KEY_PREFIX, EC_PUBLIC_KEY_OID,
+        WEBSTORE_PUBLISHER_KEY_HASH,
+    },
+    helpers::{get_crx_version, parse_crx3_header, ParseOptions},
+    pack::CRX3_SIGNATURE_CONTEXT,
+    stream::read_crx_header_with_options,
+    types::{Crx3Header, Crx3Proof},
+};
+
+// The payload is hashed this many bytes at a time, so files larger than memory can be
+// verified
+pub const VERIFY_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofAlgorithm {
+    Sha256WithRsa,
+    Sha256WithEcdsa,
+}
+
+impl fmt::Display for ProofAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofAlgorithm::Sha256WithRsa => write!(f, "sha256_with_rsa"),
+            ProofAlgorithm::Sha256WithEcdsa => write!(f, "sha256_with_ecdsa"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofStatus {
+    Valid,
+    Invalid,
+    // An EC key on a curve other than P-256 and P-384
+    Unsupported,
+}
+
+impl fmt::Display for ProofStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofStatus::Valid => write!(f, "valid"),
+            ProofStatus::Invalid => write!(f, "invalid"),
+            ProofStatus::Unsupported => write!(f, "unsupported"),
+        }
+    }
+}
+
+// What Chrome would make of a CRX3 file, given the proofs this crate can check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationResult {
+    // Every proof holds and one of them is made with the Chrome Web Store key
+    PublishedThroughWebStore,
+    // Every proof holds, none of them by the Chrome Web Store
+    Valid,
+    // The other proofs hold but some ECDSA proofs use a curve that can't be checked
+    Unverified,
+    // No proof is made with the key the declared crx_id derives from
+    MissingDeveloperProof,
+    InvalidSignature,
+}
+
+impl VerificationResult {
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            VerificationResult::PublishedThroughWebStore | VerificationResult::Valid
+        )
+    }
+}
+
+impl fmt::Display for VerificationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationResult::PublishedThroughWebStore => {
+                write!(f, "published through the Chrome Web Store")
+            }
+            VerificationResult::Valid => write!(f, "valid"),
+            VerificationResult::Unverified => {
+                write!(f, "unverified, some ECDSA proofs use an unsupported curve")
+            }
+            VerificationResult::MissingDeveloperProof => {
+                write!(f, "no proof made with the key of the extension id")
+            }
+            VerificationResult::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proof {
+    pub algorithm: ProofAlgorithm,
+    pub public_key: Vec<u8>,
+    pub status: ProofStatus,
+}
+
+pub fn is_webstore_publisher_key(public_key: &[u8]) -> bool {
+    Sha256::digest(public_key)[..] == WEBSTORE_PUBLISHER_KEY_HASH
+}
+
+// Checks every proof of a CRX3 file. They all sign the same message, the signed header
+// data followed by the zip payload, which is hashed once while it is read from the reader.
+// RSA and ECDSA proofs are all checked against that digest, the payload is never held in
+// memory
+pub fn verify_proofs(reader: impl Read) -> anyhow::Result<Vec<Proof>> {
+    Ok(read_proofs(reader, &ParseOptions::default())?.1)
+}
+
+// Checks the proofs like Chrome does before installing a CRX3 file
+pub fn verify_crx(reader: impl Read) -> anyhow::Result<(VerificationResult, Vec<Proof>)> {
+    verify_crx_with_options(reader, &ParseOptions::default())
+}
+
+pub fn verify_crx_with_options(
+    reader: impl Read,
+    options: &ParseOptions,
+) -> anyhow::Result<(VerificationResult, Vec<Proof>)> {
+    let (header, proofs) = read_proofs(reader, options)?;
+
+    Ok((
+        get_verification_result(header.crx_id.as_deref(), &proofs),
+        proofs,
+    ))
+}
+
+// A failing proof rejects the file first, then a crx_id no proof key hashes to
+pub fn get_verification_result(crx_id: Option<&[u8]>, proofs: &[Proof]) -> VerificationResult {
+    if proofs
+        .iter()
+        .any(|proof| proof.status == ProofStatus::Invalid)
+    {
+        return VerificationResult::InvalidSignature;
+    }
+
+    let has_developer_proof = crx_id.is_some_and(|crx_id| {
+        proofs.iter().any(|proof| {
+            crx_id.len() == CRX_ID_LENGTH
+                && Sha256::digest(&proof.public_key)[..CRX_ID_LENGTH] == *crx_id
+        })
+    });
+
+    if !has_developer_proof {
+        return VerificationResult::MissingDeveloperProof;
+    }
+
+    if proofs
+        .iter()
+        .any(|proof| proof.status == ProofStatus::Unsupported)
+    {
+        return VerificationResult::Unverified;
+    }
+
+    match proofs
+        .iter()
+        .any(|proof| is_webstore_publisher_key(&proof.public_key))
+    {
+        true => VerificationResult::PublishedThroughWebStore,
+        false => VerificationResult::Valid,
+    }
+}
+
+fn read_proofs(
+    reader: impl Read,
+    options: &ParseOptions,
+) -> anyhow::Result<(Crx3Header, Vec<Proof>)> {
+    let (header, mut payload) = read_crx_header_with_options(reader, options)?;
+
+    let version = get_crx_version(&header)?;
+
+    if version != 3 {
+        return Err(anyhow::anyhow!(
+            "Only CRX3 proofs can be verified, found a CRX{} file",
+            version
+        ));
+    }
+
+    let header = parse_crx3_header(&header[12..])?;
+    let signed_data = &header.signed_header_data;
+
+    let mut hasher = Sha256::new();
+    hasher.update(CRX3_SIGNATURE_CONTEXT);
+    hasher.update((signed_data.len() as u32).to_le_bytes());
+    hasher.update(signed_data);
+
+    let mut chunk = vec![0; VERIFY_CHUNK_SIZE];
+
+    loop {
+        match payload.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(length) => hasher.update(&chunk[..length]),
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    let digest = hasher.finalize();
+
+    let rsa_proofs = header
+        .sha256_with_rsa
+        .iter()
+        .map(|proof| (ProofAlgorithm::Sha256WithRsa, proof));
+    let ecdsa_proofs = header
+        .sha256_with_ecdsa
+        .iter()
+        .map(|proof| (ProofAlgorithm::Sha256WithEcdsa, proof));
+
+    let proofs = rsa_proofs
+        .chain(ecdsa_proofs)
+        .map(|(algorithm, proof)| verify_proof(algorithm, proof, &digest))
+        .collect();
+
+    Ok((header, proofs))
+}
+
+fn verify_proof(algorithm: ProofAlgorithm, proof: &Crx3Proof, digest: &[u8]) -> Proof {
+    // A key that doesn't parse can't have produced the signature either
+    let status = match algorithm {
+        ProofAlgorithm::Sha256WithRsa => match RsaPublicKey::from_public_key_der(&proof.public_key)
+        {
+            Ok(key)
+                if key
+                    .verify(Pkcs1v15Sign::new::<Sha256>(), digest, &proof.signature)
+                    .is_ok() =>
+            {
+                ProofStatus::Valid
+            }
+            _ => ProofStatus::Invalid,
+        },
+        ProofAlgorithm::Sha256WithEcdsa => verify_ecdsa_proof(proof, digest),
+    };
+
+    Proof {
+        algorithm,
+        public_key: proof.public_key.clone(),
+        status,
+    }
+}
+
+// Checked against the digest of the streamed message, the key is the SEC1 point following
+// the fixed SubjectPublicKeyInfo prefix of its curve
+fn verify_ecdsa_proof(proof: &Crx3Proof, digest: &[u8]) -> ProofStatus {
+    let key = &proof.public_key;
+
+    let verified = if let Some(point) = key.strip_prefix(&EC_P256_PUBLIC_KEY_PREFIX[..]) {
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(point).is_ok_and(|key| {
+            p256::ecdsa::Signature::from_der(&proof.signature)
+                .is_ok_and(|signature| key.verify_prehash(digest, &signature).is_ok())
+        })
+    } else if let Some(point) = key.strip_prefix(&EC_P384_PUBLIC_KEY_PREFIX[..]) {
+        p384::ecdsa::VerifyingKey::from_sec1_bytes(point).is_ok_and(|key| {
+            p384::ecdsa::Signature::from_der(&proof.signature)
+                .is_ok_and(|signature| key.verify_prehash(digest, &signature).is_ok())
+        })
+    } else if key
+        .windows(EC_PUBLIC_KEY_OID.len())
+        .any(|window| window == EC_PUBLIC_KEY_OID)
+    {
+        return ProofStatus::Unsupported;
+    } else {
+        false
+    };
+
+    match verified {
+        true => ProofStatus::Valid,
+        false => ProofStatus::Invalid,
+    }
+}
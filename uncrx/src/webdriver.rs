@@ -0,0 +1,57 @@
+use base64ct::{Base64, Encoding};
+use serde_json::{json, Value};
+use ureq::http::header::CONTENT_TYPE;
+
+pub const DEFAULT_WEBDRIVER_URL: &str = "http://localhost:9515";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallResult {
+    Accepted,
+    Rejected(String),
+}
+
+// Starts a headless session with the CRX file installed through a running chromedriver, which
+// refuses to create the session when Chrome does not accept the extension
+pub fn test_install(webdriver_url: &str, crx: &[u8]) -> anyhow::Result<InstallResult> {
+    let webdriver_url = webdriver_url.trim_end_matches('/');
+
+    let capabilities = json!({
+        "capabilities": {
+            "alwaysMatch": {
+                "browserName": "chrome",
+                "goog:chromeOptions": {
+                    "args": ["--headless=new", "--no-first-run"],
+                    "extensions": [Base64::encode_string(crx)],
+                },
+            },
+        },
+    });
+
+    let mut response = ureq::post(format!("{}/session", webdriver_url))
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .header(CONTENT_TYPE, "application/json")
+        .send(serde_json::to_vec(&capabilities)?)?;
+
+    let status = response.status();
+    let body: Value = serde_json::from_str(&response.body_mut().read_to_string()?)?;
+    let value = &body["value"];
+
+    if !status.is_success() {
+        let message = value["message"]
+            .as_str()
+            .or(value["error"].as_str())
+            .unwrap_or("session not created");
+
+        return Ok(InstallResult::Rejected(message.to_string()));
+    }
+
+    let session_id = value["sessionId"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("The WebDriver response has no session id"))?;
+
+    ureq::delete(format!("{}/session/{}", webdriver_url, session_id)).call()?;
+
+    Ok(InstallResult::Accepted)
+}
@@ -0,0 +1,68 @@
+use std::env::consts;
+
+use super::{
+    errors::UncrxError, id::is_valid_extension_id, policy::WEB_STORE_UPDATE_URL,
+    update::percent_encode,
+};
+
+// Reported when none is given, the Web Store serves the newest package supporting it
+pub const DEFAULT_CHROME_VERSION: &str = "131.0.6778.85";
+
+// The os and arch Chrome would report on this machine
+pub fn get_host_platform() -> (&'static str, &'static str) {
+    let os = match consts::OS {
+        "macos" => "mac",
+        "windows" => "win",
+        os => os,
+    };
+
+    let arch = match consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        arch => arch,
+    };
+
+    (os, arch)
+}
+
+// The Native Client architecture Chrome reports alongside its own, the Web Store picks
+// platform specific packages with it
+fn get_nacl_arch(arch: &str) -> &str {
+    match arch {
+        "x64" | "x86_64" | "amd64" => "x86-64",
+        "x86" | "ia32" | "i686" => "x86-32",
+        "arm" | "arm64" | "aarch64" => "arm",
+        _ => arch,
+    }
+}
+
+// The URL Chrome requests when installing from the Web Store, which redirects to the CRX
+// file. Nothing is downloaded, the URL is only built
+pub fn download_url(
+    id: &str,
+    chrome_version: &str,
+    os: &str,
+    arch: &str,
+) -> anyhow::Result<String> {
+    if !is_valid_extension_id(id) {
+        return Err(UncrxError::InvalidExtensionId(id.to_string()).into());
+    }
+
+    let query = [
+        ("response", "redirect"),
+        ("os", os),
+        ("arch", arch),
+        ("nacl_arch", get_nacl_arch(arch)),
+        ("prod", "chromiumcrx"),
+        ("prodchannel", "unknown"),
+        ("prodversion", chrome_version),
+        ("acceptformat", "crx2,crx3"),
+        ("x", &format!("id={}&uc", id)),
+    ]
+    .iter()
+    .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+    .collect::<Vec<_>>()
+    .join("&");
+
+    Ok(format!("{}?{}", WEB_STORE_UPDATE_URL, query))
+}
@@ -0,0 +1,145 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+use flate2::read::MultiGzDecoder;
+use lzma_rust2::XzReader;
+use ruzstd::decoding::{FrameDecoder, StreamingDecoder};
+
+use super::helpers::is_crx_path;
+
+// Guards against decompression bombs, far above the size of any published extension
+pub const MAX_UNWRAPPED_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+// Compressions archive mirrors store recompressed CRX files with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrxWrapper {
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+pub const CRX_WRAPPERS: [CrxWrapper; 3] = [CrxWrapper::Gzip, CrxWrapper::Xz, CrxWrapper::Zstd];
+
+impl CrxWrapper {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CrxWrapper::Gzip => "gz",
+            CrxWrapper::Xz => "xz",
+            CrxWrapper::Zstd => "zst",
+        }
+    }
+
+    fn magic(&self) -> &'static [u8] {
+        match self {
+            CrxWrapper::Gzip => &[0x1f, 0x8b],
+            CrxWrapper::Xz => &[0xfd, b'7', b'z', b'X', b'Z', 0x00],
+            CrxWrapper::Zstd => &[0x28, 0xb5, 0x2f, 0xfd],
+        }
+    }
+}
+
+impl fmt::Display for CrxWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrxWrapper::Gzip => write!(f, "gzip"),
+            CrxWrapper::Xz => write!(f, "xz"),
+            CrxWrapper::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+// Recognised by their magic bytes, whatever the file is named
+pub fn get_crx_wrapper(data: &[u8]) -> Option<CrxWrapper> {
+    CRX_WRAPPERS
+        .into_iter()
+        .find(|wrapper| data.starts_with(wrapper.magic()))
+}
+
+// Names like extension.crx.gz, case insensitive like is_crx_path
+pub fn get_path_wrapper(path: &Path) -> Option<CrxWrapper> {
+    let extension = path.extension()?;
+
+    CRX_WRAPPERS
+        .into_iter()
+        .find(|wrapper| extension.eq_ignore_ascii_case(wrapper.extension()))
+        .filter(|_| is_crx_path(&path.with_extension("")))
+}
+
+// Plain CRX files are returned as they are
+pub fn unwrap_crx(data: &[u8]) -> anyhow::Result<Cow<'_, [u8]>> {
+    let Some(wrapper) = get_crx_wrapper(data) else {
+        return Ok(Cow::Borrowed(data));
+    };
+
+    let mut unwrapped = vec![];
+    let length = get_unwrapping_reader(data, wrapper)?
+        .take(MAX_UNWRAPPED_SIZE + 1)
+        .read_to_end(&mut unwrapped)?;
+
+    if length as u64 > MAX_UNWRAPPED_SIZE {
+        return Err(anyhow::anyhow!(
+            "The {} stream decompresses to more than {} bytes",
+            wrapper,
+            MAX_UNWRAPPED_SIZE
+        ));
+    }
+
+    Ok(Cow::Owned(unwrapped))
+}
+
+// Decompresses while reading, for callers streaming the CRX file
+pub fn get_unwrapping_reader<'a>(
+    reader: impl Read + 'a,
+    wrapper: CrxWrapper,
+) -> anyhow::Result<Box<dyn Read + 'a>> {
+    match wrapper {
+        CrxWrapper::Gzip => Ok(Box::new(MultiGzDecoder::new(reader))),
+        CrxWrapper::Xz => Ok(Box::new(XzReader::new(reader, true))),
+        CrxWrapper::Zstd => Ok(Box::new(ZstdReader::new(BufReader::new(reader))?)),
+    }
+}
+
+// ruzstd decodes a single frame, while files written by pzstd or concatenated hold several
+// of them one after the other, like multi-member gzip files
+struct ZstdReader<R: BufRead> {
+    decoder: Option<StreamingDecoder<R, FrameDecoder>>,
+}
+
+impl<R: BufRead> ZstdReader<R> {
+    fn new(reader: R) -> anyhow::Result<Self> {
+        Ok(ZstdReader {
+            decoder: Some(StreamingDecoder::new(reader)?),
+        })
+    }
+}
+
+impl<R: BufRead> Read for ZstdReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let Some(decoder) = self.decoder.as_mut() else {
+                return Ok(0);
+            };
+
+            let length = decoder.read(buffer)?;
+
+            if length > 0 || buffer.is_empty() || decoder.get_mut().fill_buf()?.is_empty() {
+                return Ok(length);
+            }
+
+            // The frame ended and more data follows, the decoder starts over on the next frame
+            let Some((reader, frame_decoder)) =
+                self.decoder.take().map(StreamingDecoder::into_parts)
+            else {
+                return Ok(0);
+            };
+            self.decoder = Some(
+                StreamingDecoder::new_with_decoder(reader, frame_decoder)
+                    .map_err(io::Error::other)?,
+            );
+        }
+    }
+}
@@ -0,0 +1,413 @@
+pub const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+pub const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+pub const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+pub const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+
+pub const LOCAL_FILE_HEADER_SIZE: usize = 30;
+pub const CENTRAL_DIRECTORY_RECORD_SIZE: usize = 46;
+pub const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+
+pub const FLAG_ENCRYPTED: u16 = 0x0001;
+pub const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+const ZIP64_MARKER: u32 = 0xffffffff;
+pub const MAX_COMMENT_LENGTH: usize = 0xffff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalFileHeader {
+    pub offset: usize,
+    pub version_needed: u16,
+    pub flags: u16,
+    pub compression: u16,
+    pub modified_time: u16,
+    pub modified_date: u16,
+    pub crc32: u32,
+    pub compressed_size: u32,
+    pub size: u32,
+    pub name: Vec<u8>,
+    pub extra: Vec<u8>,
+}
+
+impl LocalFileHeader {
+    pub fn data_start(&self) -> usize {
+        self.offset + LOCAL_FILE_HEADER_SIZE + self.name.len() + self.extra.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CentralDirectoryRecord {
+    pub version_made_by: u16,
+    pub version_needed: u16,
+    pub flags: u16,
+    pub compression: u16,
+    pub modified_time: u16,
+    pub modified_date: u16,
+    pub crc32: u32,
+    pub compressed_size: u32,
+    pub size: u32,
+    pub internal_attributes: u16,
+    pub external_attributes: u32,
+    pub local_header_offset: u32,
+    pub name: Vec<u8>,
+    pub extra: Vec<u8>,
+    pub comment: Vec<u8>,
+}
+
+impl CentralDirectoryRecord {
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.name).into_owned()
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & FLAG_ENCRYPTED != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndOfCentralDirectory {
+    pub offset: usize,
+    pub entries: u16,
+    pub directory_size: u32,
+    pub directory_offset: u32,
+    pub comment: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipRecords {
+    pub end: EndOfCentralDirectory,
+    pub entries: Vec<CentralDirectoryRecord>,
+    // Bytes preceding the archive that its offsets don't account for
+    pub prefix: usize,
+}
+
+impl ZipRecords {
+    pub fn local_header_offset(&self, record: &CentralDirectoryRecord) -> usize {
+        self.prefix + record.local_header_offset as usize
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Reader { data, position }
+    }
+
+    fn bytes(&mut self, length: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow::anyhow!("Zip record at byte {} is truncated", self.position))?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    fn signature(&mut self, expected: u32, record: &str) -> anyhow::Result<()> {
+        let offset = self.position;
+        let found = self.u32()?;
+
+        if found != expected {
+            return Err(anyhow::anyhow!(
+                "Expected a {} signature at byte {}, found {:#010x}",
+                record,
+                offset,
+                found
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn find_end_of_central_directory(zip: &[u8]) -> anyhow::Result<EndOfCentralDirectory> {
+    if zip.len() < END_OF_CENTRAL_DIRECTORY_SIZE {
+        return Err(anyhow::anyhow!("Data is too short to be a zip archive"));
+    }
+
+    let last = zip.len() - END_OF_CENTRAL_DIRECTORY_SIZE;
+    let first = last.saturating_sub(MAX_COMMENT_LENGTH);
+
+    // The record is scanned backwards since a trailing comment may follow it
+    for offset in (first..=last).rev() {
+        if zip[offset..offset + 4] != END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes() {
+            continue;
+        }
+
+        let mut reader = Reader::new(zip, offset + 10);
+        let entries = reader.u16()?;
+        let directory_size = reader.u32()?;
+        let directory_offset = reader.u32()?;
+        let comment_length = reader.u16()? as usize;
+
+        if reader.position + comment_length > zip.len() {
+            continue;
+        }
+
+        return Ok(EndOfCentralDirectory {
+            offset,
+            entries,
+            directory_size,
+            directory_offset,
+            comment: reader.bytes(comment_length)?.to_vec(),
+        });
+    }
+
+    Err(anyhow::anyhow!("End of central directory record not found"))
+}
+
+// Scans forwards for the first end record consistent with a zip starting at byte 0, so
+// that trailing data (another CRX, the rest of a disk image) is left out
+pub fn find_zip_end(data: &[u8]) -> anyhow::Result<usize> {
+    let signature = END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes();
+
+    for offset in 0..data.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_SIZE - 1) {
+        if data[offset..offset + 4] != signature {
+            continue;
+        }
+
+        let mut reader = Reader::new(data, offset + 12);
+        let directory_size = reader.u32()? as usize;
+        let directory_offset = reader.u32()? as usize;
+        let comment_length = reader.u16()? as usize;
+        let end = reader.position + comment_length;
+
+        if directory_offset + directory_size == offset && end <= data.len() {
+            return Ok(end);
+        }
+    }
+
+    Err(anyhow::anyhow!("End of central directory record not found"))
+}
+
+pub fn read_central_directory(zip: &[u8]) -> anyhow::Result<ZipRecords> {
+    let end = find_end_of_central_directory(zip)?;
+    let prefix = get_directory_prefix(&end)?;
+    let entries = read_directory_records(zip, prefix + end.directory_offset as usize, end.entries)?;
+
+    Ok(ZipRecords {
+        end,
+        entries,
+        prefix,
+    })
+}
+
+// The central directory is expected to end right where its end record starts
+pub fn get_directory_prefix(end: &EndOfCentralDirectory) -> anyhow::Result<usize> {
+    if end.directory_offset == ZIP64_MARKER || end.directory_size == ZIP64_MARKER {
+        return Err(anyhow::anyhow!("ZIP64 archives are not supported"));
+    }
+
+    let directory_end = end.directory_offset as usize + end.directory_size as usize;
+
+    end.offset.checked_sub(directory_end).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Central directory ends at byte {} past its end record at byte {}",
+            directory_end,
+            end.offset
+        )
+    })
+}
+
+pub fn read_directory_records(
+    data: &[u8],
+    position: usize,
+    count: u16,
+) -> anyhow::Result<Vec<CentralDirectoryRecord>> {
+    let mut reader = Reader::new(data, position);
+    let mut entries = Vec::with_capacity(count as usize);
+
+    for _ in 0..count {
+        reader.signature(CENTRAL_DIRECTORY_SIGNATURE, "central directory")?;
+
+        let version_made_by = reader.u16()?;
+        let version_needed = reader.u16()?;
+        let flags = reader.u16()?;
+        let compression = reader.u16()?;
+        let modified_time = reader.u16()?;
+        let modified_date = reader.u16()?;
+        let crc32 = reader.u32()?;
+        let compressed_size = reader.u32()?;
+        let size = reader.u32()?;
+        let name_length = reader.u16()? as usize;
+        let extra_length = reader.u16()? as usize;
+        let comment_length = reader.u16()? as usize;
+        let _disk_start = reader.u16()?;
+        let internal_attributes = reader.u16()?;
+        let external_attributes = reader.u32()?;
+        let local_header_offset = reader.u32()?;
+
+        entries.push(CentralDirectoryRecord {
+            version_made_by,
+            version_needed,
+            flags,
+            compression,
+            modified_time,
+            modified_date,
+            crc32,
+            compressed_size,
+            size,
+            internal_attributes,
+            external_attributes,
+            local_header_offset,
+            name: reader.bytes(name_length)?.to_vec(),
+            extra: reader.bytes(extra_length)?.to_vec(),
+            comment: reader.bytes(comment_length)?.to_vec(),
+        });
+    }
+
+    Ok(entries)
+}
+
+pub fn read_local_header(zip: &[u8], offset: usize) -> anyhow::Result<LocalFileHeader> {
+    let mut reader = Reader::new(zip, offset);
+    reader.signature(LOCAL_FILE_HEADER_SIGNATURE, "local file header")?;
+
+    let version_needed = reader.u16()?;
+    let flags = reader.u16()?;
+    let compression = reader.u16()?;
+    let modified_time = reader.u16()?;
+    let modified_date = reader.u16()?;
+    let crc32 = reader.u32()?;
+    let compressed_size = reader.u32()?;
+    let size = reader.u32()?;
+    let name_length = reader.u16()? as usize;
+    let extra_length = reader.u16()? as usize;
+
+    Ok(LocalFileHeader {
+        offset,
+        version_needed,
+        flags,
+        compression,
+        modified_time,
+        modified_date,
+        crc32,
+        compressed_size,
+        size,
+        name: reader.bytes(name_length)?.to_vec(),
+        extra: reader.bytes(extra_length)?.to_vec(),
+    })
+}
+
+pub fn write_local_header(output: &mut Vec<u8>, header: &LocalFileHeader) {
+    output.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+    output.extend_from_slice(&header.version_needed.to_le_bytes());
+    output.extend_from_slice(&header.flags.to_le_bytes());
+    output.extend_from_slice(&header.compression.to_le_bytes());
+    output.extend_from_slice(&header.modified_time.to_le_bytes());
+    output.extend_from_slice(&header.modified_date.to_le_bytes());
+    output.extend_from_slice(&header.crc32.to_le_bytes());
+    output.extend_from_slice(&header.compressed_size.to_le_bytes());
+    output.extend_from_slice(&header.size.to_le_bytes());
+    output.extend_from_slice(&(header.name.len() as u16).to_le_bytes());
+    output.extend_from_slice(&(header.extra.len() as u16).to_le_bytes());
+    output.extend_from_slice(&header.name);
+    output.extend_from_slice(&header.extra);
+}
+
+pub fn write_central_directory_record(output: &mut Vec<u8>, record: &CentralDirectoryRecord) {
+    output.extend_from_slice(&CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    output.extend_from_slice(&record.version_made_by.to_le_bytes());
+    output.extend_from_slice(&record.version_needed.to_le_bytes());
+    output.extend_from_slice(&record.flags.to_le_bytes());
+    output.extend_from_slice(&record.compression.to_le_bytes());
+    output.extend_from_slice(&record.modified_time.to_le_bytes());
+    output.extend_from_slice(&record.modified_date.to_le_bytes());
+    output.extend_from_slice(&record.crc32.to_le_bytes());
+    output.extend_from_slice(&record.compressed_size.to_le_bytes());
+    output.extend_from_slice(&record.size.to_le_bytes());
+    output.extend_from_slice(&(record.name.len() as u16).to_le_bytes());
+    output.extend_from_slice(&(record.extra.len() as u16).to_le_bytes());
+    output.extend_from_slice(&(record.comment.len() as u16).to_le_bytes());
+    output.extend_from_slice(&0u16.to_le_bytes());
+    output.extend_from_slice(&record.internal_attributes.to_le_bytes());
+    output.extend_from_slice(&record.external_attributes.to_le_bytes());
+    output.extend_from_slice(&record.local_header_offset.to_le_bytes());
+    output.extend_from_slice(&record.name);
+    output.extend_from_slice(&record.extra);
+    output.extend_from_slice(&record.comment);
+}
+
+pub fn write_end_of_central_directory(output: &mut Vec<u8>, end: &EndOfCentralDirectory) {
+    output.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    output.extend_from_slice(&0u16.to_le_bytes());
+    output.extend_from_slice(&0u16.to_le_bytes());
+    output.extend_from_slice(&end.entries.to_le_bytes());
+    output.extend_from_slice(&end.entries.to_le_bytes());
+    output.extend_from_slice(&end.directory_size.to_le_bytes());
+    output.extend_from_slice(&end.directory_offset.to_le_bytes());
+    output.extend_from_slice(&(end.comment.len() as u16).to_le_bytes());
+    output.extend_from_slice(&end.comment);
+}
+
+pub fn rebuild_zip(
+    entries: &[(CentralDirectoryRecord, &[u8])],
+    comment: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let mut zip = vec![];
+    let mut records = Vec::with_capacity(entries.len());
+
+    for (record, data) in entries {
+        let local_header_offset = u32::try_from(zip.len())
+            .map_err(|_| anyhow::anyhow!("ZIP64 archives are not supported"))?;
+
+        // Sizes are known up front, so no data descriptor follows the data
+        let flags = record.flags & !FLAG_DATA_DESCRIPTOR;
+
+        write_local_header(
+            &mut zip,
+            &LocalFileHeader {
+                offset: local_header_offset as usize,
+                version_needed: record.version_needed,
+                flags,
+                compression: record.compression,
+                modified_time: record.modified_time,
+                modified_date: record.modified_date,
+                crc32: record.crc32,
+                compressed_size: record.compressed_size,
+                size: record.size,
+                name: record.name.clone(),
+                extra: record.extra.clone(),
+            },
+        );
+        zip.extend_from_slice(data);
+
+        records.push(CentralDirectoryRecord {
+            flags,
+            local_header_offset,
+            ..record.clone()
+        });
+    }
+
+    let directory_offset = zip.len();
+
+    for record in &records {
+        write_central_directory_record(&mut zip, record);
+    }
+
+    let end = EndOfCentralDirectory {
+        offset: zip.len(),
+        entries: u16::try_from(records.len())
+            .map_err(|_| anyhow::anyhow!("ZIP64 archives are not supported"))?,
+        directory_size: (zip.len() - directory_offset) as u32,
+        directory_offset: directory_offset as u32,
+        comment: comment.to_vec(),
+    };
+    write_end_of_central_directory(&mut zip, &end);
+
+    Ok(zip)
+}
@@ -0,0 +1,197 @@
+// Unpacks every CRX file of a corpus, plus fixtures reproducing the quirks seen in the wild,
+// with both our parser and the Node unzip-crx-3 package, and reports where they disagree.
+// Needs Node and the package:
+//
+//     npm install --prefix uncrx/tests/differential
+//     UNCRX_DIFFERENTIAL_CORPUS=$PWD/corpus cargo test -p uncrx --features test-support \
+//         --test differential -- --ignored
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use uncrx::{
+    archive::unpack_zip,
+    helpers::{get_sha256, is_crx_path, parse_crx},
+    test_support::{build_fixture, generate_test_key, FixtureQuirk},
+};
+
+const REFERENCE_SCRIPT: &str = "tests/differential/reference.js";
+
+// Unpacked files by path relative to the output directory, with the SHA-256 of their content
+type Tree = BTreeMap<String, String>;
+
+fn unpack_with_reference(crx: &Path, output: &Path) -> Result<Tree, String> {
+    let result = Command::new("node")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join(REFERENCE_SCRIPT))
+        .arg(crx)
+        .arg(output)
+        .output()
+        .expect("Failed to run node");
+
+    match result.status.success() {
+        true => Ok(hash_tree(output)),
+        false => Err(String::from_utf8_lossy(&result.stderr).trim().to_string()),
+    }
+}
+
+fn unpack_with_uncrx(crx: &Path, output: &Path) -> Result<Tree, String> {
+    let data = fs::read(crx).map_err(|error| error.to_string())?;
+
+    parse_crx(&data)
+        .and_then(|extension| unpack_zip(&extension.zip, output))
+        .map(|_| hash_tree(output))
+        .map_err(|error| error.to_string())
+}
+
+fn hash_tree(root: &Path) -> Tree {
+    let mut tree = Tree::new();
+    let mut directories = vec![root.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory).expect("Failed to read directory") {
+            let path = entry.expect("Failed to read directory entry").path();
+
+            if path.is_dir() {
+                directories.push(path);
+                continue;
+            }
+
+            let name = path
+                .strip_prefix(root)
+                .expect("Entries are inside the root")
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            tree.insert(
+                name,
+                get_sha256(&fs::read(&path).expect("Failed to read file")),
+            );
+        }
+    }
+
+    tree
+}
+
+// Refusing a file both parsers refuse is fine, the reasons are worded differently anyway
+fn compare(crx: &Path, work_dir: &Path) -> Vec<String> {
+    let ours = unpack_with_uncrx(crx, &work_dir.join("uncrx"));
+    let reference = unpack_with_reference(crx, &work_dir.join("reference"));
+
+    let _ = fs::remove_dir_all(work_dir);
+
+    match (ours, reference) {
+        (Err(_), Err(_)) => vec![],
+        (Err(error), Ok(_)) => vec![format!(
+            "only unzip-crx-3 unpacks it, we fail with: {}",
+            error
+        )],
+        (Ok(_), Err(error)) => vec![format!(
+            "only we unpack it, unzip-crx-3 fails with: {}",
+            error
+        )],
+        (Ok(ours), Ok(reference)) => {
+            let mut divergences = vec![];
+
+            for (name, hash) in &ours {
+                match reference.get(name) {
+                    None => divergences.push(format!("{} is only unpacked by us", name)),
+                    Some(other) if other != hash => {
+                        divergences.push(format!("{} has a different content", name))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            for name in reference.keys().filter(|name| !ours.contains_key(*name)) {
+                divergences.push(format!("{} is only unpacked by unzip-crx-3", name));
+            }
+
+            divergences
+        }
+    }
+}
+
+fn write_fixtures(directory: &Path) -> Vec<PathBuf> {
+    let key = generate_test_key().expect("Failed to generate key");
+    let quirks = [
+        vec![],
+        vec![FixtureQuirk::ZeroLengthKey],
+        vec![FixtureQuirk::Entries(200)],
+        vec![FixtureQuirk::TrailingGarbage(1000)],
+        vec![FixtureQuirk::Zip64],
+    ];
+
+    fs::create_dir_all(directory).expect("Failed to create directory");
+
+    [2, 3]
+        .iter()
+        .flat_map(|version| quirks.iter().map(move |quirks| (*version, quirks)))
+        .map(|(version, quirks)| {
+            let name = quirks
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("+");
+            let path = directory.join(format!("crx{}-{}.crx", version, name));
+
+            let crx = build_fixture(version, quirks, &key).expect("Failed to build fixture");
+            fs::write(&path, crx).expect("Failed to write fixture");
+
+            path
+        })
+        .collect()
+}
+
+fn find_corpus_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory).expect("Failed to read corpus") {
+            let path = entry.expect("Failed to read corpus entry").path();
+
+            match path.is_dir() {
+                true => directories.push(path),
+                false if is_crx_path(&path) => files.push(path),
+                false => {}
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[test]
+#[ignore = "needs Node and unzip-crx-3, see the top of the file"]
+fn matches_reference_unpacker() {
+    let temp_dir = env::temp_dir().join(format!("uncrx-differential-{}", process::id()));
+
+    let mut files = write_fixtures(&temp_dir.join("fixtures"));
+
+    if let Some(corpus) = env::var_os("UNCRX_DIFFERENTIAL_CORPUS") {
+        files.extend(find_corpus_files(Path::new(&corpus)));
+    }
+
+    let report: Vec<String> = files
+        .iter()
+        .flat_map(|file| {
+            compare(file, &temp_dir.join("work"))
+                .into_iter()
+                .map(move |divergence| format!("{}: {}", file.display(), divergence))
+        })
+        .collect();
+
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    assert!(
+        report.is_empty(),
+        "Divergences from unzip-crx-3, {} across {} files:\n{}",
+        report.len(),
+        files.len(),
+        report.join("\n")
+    );
+}
@@ -0,0 +1,10 @@
+// Unpacks a CRX file with unzip-crx-3, which most Node tooling relies on. Exits with 1 and the
+// reason on stderr when the package refuses the file
+const unzip = require("unzip-crx-3");
+
+const [crx, output] = process.argv.slice(2);
+
+unzip(crx, output).catch((error) => {
+  console.error(error instanceof Error ? error.message : String(error));
+  process.exit(1);
+});